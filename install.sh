@@ -74,6 +74,12 @@ chmod +x "$INSTALL_DIR/command_line_helper"
 ln -sf "$INSTALL_DIR/command_line_helper" "$INSTALL_DIR/clh"
 echo -e "${GREEN}✓ Installed to: $INSTALL_DIR${NC}"
 
+# Install man pages
+MAN_DIR="$HOME/.local/share/man/man1"
+mkdir -p "$MAN_DIR"
+"$INSTALL_DIR/command_line_helper" man --out-dir "$MAN_DIR"
+echo -e "${GREEN}✓ Man pages installed to: $MAN_DIR${NC}"
+
 # Add to PATH if not already there
 echo ""
 echo -e "${CYAN}→ Configuring PATH...${NC}"
@@ -91,6 +97,11 @@ if [ -n "$SHELL_RC" ]; then
         echo "# Command Line Helper" >> "$SHELL_RC"
         echo "export PATH=\"\$PATH:$INSTALL_DIR\"" >> "$SHELL_RC"
         echo "alias clh='command_line_helper'" >> "$SHELL_RC"
+        if [ -n "$BASH_VERSION" ]; then
+            echo "source <(command_line_helper completions bash)" >> "$SHELL_RC"
+        else
+            echo "source <(command_line_helper completions zsh)" >> "$SHELL_RC"
+        fi
         echo -e "${GREEN}✓ Added to PATH in $SHELL_RC${NC}"
         echo -e "${YELLOW}  Run: source $SHELL_RC${NC}"
     else
@@ -0,0 +1,1019 @@
+// cli.rs
+
+pub mod alias;
+pub mod complete;
+pub mod legacy;
+
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::engine::SubcommandCandidates;
+use clap_complete::{ArgValueCandidates, ArgValueCompleter};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use command_line_helper::input::Input;
+use command_line_helper::modules::duplicate_module::{DuplicateAction, Keep};
+use command_line_helper::modules::export_module::ExportFormat;
+use command_line_helper::modules::hash_module::HashAlgorithm;
+use command_line_helper::modules::os_modules::{
+    parse_time, EntryType, FileFilters, SizeRange, SortKey,
+};
+use command_line_helper::modules::search_data_module::{MatchOptions, Report, SearchOptions};
+use command_line_helper::modules::sync_module::CompareMode;
+use command_line_helper::modules::text_module::TextCase;
+use command_line_helper::modules::transfer_module::ConflictPolicy;
+use command_line_helper::modules::walk_module::WalkOptions;
+use command_line_helper::output::OutputFormat;
+use complete::Shell;
+use encoding_rs::Encoding;
+use legacy::LegacyArgs;
+
+#[derive(Parser, Debug)]
+#[command(version, about, add = SubcommandCandidates::new(complete::plugins))]
+pub struct Args {
+    /// Navigates to the specified path before running the command.
+    #[clap(short = 'g', long, global = true)]
+    pub goto: Option<PathBuf>,
+
+    /// Enables verbose mode, providing detailed logs during execution.
+    #[clap(short = 'v', long, global = true)]
+    pub verbose: bool,
+
+    /// Outputs trace logs to a file for debugging purposes.
+    #[clap(short = 'O', long = "logs-out", global = true)]
+    pub log_out: bool,
+
+    /// Output format for command results.
+    #[clap(long = "output", global = true, value_enum, default_value = "text")]
+    pub output_format: OutputFormat,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Pre-subcommand flat flags, kept as hidden aliases for existing scripts.
+    #[command(flatten)]
+    pub legacy: LegacyArgs,
+}
+
+impl Args {
+    /// Resolve the command to run, falling back to the legacy flat flags.
+    /// Mixing a subcommand with a legacy operation flag is a usage error.
+    pub fn into_command(self) -> Result<Option<Command>, clap::Error> {
+        match (self.command, self.legacy.into_command()) {
+            (Some(_), Some(_)) => Err(Args::command().error(
+                ErrorKind::ArgumentConflict,
+                "legacy operation flags cannot be combined with a subcommand",
+            )),
+            (command, legacy) => Ok(command.or(legacy)),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Prints a greeting for the given name.
+    Greet { name: String },
+
+    /// Downloads YouTube videos or shorts from the provided URL.
+    Download {
+        url: String,
+
+        /// Quality of the download.
+        #[clap(long, default_value = "best", value_parser = ["best", "worst", "audio"])]
+        quality: String,
+
+        /// Directory the video is saved to.
+        #[clap(short = 'o', long, default_value = ".")]
+        output_path: PathBuf,
+    },
+
+    /// Searches for files matching a glob pattern.
+    Search {
+        pattern: String,
+
+        /// Directory to search in.
+        #[clap(short = 'o', long, default_value = ".")]
+        output_path: PathBuf,
+
+        /// Limits the number of results (0 = unlimited).
+        #[clap(short = 'l', long, default_value = "0")]
+        limit: usize,
+
+        #[command(flatten)]
+        filters: FilterArgs,
+
+        /// Show the size and modification time of each result.
+        #[clap(long)]
+        long: bool,
+
+        /// Write the results as JSON lines, vimgrep lines or SARIF instead
+        /// of using --output; exports are never colored.
+        #[clap(long, value_enum, value_name = "FORMAT")]
+        export: Option<ExportFormat>,
+    },
+
+    /// Finds files by fuzzy matching their paths.
+    Find {
+        /// Characters of the path, in order; e.g. `srmain` finds src/main.rs.
+        #[clap(required_unless_present = "interactive")]
+        query: Option<String>,
+
+        /// Directory to search in.
+        #[clap(short = 'o', long, default_value = ".")]
+        output_path: PathBuf,
+
+        /// Number of results to show (0 = all).
+        #[clap(short = 'l', long, default_value = "20")]
+        limit: usize,
+
+        /// Narrow the results as you type and print the chosen file.
+        #[clap(short = 'p', long)]
+        interactive: bool,
+
+        #[command(flatten)]
+        walk: WalkArgs,
+    },
+
+    /// Searches for specific data inside files.
+    DataSearch {
+        /// Text to search for (a regular expression with --regex).
+        #[clap(required_unless_present = "patterns")]
+        data: Option<String>,
+
+        #[command(flatten)]
+        matching: MatchArgs,
+
+        /// Directory to search in.
+        #[clap(short = 'o', long, default_value = ".")]
+        output_path: PathBuf,
+
+        /// Depth of directories to descend into.
+        #[clap(long = "root-level", default_value = "3")]
+        root_level: usize,
+
+        /// Limits the number of matching lines per file (0 = unlimited).
+        #[clap(short = 'l', long, default_value = "0")]
+        limit: usize,
+
+        #[command(flatten)]
+        report: ReportArgs,
+
+        #[command(flatten)]
+        walk: WalkArgs,
+
+        /// Print the matching lines of binary files (containing NUL bytes)
+        /// instead of only noting that they match.
+        #[clap(long)]
+        binary: bool,
+
+        /// Decode files with this encoding (e.g. latin1, utf-16le) instead
+        /// of detecting it.
+        #[clap(long, value_parser = parse_encoding)]
+        encoding: Option<&'static Encoding>,
+
+        /// Skip the files that the index built with `index build` rules
+        /// out; the index is brought up to date first.
+        #[clap(long, conflicts_with = "encoding")]
+        index: bool,
+
+        /// Also search the files inside .zip and .tar.gz archives, reporting
+        /// them as archive.zip!inner/path. Members over 256 MiB are skipped.
+        #[clap(long)]
+        archives: bool,
+    },
+
+    /// Replaces text in the files below a directory.
+    Replace {
+        /// Text to find (a regular expression with --regex).
+        find: String,
+
+        /// Replacement text; with --regex, `$1` or `${name}` insert capture groups.
+        replacement: String,
+
+        #[command(flatten)]
+        matching: MatchArgs,
+
+        /// Print a unified diff of the changes without writing any file.
+        #[clap(long, conflicts_with = "interactive")]
+        dry_run: bool,
+
+        /// Ask before applying each hunk.
+        #[clap(short = 'p', long)]
+        interactive: bool,
+
+        /// Directory to replace in.
+        #[clap(short = 'o', long, default_value = ".")]
+        output_path: PathBuf,
+
+        /// Depth of directories to descend into.
+        #[clap(long = "root-level", default_value = "3")]
+        root_level: usize,
+
+        #[command(flatten)]
+        walk: WalkArgs,
+    },
+
+    /// Copies files and directories (recursively) to the destination directory.
+    Copy {
+        /// Files, directories or glob patterns.
+        #[clap(required = true)]
+        sources: Vec<PathBuf>,
+
+        /// Destination directory.
+        #[clap(short = 'o', long, default_value = ".")]
+        output_path: PathBuf,
+
+        /// New name for the copy of a single source.
+        #[clap(short = 'n', long)]
+        name: Option<String>,
+
+        /// Keep permissions and access and modification times.
+        #[clap(short = 'p', long)]
+        preserve: bool,
+
+        /// What to do with files that already exist at the destination.
+        #[clap(long, value_enum, default_value = "overwrite")]
+        on_conflict: ConflictPolicy,
+    },
+
+    /// Moves files and directories to the destination directory.
+    Move {
+        /// Files, directories or glob patterns.
+        #[clap(required = true)]
+        sources: Vec<PathBuf>,
+
+        /// Destination directory.
+        #[clap(short = 'o', long, default_value = ".")]
+        output_path: PathBuf,
+
+        /// New name for a single moved source.
+        #[clap(short = 'n', long)]
+        name: Option<String>,
+
+        /// What to do with files that already exist at the destination.
+        #[clap(long, value_enum, default_value = "overwrite")]
+        on_conflict: ConflictPolicy,
+    },
+
+    /// Finds groups of identical files and optionally deletes or hard-links the extra copies.
+    Duplicates {
+        /// Directory to scan.
+        #[clap(default_value = ".")]
+        path: PathBuf,
+
+        /// Ignore files smaller than this many bytes.
+        #[clap(long, default_value = "1")]
+        min_size: u64,
+
+        /// Delete the duplicates, or replace them with hard links to the kept file.
+        #[clap(long, value_enum)]
+        action: Option<DuplicateAction>,
+
+        /// Which file of each group to keep.
+        #[clap(long, value_enum, default_value = "oldest")]
+        keep: Keep,
+
+        /// Carry out --action; without it, only show what would be done.
+        #[clap(long, requires = "action")]
+        apply: bool,
+
+        /// Only scan files matching this glob; may be repeated.
+        #[clap(long, value_name = "GLOB")]
+        include: Vec<String>,
+
+        /// Skip files and directories matching this glob; may be repeated.
+        /// Hidden and git-ignored files are scanned unless excluded.
+        #[clap(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+    },
+
+    /// Renames many files with a regex or a template; previews, checks collisions and can be undone.
+    Rename {
+        /// Files to rename, or glob patterns.
+        #[clap(required_unless_present = "undo")]
+        files: Vec<PathBuf>,
+
+        /// Regular expression to find in each file name.
+        #[clap(long, requires = "replace", conflicts_with = "template")]
+        find: Option<String>,
+
+        /// Replacement for --find; `$1` or `${name}` insert capture groups.
+        #[clap(long, requires = "find")]
+        replace: Option<String>,
+
+        /// New file name from {name}, {ext}, {n}, {n:03} and {mtime:%Y%m%d}.
+        #[clap(short = 't', long)]
+        template: Option<String>,
+
+        /// Convert the new name, without its extension, to this case.
+        #[clap(long, value_enum)]
+        case: Option<TextCase>,
+
+        /// First value of {n}.
+        #[clap(long, default_value = "1")]
+        start: usize,
+
+        /// Show the new names without renaming anything.
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Reverse the last batch of renames.
+        #[clap(long, conflicts_with_all = ["files", "find", "template", "case"])]
+        undo: bool,
+    },
+
+    /// Makes a destination directory match a source directory (one-way); links stay links.
+    Sync {
+        /// Directory to copy from.
+        source: PathBuf,
+
+        /// Directory to bring up to date; created if needed.
+        destination: PathBuf,
+
+        /// How to decide whether a file changed.
+        #[clap(long, value_enum, default_value = "quick")]
+        compare: CompareMode,
+
+        /// Delete files, directories and links at the destination that are not in the source.
+        #[clap(long)]
+        delete: bool,
+
+        /// Leave files and directories matching this glob alone; may be repeated.
+        #[clap(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+
+        /// List the planned creates, updates and deletes without changing anything.
+        #[clap(long)]
+        dry_run: bool,
+    },
+
+    /// Runs a project action (run, build, test, ...), detecting the language if not given.
+    Project {
+        #[clap(add = ArgValueCompleter::new(complete::project_actions))]
+        action: String,
+
+        /// Programming language of the project (enhances performance).
+        #[clap(short = 'L', long, add = ArgValueCandidates::new(complete::languages))]
+        language: Option<String>,
+    },
+
+    /// Environment variable operations.
+    #[command(subcommand)]
+    Env(EnvCommand),
+
+    /// File and string hashing.
+    #[command(subcommand)]
+    Hash(HashCommand),
+
+    /// Text encoding and conversion.
+    #[command(subcommand)]
+    Text(TextCommand),
+
+    /// System information.
+    #[command(subcommand)]
+    System(SystemCommand),
+
+    /// Git repository operations.
+    #[command(subcommand)]
+    Git(GitCommand),
+
+    /// Zip and tar.gz archive operations.
+    #[command(subcommand)]
+    Archive(ArchiveCommand),
+
+    /// Trigram indexes that speed up `data-search --index`.
+    #[command(subcommand)]
+    Index(IndexCommand),
+
+    /// JSON and YAML formatting.
+    #[command(subcommand)]
+    Format(FormatCommand),
+
+    /// Network utilities.
+    #[command(subcommand)]
+    Net(NetCommand),
+
+    /// Configuration management.
+    #[command(subcommand)]
+    Config(ConfigCommand),
+
+    /// External plugins (`clh-<name>` executables on PATH).
+    #[command(subcommand)]
+    Plugins(PluginsCommand),
+
+    /// Starts the web dashboard server.
+    Server {
+        /// Port for the web server.
+        #[clap(short = 'p', long, default_value = "3000")]
+        port: u16,
+    },
+
+    /// Runs the helper commands listed in a YAML script, in order.
+    RunScript {
+        /// Script file (see README for the format).
+        file: PathBuf,
+
+        /// Keep running the remaining steps after a step fails.
+        #[clap(long)]
+        keep_going: bool,
+
+        /// Define or override a script variable (format: KEY=VALUE).
+        #[clap(long = "var", value_parser = parse_key_value)]
+        vars: Vec<(String, String)>,
+    },
+
+    /// Opens an interactive shell (also the default when no command is given).
+    Shell,
+
+    /// Prints the shell completion script (e.g. `source <(clh completions bash)`).
+    Completions {
+        #[clap(value_enum)]
+        shell: Shell,
+    },
+
+    /// Prints the man page in roff format.
+    Man {
+        /// Write one page per command into this directory instead.
+        #[clap(long = "out-dir")]
+        out_dir: Option<PathBuf>,
+    },
+
+    /// Any other command runs the `clh-<name>` plugin with the remaining arguments.
+    #[command(external_subcommand)]
+    External(Vec<String>),
+}
+
+/// Pattern options of `data-search` and `replace`.
+#[derive(clap::Args, Debug, Default)]
+pub struct MatchArgs {
+    /// Another pattern to search for; text matching any of the patterns matches.
+    #[clap(short = 'e', long = "pattern")]
+    pub patterns: Vec<String>,
+
+    /// Treat the patterns as regular expressions.
+    #[clap(short = 'E', long)]
+    pub regex: bool,
+
+    /// Ignore case distinctions.
+    #[clap(short = 'i', long)]
+    pub ignore_case: bool,
+
+    /// Only match whole words.
+    #[clap(short = 'w', long)]
+    pub word: bool,
+
+    /// Show the lines that do not match.
+    #[clap(long)]
+    pub invert: bool,
+}
+
+impl MatchArgs {
+    pub fn options(&self) -> MatchOptions {
+        MatchOptions {
+            regex: self.regex,
+            ignore_case: self.ignore_case,
+            whole_word: self.word,
+            invert: self.invert,
+        }
+    }
+}
+
+/// Filters and ordering of `search`.
+#[derive(clap::Args, Debug, Default)]
+pub struct FilterArgs {
+    /// Maximum directory depth; 1 searches only the given directory (0 = unlimited).
+    #[clap(long, default_value = "0")]
+    pub depth: usize,
+
+    /// Only report entries of this type.
+    #[clap(short = 't', long = "type", value_enum)]
+    pub entry_type: Option<EntryType>,
+
+    /// Only report files with a size in this range, e.g. 10K..2M, 1G.. or ..512.
+    #[clap(long, value_parser = parse_size_range)]
+    pub size: Option<SizeRange>,
+
+    /// Only report entries modified at or after this time
+    /// (YYYY-MM-DD, YYYY-MM-DD HH:MM or an age like 7d).
+    #[clap(long, value_name = "TIME", value_parser = parse_time)]
+    pub modified_since: Option<SystemTime>,
+
+    /// Only report entries modified before this time.
+    #[clap(long, value_name = "TIME", value_parser = parse_time)]
+    pub modified_before: Option<SystemTime>,
+
+    /// Only report files with one of these extensions, e.g. rs,toml.
+    #[clap(long = "ext", value_delimiter = ',')]
+    pub extensions: Vec<String>,
+
+    /// Leave out entries matching this glob; may be repeated.
+    #[clap(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// Sort the results.
+    #[clap(long, value_enum)]
+    pub sort: Option<SortKey>,
+
+    /// Reverse the order of the results.
+    #[clap(long)]
+    pub reverse: bool,
+
+    /// Also look inside .zip and .tar.gz files, reporting their entries as
+    /// archive.zip!inner/path.
+    #[clap(long)]
+    pub archives: bool,
+}
+
+impl FilterArgs {
+    pub fn filters(&self, limit: usize) -> FileFilters {
+        FileFilters {
+            max_depth: self.depth,
+            limit,
+            entry_type: self.entry_type,
+            size: self.size,
+            modified_since: self.modified_since,
+            modified_before: self.modified_before,
+            extensions: self.extensions.clone(),
+            exclude: self.exclude.clone(),
+            sort: self.sort,
+            reverse: self.reverse,
+            archives: self.archives,
+        }
+    }
+}
+
+/// Output options of `data-search`.
+#[derive(clap::Args, Debug, Default)]
+pub struct ReportArgs {
+    /// Show NUM lines before each matching line.
+    #[clap(short = 'B', long, value_name = "NUM")]
+    pub before_context: Option<usize>,
+
+    /// Show NUM lines after each matching line.
+    #[clap(short = 'A', long, value_name = "NUM")]
+    pub after_context: Option<usize>,
+
+    /// Show NUM lines before and after each matching line.
+    #[clap(short = 'C', long, value_name = "NUM")]
+    pub context: Option<usize>,
+
+    /// Only print the number of matching lines per file.
+    #[clap(short = 'c', long, conflicts_with = "files_with_matches")]
+    pub count: bool,
+
+    /// Only print the names of files with matches.
+    #[clap(long)]
+    pub files_with_matches: bool,
+
+    /// Print the total number of matching lines and files at the end.
+    #[clap(long)]
+    pub summary: bool,
+
+    /// Show the column of the first match on each line.
+    #[clap(long)]
+    pub column: bool,
+
+    /// Limits the number of matching lines in the whole search (0 = unlimited).
+    #[clap(long, default_value = "0")]
+    pub total_limit: usize,
+
+    /// Write the results as JSON lines, vimgrep lines or SARIF instead of
+    /// using --output; exports are never colored.
+    #[clap(long, value_enum, value_name = "FORMAT")]
+    pub export: Option<ExportFormat>,
+}
+
+impl ReportArgs {
+    /// The search options set by these flags; `-A` and `-B` win over `-C`.
+    pub fn options(&self) -> SearchOptions {
+        let context = self.context.unwrap_or(0);
+        SearchOptions {
+            total_limit: self.total_limit,
+            before_context: self.before_context.unwrap_or(context),
+            after_context: self.after_context.unwrap_or(context),
+            report: if self.count {
+                Report::Count
+            } else if self.files_with_matches {
+                Report::Files
+            } else {
+                Report::Lines
+            },
+            column: self.column,
+            summary: self.summary,
+            ..SearchOptions::default()
+        }
+    }
+}
+
+/// Options that choose which files `data-search`, `replace` and `find` read.
+#[derive(clap::Args, Debug, Default)]
+pub struct WalkArgs {
+    /// Only search files matching this glob; may be repeated.
+    #[clap(long, value_name = "GLOB")]
+    pub include: Vec<String>,
+
+    /// Skip files and directories matching this glob; may be repeated.
+    #[clap(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// Search hidden files and directories too.
+    #[clap(long)]
+    pub hidden: bool,
+
+    /// Do not respect .gitignore, .ignore and git exclude files.
+    #[clap(long)]
+    pub no_ignore: bool,
+
+    /// Number of search threads (0 = one per CPU).
+    #[clap(long, default_value = "0")]
+    pub threads: usize,
+}
+
+impl WalkArgs {
+    /// `root_level` counts directory levels below the root, so files
+    /// directly in the root are at level 0.
+    pub fn options(&self, root_level: usize) -> WalkOptions {
+        WalkOptions {
+            max_depth: Some(root_level + 1),
+            hidden: self.hidden,
+            no_ignore: self.no_ignore,
+            include: self.include.clone(),
+            exclude: self.exclude.clone(),
+            threads: self.threads,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum EnvCommand {
+    /// List all environment variables.
+    List,
+    /// Get a specific environment variable.
+    Get { key: String },
+    /// Set an environment variable for this session (format: KEY=VALUE).
+    Set {
+        #[clap(value_parser = parse_key_value)]
+        pair: (String, String),
+    },
+    /// Load environment variables from a file.
+    Load { file: PathBuf },
+    /// Export environment variables to a file.
+    Export {
+        file: PathBuf,
+
+        /// Only export variables whose name contains this text.
+        #[clap(long)]
+        filter: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum HashCommand {
+    /// Calculate the hash of a file, optionally verifying it.
+    File {
+        path: PathBuf,
+
+        /// Hash algorithm (md5, sha256, sha512).
+        #[clap(short = 'a', long, default_value = "sha256", value_parser = parse_hash_algorithm)]
+        algo: HashAlgorithm,
+
+        /// Expected hash to verify the file against.
+        #[clap(long)]
+        verify: Option<String>,
+    },
+    /// Calculate the hash of a string.
+    String {
+        text: String,
+
+        /// Hash algorithm (md5, sha256, sha512).
+        #[clap(short = 'a', long, default_value = "sha256", value_parser = parse_hash_algorithm)]
+        algo: HashAlgorithm,
+    },
+    /// Calculate all supported hashes for a file.
+    All { path: PathBuf },
+}
+
+/// Help for every text and format input argument.
+const INPUT_HELP: &str = "Input text, `-` to read stdin or `@path` to read a file";
+
+#[derive(Subcommand, Debug)]
+pub enum TextCommand {
+    /// Base64 encode text (binary-safe for stdin and files).
+    Base64Encode {
+        #[clap(help = INPUT_HELP)]
+        text: Input,
+    },
+    /// Base64 decode text.
+    Base64Decode {
+        #[clap(help = INPUT_HELP)]
+        text: Input,
+        /// Write the decoded bytes to a file instead of printing them.
+        #[clap(short = 'o', long = "out")]
+        out: Option<PathBuf>,
+    },
+    /// URL encode text.
+    UrlEncode {
+        #[clap(help = INPUT_HELP)]
+        text: Input,
+    },
+    /// URL decode text.
+    UrlDecode {
+        #[clap(help = INPUT_HELP)]
+        text: Input,
+    },
+    /// Convert text to another case.
+    Case {
+        #[clap(value_enum)]
+        case: TextCase,
+        #[clap(help = INPUT_HELP)]
+        text: Input,
+    },
+    /// Show line, word and character counts.
+    Stats {
+        #[clap(help = INPUT_HELP)]
+        text: Input,
+    },
+    /// Replace every occurrence of a string.
+    Replace {
+        find: String,
+        replace: String,
+        #[clap(help = INPUT_HELP)]
+        text: Input,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SystemCommand {
+    /// Display all system information.
+    Info,
+    /// Display CPU information.
+    Cpu,
+    /// Display memory information.
+    Memory,
+    /// Display disk information.
+    Disk,
+    /// Display network information.
+    Network,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum GitCommand {
+    /// Show the working tree status.
+    Status,
+    /// Clone a repository.
+    Clone {
+        url: String,
+
+        /// Directory to clone into.
+        #[clap(short = 'o', long, default_value = ".")]
+        output_path: PathBuf,
+    },
+    /// Create a branch from HEAD.
+    Branch {
+        #[clap(add = ArgValueCandidates::new(complete::branches))]
+        name: String,
+    },
+    /// Stage all changes.
+    Add,
+    /// Commit staged changes.
+    Commit { message: String },
+    /// List local branches.
+    Branches,
+    /// Show recent commits.
+    Log {
+        /// Number of commits to show.
+        #[clap(default_value = "10")]
+        count: usize,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ArchiveCommand {
+    /// Zip archive operations.
+    #[command(subcommand)]
+    Zip(ArchiveAction),
+    /// Tar.gz archive operations.
+    #[command(subcommand)]
+    Tar(ArchiveAction),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ArchiveAction {
+    /// Create an archive from a file or directory.
+    Create { source: PathBuf, output: PathBuf },
+    /// Extract an archive.
+    Extract {
+        archive: PathBuf,
+
+        /// Directory to extract into.
+        #[clap(short = 'o', long, default_value = ".")]
+        output_path: PathBuf,
+    },
+    /// List the contents of an archive.
+    List { archive: PathBuf },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum IndexCommand {
+    /// Build the index of a directory, or update it for the files changed
+    /// since.
+    Build {
+        #[clap(default_value = ".")]
+        path: PathBuf,
+    },
+    /// Show what the index of a directory holds.
+    Status {
+        #[clap(default_value = ".")]
+        path: PathBuf,
+    },
+    /// Delete the index of a directory.
+    Remove {
+        #[clap(default_value = ".")]
+        path: PathBuf,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum FormatCommand {
+    /// JSON operations.
+    #[command(subcommand)]
+    Json(JsonCommand),
+    /// YAML operations.
+    #[command(subcommand)]
+    Yaml(YamlCommand),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum JsonCommand {
+    /// Pretty print JSON.
+    Pretty {
+        #[clap(help = INPUT_HELP)]
+        input: Input,
+    },
+    /// Minify JSON.
+    Minify {
+        #[clap(help = INPUT_HELP)]
+        input: Input,
+    },
+    /// Validate JSON.
+    Validate {
+        #[clap(help = INPUT_HELP)]
+        input: Input,
+    },
+    /// Convert JSON to YAML.
+    ToYaml {
+        #[clap(help = INPUT_HELP)]
+        input: Input,
+    },
+    /// Query a value by dotted path (e.g. user.name).
+    Query {
+        #[clap(help = INPUT_HELP)]
+        input: Input,
+        path: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum YamlCommand {
+    /// Pretty print YAML.
+    Pretty {
+        #[clap(help = INPUT_HELP)]
+        input: Input,
+    },
+    /// Validate YAML.
+    Validate {
+        #[clap(help = INPUT_HELP)]
+        input: Input,
+    },
+    /// Convert YAML to JSON.
+    ToJson {
+        #[clap(help = INPUT_HELP)]
+        input: Input,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum NetCommand {
+    /// Check if a port is open.
+    Port {
+        port: u16,
+
+        /// Host to check.
+        #[clap(long, default_value = "localhost")]
+        host: String,
+    },
+    /// Check that a host is reachable.
+    Ping { host: String },
+    /// Show the public IP address.
+    PublicIp,
+    /// Perform an HTTP GET request.
+    Get { url: String },
+    /// Perform an HTTP POST request.
+    Post {
+        url: String,
+
+        /// Request body.
+        #[clap(long, default_value = "")]
+        body: String,
+    },
+    /// Resolve a domain name.
+    Dns { domain: String },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PluginsCommand {
+    /// List the plugins found on PATH.
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Show the current configuration.
+    Show,
+    /// Load configuration from a file.
+    Load { file: PathBuf },
+}
+
+pub fn parse_hash_algorithm(s: &str) -> Result<HashAlgorithm, String> {
+    s.parse()
+}
+
+fn parse_size_range(s: &str) -> Result<SizeRange, String> {
+    s.parse()
+}
+
+fn parse_encoding(s: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(s.as_bytes()).ok_or_else(|| format!("unknown encoding '{}'", s))
+}
+
+fn parse_key_value(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| "expected KEY=VALUE".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        Args::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_subcommand() {
+        let args = Args::try_parse_from(["clh", "hash", "file", "a.txt", "--algo", "md5"]).unwrap();
+        match args.into_command().unwrap() {
+            Some(Command::Hash(HashCommand::File { algo, verify, .. })) => {
+                assert!(matches!(algo, HashAlgorithm::MD5));
+                assert!(verify.is_none());
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_legacy_flag() {
+        let args = Args::try_parse_from(["clh", "--git-log", "3"]).unwrap();
+        assert!(matches!(
+            args.into_command().unwrap(),
+            Some(Command::Git(GitCommand::Log { count: 3 }))
+        ));
+    }
+
+    #[test]
+    fn test_parse_external_subcommand() {
+        let args = Args::try_parse_from(["clh", "deploy", "--to", "staging"]).unwrap();
+        match args.into_command().unwrap() {
+            Some(Command::External(words)) => assert_eq!(words, ["deploy", "--to", "staging"]),
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_duplicates_apply_requires_action() {
+        assert!(Args::try_parse_from(["clh", "duplicates", "--apply"]).is_err());
+        assert!(Args::try_parse_from(["clh", "duplicates", "--action", "link", "--apply"]).is_ok());
+    }
+
+    #[test]
+    fn test_legacy_flags_are_exclusive() {
+        assert!(Args::try_parse_from(["clh", "--hash-file", "x", "--git-status"]).is_err());
+    }
+
+    #[test]
+    fn test_legacy_flag_conflicts_with_subcommand() {
+        let args = Args::try_parse_from(["clh", "--hash-file", "x", "git", "status"]).unwrap();
+        assert!(args.into_command().is_err());
+
+        let args = Args::try_parse_from(["clh", "-v", "git", "status"]).unwrap();
+        assert!(args.into_command().is_ok());
+    }
+
+    #[test]
+    fn test_legacy_helper_requires_operation() {
+        assert!(Args::try_parse_from(["clh", "--text-case", "upper"]).is_err());
+        assert!(Args::try_parse_from(["clh", "--zip-create", "out.zip"]).is_err());
+    }
+}
@@ -0,0 +1,205 @@
+// alias.rs
+//
+// `[aliases]` from config.toml. An alias replaces the first command word of
+// an invocation before clap sees it, so `clh -v build-release --extra` with
+// `build-release = "--language rust --action build"` parses as
+// `clh -v --language rust --action build --extra`. Built-in commands always
+// win over an alias of the same name.
+
+use clap::{ArgAction, CommandFactory};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+
+use super::Args;
+use crate::script::split_command_line;
+use command_line_helper::config::Alias;
+use command_line_helper::{Error, Result};
+
+/// The CLI definition with every alias listed as a command, for `--help`
+/// and completion.
+pub fn command_with_aliases(aliases: &BTreeMap<String, Alias>) -> clap::Command {
+    let mut command = Args::command();
+    for (name, alias) in aliases {
+        if command.find_subcommand(name).is_none() {
+            command = command.subcommand(
+                clap::Command::new(name.clone()).about(format!("Alias for `{}`", alias)),
+            );
+        }
+    }
+    command
+}
+
+/// Expand an alias in `args` (given without the program name). Returns the
+/// command lines to run in order; the first keeps the global options that
+/// preceded the alias, and the others are run like script steps.
+pub fn expand(args: Vec<String>, aliases: &BTreeMap<String, Alias>) -> Result<Vec<Vec<String>>> {
+    expand_line(args, aliases, &Args::command(), &mut Vec::new())
+}
+
+/// Expand the process arguments (without the program name). Returns the
+/// arguments to parse and any further command lines of a sequence. Aliases
+/// are text, so arguments that are not UTF-8 are passed through unexpanded.
+pub fn expand_invocation(
+    args: Vec<OsString>,
+    aliases: &BTreeMap<String, Alias>,
+) -> Result<(Vec<OsString>, Vec<Vec<String>>)> {
+    let Some(args) = args
+        .iter()
+        .map(|arg| arg.to_str().map(str::to_string))
+        .collect::<Option<Vec<_>>>()
+    else {
+        return Ok((args, Vec::new()));
+    };
+
+    let mut lines = expand(args, aliases)?;
+    let first = lines.remove(0).into_iter().map(OsString::from).collect();
+    Ok((first, lines))
+}
+
+/// `expanding` holds the aliases being expanded, to report loops.
+fn expand_line(
+    args: Vec<String>,
+    aliases: &BTreeMap<String, Alias>,
+    command: &clap::Command,
+    expanding: &mut Vec<String>,
+) -> Result<Vec<Vec<String>>> {
+    let Some(position) = command_position(&args, command) else {
+        return Ok(vec![args]);
+    };
+    let name = &args[position];
+    let alias = match aliases.get(name) {
+        Some(alias) if command.find_subcommand(name).is_none() => alias,
+        _ => return Ok(vec![args]),
+    };
+    if expanding.contains(name) {
+        return Err(Error::InvalidInput(format!(
+            "Alias '{}' refers to itself",
+            name
+        )));
+    }
+
+    let mut steps = alias
+        .lines()
+        .iter()
+        .map(|line| split_command_line(line))
+        .collect::<Result<Vec<_>>>()?;
+    let rest = &args[position + 1..];
+    match steps.len() {
+        0 => return Err(Error::InvalidInput(format!("Alias '{}' is empty", name))),
+        1 => {}
+        _ if !rest.is_empty() => {
+            return Err(Error::InvalidInput(format!(
+                "Alias '{}' runs several commands and takes no arguments",
+                name
+            )))
+        }
+        _ => {}
+    }
+    steps[0].splice(0..0, args[..position].iter().cloned());
+    steps[0].extend_from_slice(rest);
+
+    expanding.push(name.clone());
+    let mut lines = Vec::new();
+    for step in steps {
+        lines.extend(expand_line(step, aliases, command, expanding)?);
+    }
+    expanding.pop();
+
+    Ok(lines)
+}
+
+/// Index of the first argument that is not a global or legacy option or
+/// one of their values.
+fn command_position(args: &[String], command: &clap::Command) -> Option<usize> {
+    let takes_value = |arg: Option<&clap::Arg>| {
+        arg.is_some_and(|arg| matches!(arg.get_action(), ArgAction::Set | ArgAction::Append))
+    };
+
+    let mut index = 0;
+    while index < args.len() {
+        let arg = &args[index];
+        if arg == "--" {
+            return None;
+        } else if let Some(long) = arg.strip_prefix("--") {
+            let flag = command.get_arguments().find(|a| a.get_long() == Some(long));
+            if !long.contains('=') && takes_value(flag) {
+                index += 1;
+            }
+        } else if let Some(shorts) = arg.strip_prefix('-').filter(|shorts| !shorts.is_empty()) {
+            // In a cluster like `-vg path`, the first option that takes a
+            // value uses the rest of the word or the next argument
+            for (offset, short) in shorts.char_indices() {
+                let flag = command
+                    .get_arguments()
+                    .find(|a| a.get_short() == Some(short));
+                if takes_value(flag) {
+                    if offset + short.len_utf8() == shorts.len() {
+                        index += 1;
+                    }
+                    break;
+                }
+            }
+        } else {
+            return Some(index);
+        }
+        index += 1;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(line: &str) -> Vec<String> {
+        split_command_line(line).unwrap()
+    }
+
+    #[test]
+    fn test_expand_alias() {
+        let aliases = BTreeMap::from([
+            (
+                "build-release".to_string(),
+                Alias::Command("--language rust --action build".to_string()),
+            ),
+            (
+                "ship".to_string(),
+                Alias::Sequence(vec!["build-release".to_string(), "git status".to_string()]),
+            ),
+            ("git".to_string(), Alias::Command("system info".to_string())),
+        ]);
+
+        assert_eq!(
+            expand(words("-g ../app -v build-release --verbose"), &aliases).unwrap(),
+            vec![words(
+                "-g ../app -v --language rust --action build --verbose"
+            )]
+        );
+        assert_eq!(
+            expand(words("--output json ship"), &aliases).unwrap(),
+            vec![
+                words("--output json --language rust --action build"),
+                words("git status")
+            ]
+        );
+        // Built-in commands are never replaced
+        assert_eq!(
+            expand(words("git log"), &aliases).unwrap(),
+            vec![words("git log")]
+        );
+        assert!(expand(words("ship now"), &aliases).is_err());
+    }
+
+    #[test]
+    fn test_alias_loop() {
+        let aliases = BTreeMap::from([
+            ("a".to_string(), Alias::Command("b".to_string())),
+            ("b".to_string(), Alias::Command("a".to_string())),
+        ]);
+        assert!(matches!(
+            expand(words("a"), &aliases),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+}
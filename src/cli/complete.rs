@@ -0,0 +1,185 @@
+// complete.rs
+//
+// Shell completion and the man page, both generated from the `Args`
+// definition. Completion is dynamic: the registration script calls the
+// binary back with `COMPLETE=<shell>` set, so languages, project actions,
+// branch names and plugins are looked up when tab is pressed.
+
+use clap::{CommandFactory, ValueEnum};
+use clap_complete::env::Shells;
+use clap_complete::{CompleteEnv, CompletionCandidate};
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsStr;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::alias::command_with_aliases;
+use super::Args;
+use command_line_helper::config::Alias;
+use command_line_helper::modules::git_module::git_list_branches;
+use command_line_helper::modules::language_identifier_module::identify_project_type;
+use command_line_helper::modules::language_module::map_language_actions;
+use command_line_helper::modules::plugin_module::discover_plugins;
+use command_line_helper::utilities::clean_language_string;
+use command_line_helper::{Error, Result};
+
+/// Environment variable that switches the binary into completion mode.
+const COMPLETE_VAR: &str = "COMPLETE";
+
+/// Names the helper is installed under; install.sh links `clh` to the binary.
+const BIN_NAMES: [&str; 2] = ["command_line_helper", "clh"];
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+impl Shell {
+    fn name(self) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+            Shell::Powershell => "powershell",
+        }
+    }
+}
+
+/// Answer the shell's completion request and exit, if this run is one.
+/// Must run before anything is written to stdout.
+pub fn handle_completion_request(aliases: &BTreeMap<String, Alias>) {
+    CompleteEnv::with_factory(|| command_with_aliases(aliases))
+        .var(COMPLETE_VAR)
+        .complete();
+}
+
+/// Write the script that registers dynamic completion with `shell`.
+pub fn write_registration(shell: Shell, out: &mut dyn Write) -> Result<()> {
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(shell.name())
+        .ok_or_else(|| Error::InvalidInput(format!("Unsupported shell '{}'", shell.name())))?;
+
+    // Call back into the binary the way it was invoked, as clap_complete does
+    let mut program = PathBuf::from(env::args_os().next().unwrap_or_default());
+    if program.components().count() > 1 {
+        program = env::current_dir()?.join(program);
+    }
+    let program = program.to_string_lossy();
+
+    for bin in BIN_NAMES {
+        completer.write_registration(COMPLETE_VAR, bin, bin, &program, out)?;
+    }
+    Ok(())
+}
+
+/// Write the man page to `out`, or one page per command into `out_dir`.
+pub fn write_man_page(out_dir: Option<&Path>, out: &mut dyn Write) -> Result<()> {
+    match out_dir {
+        Some(dir) => clap_mangen::generate_to(Args::command(), dir)?,
+        None => clap_mangen::Man::new(Args::command()).render(out)?,
+    }
+    Ok(())
+}
+
+/// Supported project languages.
+pub fn languages() -> Vec<CompletionCandidate> {
+    let mut languages: Vec<_> = map_language_actions().into_keys().collect();
+    languages.sort_unstable();
+    languages
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+/// Actions for the language given with `-L`, or for the project in the
+/// current directory, or for every language when neither is known.
+pub fn project_actions(current: &OsStr) -> Vec<CompletionCandidate> {
+    let language = language_on_command_line()
+        .or_else(|| {
+            identify_project_type(".")
+                .ok()
+                .map(|project_type| project_type.to_string())
+        })
+        .map(|language| clean_language_string(&language));
+
+    let mut actions: Vec<_> = map_language_actions()
+        .into_iter()
+        .filter(|(name, _)| language.as_deref().is_none_or(|language| language == *name))
+        .flat_map(|(_, actions)| actions)
+        .filter(|action| action.starts_with(&*current.to_string_lossy()))
+        .collect();
+    actions.sort_unstable();
+    actions.dedup();
+    actions.into_iter().map(CompletionCandidate::new).collect()
+}
+
+/// Local branches of the repository in the current directory.
+pub fn branches() -> Vec<CompletionCandidate> {
+    git_list_branches(Path::new("."))
+        .map(|list| list.branches)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|branch| CompletionCandidate::new(branch.name))
+        .collect()
+}
+
+/// Plugins on PATH, completed like built-in subcommands.
+pub fn plugins() -> Vec<CompletionCandidate> {
+    discover_plugins()
+        .plugins
+        .into_iter()
+        .map(|plugin| {
+            CompletionCandidate::new(plugin.name)
+                .help(Some(plugin.path.display().to_string().into()))
+        })
+        .collect()
+}
+
+/// The `-L`/`--language` value among the words being completed. Custom
+/// completers only see the current word, so read the rest from argv.
+fn language_on_command_line() -> Option<String> {
+    let words: Vec<String> = env::args().skip_while(|arg| arg != "--").collect();
+
+    words.iter().enumerate().find_map(|(index, word)| {
+        if let Some(value) = word.strip_prefix("--language=") {
+            Some(value.to_string())
+        } else if word == "-L" || word == "--language" {
+            words
+                .get(index + 1)
+                .filter(|value| !value.is_empty())
+                .cloned()
+        } else {
+            word.strip_prefix("-L")
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_registration() {
+        let mut script = Vec::new();
+        write_registration(Shell::Bash, &mut script).unwrap();
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("COMPLETE"));
+        assert!(script.contains(" clh"));
+    }
+
+    #[test]
+    fn test_languages_include_cargo() {
+        let languages: Vec<_> = languages()
+            .into_iter()
+            .map(|candidate| candidate.get_value().to_string_lossy().into_owned())
+            .collect();
+        assert!(languages.contains(&"cargo".to_string()));
+    }
+}
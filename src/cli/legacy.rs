@@ -0,0 +1,455 @@
+// legacy.rs
+//
+// The flat flag interface from before the subcommand tree. Every flag is
+// hidden from --help and translated into the equivalent `Command`, so old
+// scripts such as `--hash-file x --hash-algo md5` keep working. At most one
+// operation flag may be given per invocation.
+
+use clap::ArgGroup;
+use std::path::PathBuf;
+
+use super::{
+    parse_hash_algorithm, ArchiveAction, ArchiveCommand, Command, ConfigCommand, EnvCommand,
+    FilterArgs, FormatCommand, GitCommand, HashCommand, JsonCommand, MatchArgs, NetCommand,
+    ReportArgs, SystemCommand, TextCase, TextCommand, WalkArgs, YamlCommand,
+};
+use command_line_helper::input::Input;
+use command_line_helper::modules::hash_module::HashAlgorithm;
+use command_line_helper::modules::transfer_module::ConflictPolicy;
+
+#[derive(clap::Args, Debug)]
+#[command(group(ArgGroup::new("operation").multiple(false)))]
+pub struct LegacyArgs {
+    #[clap(short = 'n', long, hide = true)]
+    name: Option<String>,
+
+    #[clap(short = 'u', long, hide = true, group = "operation")]
+    url: Option<String>,
+
+    #[clap(long, hide = true, default_value = "best", value_parser = ["best", "worst", "audio"])]
+    quality: String,
+
+    #[clap(short = 'o', long, hide = true, default_value = ".")]
+    output_path: PathBuf,
+
+    #[clap(short = 's', long, hide = true, group = "operation")]
+    search: Option<String>,
+
+    #[clap(short = 'S', long = "data-search", hide = true, group = "operation")]
+    data_search: Option<String>,
+
+    #[clap(short = 'l', long, hide = true, default_value = "0")]
+    limit: usize,
+
+    #[clap(short = 'L', long, hide = true, requires = "action")]
+    language: Option<String>,
+
+    #[clap(long = "root-level", hide = true, default_value = "3")]
+    root_level: usize,
+
+    #[clap(long = "copy", hide = true, group = "operation")]
+    copy: Option<PathBuf>,
+
+    #[clap(long = "move", hide = true, group = "operation")]
+    move_files: Option<PathBuf>,
+
+    #[clap(short = 'a', long, hide = true, group = "operation")]
+    action: Option<String>,
+
+    // ========== Environment Variables ==========
+    #[clap(long = "env-list", hide = true, group = "operation")]
+    env_list: bool,
+
+    #[clap(long = "env-get", hide = true, group = "operation")]
+    env_get: Option<String>,
+
+    #[clap(long = "env-set", hide = true, group = "operation", value_parser = super::parse_key_value)]
+    env_set: Option<(String, String)>,
+
+    #[clap(long = "env-load", hide = true, group = "operation")]
+    env_load: Option<PathBuf>,
+
+    #[clap(long = "env-export", hide = true, group = "operation")]
+    env_export: Option<PathBuf>,
+
+    #[clap(long = "env-filter", hide = true, requires = "env_export")]
+    env_filter: Option<String>,
+
+    // ========== Hashing ==========
+    #[clap(long = "hash-file", hide = true, group = "operation")]
+    hash_file: Option<PathBuf>,
+
+    #[clap(long = "hash-string", hide = true, group = "operation")]
+    hash_string: Option<String>,
+
+    #[clap(long = "hash-algo", hide = true, default_value = "sha256", value_parser = parse_hash_algorithm)]
+    hash_algo: HashAlgorithm,
+
+    #[clap(long = "hash-verify", hide = true, requires = "hash_file")]
+    hash_verify: Option<String>,
+
+    #[clap(long = "hash-all", hide = true, group = "operation")]
+    hash_all: Option<PathBuf>,
+
+    // ========== Text Processing ==========
+    #[clap(long = "base64-encode", hide = true, group = "operation")]
+    base64_encode: Option<Input>,
+
+    #[clap(long = "base64-decode", hide = true, group = "operation")]
+    base64_decode: Option<Input>,
+
+    #[clap(long = "url-encode", hide = true, group = "operation")]
+    url_encode: Option<Input>,
+
+    #[clap(long = "url-decode", hide = true, group = "operation")]
+    url_decode: Option<Input>,
+
+    #[clap(
+        long = "text-case",
+        hide = true,
+        group = "operation",
+        requires = "text",
+        value_enum
+    )]
+    text_case: Option<TextCase>,
+
+    #[clap(long = "text", hide = true, requires = "text_case")]
+    text: Option<Input>,
+
+    #[clap(long = "text-stats", hide = true, group = "operation")]
+    text_stats: Option<Input>,
+
+    // ========== System Information ==========
+    #[clap(long = "system-info", hide = true, group = "operation")]
+    system_info: bool,
+
+    #[clap(long = "cpu-info", hide = true, group = "operation")]
+    cpu_info: bool,
+
+    #[clap(long = "memory-info", hide = true, group = "operation")]
+    memory_info: bool,
+
+    #[clap(long = "disk-info", hide = true, group = "operation")]
+    disk_info: bool,
+
+    #[clap(long = "network-info", hide = true, group = "operation")]
+    network_info: bool,
+
+    // ========== Git Operations ==========
+    #[clap(long = "git-status", hide = true, group = "operation")]
+    git_status: bool,
+
+    #[clap(long = "git-clone", hide = true, group = "operation")]
+    git_clone: Option<String>,
+
+    #[clap(long = "git-branch", hide = true, group = "operation")]
+    git_branch: Option<String>,
+
+    #[clap(long = "git-add", hide = true, group = "operation")]
+    git_add: bool,
+
+    #[clap(long = "git-commit", hide = true, group = "operation")]
+    git_commit: Option<String>,
+
+    #[clap(long = "git-branches", hide = true, group = "operation")]
+    git_branches: bool,
+
+    #[clap(long = "git-log", hide = true, group = "operation")]
+    git_log: Option<usize>,
+
+    // ========== Archive Operations ==========
+    #[clap(
+        long = "zip-create",
+        hide = true,
+        group = "operation",
+        requires = "archive_source"
+    )]
+    zip_create: Option<PathBuf>,
+
+    #[clap(long = "zip-extract", hide = true, group = "operation")]
+    zip_extract: Option<PathBuf>,
+
+    #[clap(long = "zip-list", hide = true, group = "operation")]
+    zip_list: Option<PathBuf>,
+
+    #[clap(
+        long = "tar-create",
+        hide = true,
+        group = "operation",
+        requires = "archive_source"
+    )]
+    tar_create: Option<PathBuf>,
+
+    #[clap(long = "tar-extract", hide = true, group = "operation")]
+    tar_extract: Option<PathBuf>,
+
+    #[clap(long = "tar-list", hide = true, group = "operation")]
+    tar_list: Option<PathBuf>,
+
+    #[clap(long = "archive-source", hide = true)]
+    archive_source: Option<PathBuf>,
+
+    // ========== Format Operations ==========
+    #[clap(long = "json-format", hide = true, group = "operation")]
+    json_format: Option<Input>,
+
+    #[clap(long = "json-minify", hide = true, group = "operation")]
+    json_minify: Option<Input>,
+
+    #[clap(long = "json-validate", hide = true, group = "operation")]
+    json_validate: Option<Input>,
+
+    #[clap(long = "yaml-format", hide = true, group = "operation")]
+    yaml_format: Option<Input>,
+
+    #[clap(long = "yaml-validate", hide = true, group = "operation")]
+    yaml_validate: Option<Input>,
+
+    #[clap(long = "json-to-yaml", hide = true, group = "operation")]
+    json_to_yaml: Option<Input>,
+
+    #[clap(long = "yaml-to-json", hide = true, group = "operation")]
+    yaml_to_json: Option<Input>,
+
+    #[clap(
+        long = "json-query",
+        hide = true,
+        group = "operation",
+        requires = "query_path"
+    )]
+    json_query: Option<Input>,
+
+    #[clap(long = "query-path", hide = true, requires = "json_query")]
+    query_path: Option<String>,
+
+    // ========== Network Operations ==========
+    #[clap(long = "port-check", hide = true, group = "operation")]
+    port_check: Option<u16>,
+
+    #[clap(long = "host", hide = true, requires = "port_check")]
+    host: Option<String>,
+
+    #[clap(long = "ping", hide = true, group = "operation")]
+    ping: Option<String>,
+
+    #[clap(long = "public-ip", hide = true, group = "operation")]
+    public_ip: bool,
+
+    #[clap(long = "http-get", hide = true, group = "operation")]
+    http_get: Option<String>,
+
+    #[clap(long = "http-post", hide = true, group = "operation")]
+    http_post: Option<String>,
+
+    #[clap(long = "post-body", hide = true, requires = "http_post")]
+    post_body: Option<String>,
+
+    #[clap(long = "dns-lookup", hide = true, group = "operation")]
+    dns_lookup: Option<String>,
+
+    // ========== Configuration ==========
+    #[clap(long = "config-show", hide = true, group = "operation")]
+    config_show: bool,
+
+    #[clap(long = "config-load", hide = true, group = "operation")]
+    config_load: Option<PathBuf>,
+
+    // ========== Web Server ==========
+    #[clap(long = "server-start", hide = true, group = "operation")]
+    server_start: bool,
+
+    #[clap(long = "server-port", hide = true, default_value = "3000")]
+    server_port: u16,
+}
+
+impl LegacyArgs {
+    /// Translate the flat flags into the equivalent subcommand, if any was given.
+    pub fn into_command(self) -> Option<Command> {
+        let output_path = self.output_path;
+
+        let command = if let Some(url) = self.url {
+            Command::Download {
+                url,
+                quality: self.quality,
+                output_path,
+            }
+        } else if let Some(pattern) = self.search {
+            Command::Search {
+                pattern,
+                output_path,
+                limit: self.limit,
+                filters: FilterArgs::default(),
+                long: false,
+                export: None,
+            }
+        } else if let Some(data) = self.data_search {
+            Command::DataSearch {
+                data: Some(data),
+                matching: MatchArgs::default(),
+                output_path,
+                root_level: self.root_level,
+                limit: self.limit,
+                report: ReportArgs::default(),
+                walk: WalkArgs::default(),
+                binary: false,
+                encoding: None,
+                index: false,
+                archives: false,
+            }
+        } else if let Some(source) = self.copy {
+            Command::Copy {
+                sources: vec![source],
+                output_path,
+                name: self.name,
+                preserve: false,
+                on_conflict: ConflictPolicy::default(),
+            }
+        } else if let Some(source) = self.move_files {
+            Command::Move {
+                sources: vec![source],
+                output_path,
+                name: self.name,
+                on_conflict: ConflictPolicy::default(),
+            }
+        } else if let Some(action) = self.action {
+            Command::Project {
+                action,
+                language: self.language,
+            }
+        } else if self.env_list {
+            Command::Env(EnvCommand::List)
+        } else if let Some(key) = self.env_get {
+            Command::Env(EnvCommand::Get { key })
+        } else if let Some(pair) = self.env_set {
+            Command::Env(EnvCommand::Set { pair })
+        } else if let Some(file) = self.env_load {
+            Command::Env(EnvCommand::Load { file })
+        } else if let Some(file) = self.env_export {
+            Command::Env(EnvCommand::Export {
+                file,
+                filter: self.env_filter,
+            })
+        } else if let Some(path) = self.hash_file {
+            Command::Hash(HashCommand::File {
+                path,
+                algo: self.hash_algo,
+                verify: self.hash_verify,
+            })
+        } else if let Some(text) = self.hash_string {
+            Command::Hash(HashCommand::String {
+                text,
+                algo: self.hash_algo,
+            })
+        } else if let Some(path) = self.hash_all {
+            Command::Hash(HashCommand::All { path })
+        } else if let Some(text) = self.base64_encode {
+            Command::Text(TextCommand::Base64Encode { text })
+        } else if let Some(text) = self.base64_decode {
+            Command::Text(TextCommand::Base64Decode { text, out: None })
+        } else if let Some(text) = self.url_encode {
+            Command::Text(TextCommand::UrlEncode { text })
+        } else if let Some(text) = self.url_decode {
+            Command::Text(TextCommand::UrlDecode { text })
+        } else if let (Some(case), Some(text)) = (self.text_case, self.text) {
+            Command::Text(TextCommand::Case { case, text })
+        } else if let Some(text) = self.text_stats {
+            Command::Text(TextCommand::Stats { text })
+        } else if self.system_info {
+            Command::System(SystemCommand::Info)
+        } else if self.cpu_info {
+            Command::System(SystemCommand::Cpu)
+        } else if self.memory_info {
+            Command::System(SystemCommand::Memory)
+        } else if self.disk_info {
+            Command::System(SystemCommand::Disk)
+        } else if self.network_info {
+            Command::System(SystemCommand::Network)
+        } else if self.git_status {
+            Command::Git(GitCommand::Status)
+        } else if let Some(url) = self.git_clone {
+            Command::Git(GitCommand::Clone { url, output_path })
+        } else if let Some(name) = self.git_branch {
+            Command::Git(GitCommand::Branch { name })
+        } else if self.git_add {
+            Command::Git(GitCommand::Add)
+        } else if let Some(message) = self.git_commit {
+            Command::Git(GitCommand::Commit { message })
+        } else if self.git_branches {
+            Command::Git(GitCommand::Branches)
+        } else if let Some(count) = self.git_log {
+            Command::Git(GitCommand::Log { count })
+        } else if let (Some(output), Some(source)) = (&self.zip_create, &self.archive_source) {
+            Command::Archive(ArchiveCommand::Zip(ArchiveAction::Create {
+                source: source.clone(),
+                output: output.clone(),
+            }))
+        } else if let Some(archive) = self.zip_extract {
+            Command::Archive(ArchiveCommand::Zip(ArchiveAction::Extract {
+                archive,
+                output_path,
+            }))
+        } else if let Some(archive) = self.zip_list {
+            Command::Archive(ArchiveCommand::Zip(ArchiveAction::List { archive }))
+        } else if let (Some(output), Some(source)) = (self.tar_create, self.archive_source) {
+            Command::Archive(ArchiveCommand::Tar(ArchiveAction::Create {
+                source,
+                output,
+            }))
+        } else if let Some(archive) = self.tar_extract {
+            Command::Archive(ArchiveCommand::Tar(ArchiveAction::Extract {
+                archive,
+                output_path,
+            }))
+        } else if let Some(archive) = self.tar_list {
+            Command::Archive(ArchiveCommand::Tar(ArchiveAction::List { archive }))
+        } else if let Some(input) = self.json_format {
+            Command::Format(FormatCommand::Json(JsonCommand::Pretty { input }))
+        } else if let Some(input) = self.json_minify {
+            Command::Format(FormatCommand::Json(JsonCommand::Minify { input }))
+        } else if let Some(input) = self.json_validate {
+            Command::Format(FormatCommand::Json(JsonCommand::Validate { input }))
+        } else if let Some(input) = self.yaml_format {
+            Command::Format(FormatCommand::Yaml(YamlCommand::Pretty { input }))
+        } else if let Some(input) = self.yaml_validate {
+            Command::Format(FormatCommand::Yaml(YamlCommand::Validate { input }))
+        } else if let Some(input) = self.json_to_yaml {
+            Command::Format(FormatCommand::Json(JsonCommand::ToYaml { input }))
+        } else if let Some(input) = self.yaml_to_json {
+            Command::Format(FormatCommand::Yaml(YamlCommand::ToJson { input }))
+        } else if let (Some(input), Some(path)) = (self.json_query, self.query_path) {
+            Command::Format(FormatCommand::Json(JsonCommand::Query { input, path }))
+        } else if let Some(port) = self.port_check {
+            Command::Net(NetCommand::Port {
+                port,
+                host: self.host.unwrap_or_else(|| "localhost".to_string()),
+            })
+        } else if let Some(host) = self.ping {
+            Command::Net(NetCommand::Ping { host })
+        } else if self.public_ip {
+            Command::Net(NetCommand::PublicIp)
+        } else if let Some(url) = self.http_get {
+            Command::Net(NetCommand::Get { url })
+        } else if let Some(url) = self.http_post {
+            Command::Net(NetCommand::Post {
+                url,
+                body: self.post_body.unwrap_or_default(),
+            })
+        } else if let Some(domain) = self.dns_lookup {
+            Command::Net(NetCommand::Dns { domain })
+        } else if self.config_show {
+            Command::Config(ConfigCommand::Show)
+        } else if let Some(file) = self.config_load {
+            Command::Config(ConfigCommand::Load { file })
+        } else if self.server_start {
+            Command::Server {
+                port: self.server_port,
+            }
+        } else if let Some(name) = self.name {
+            Command::Greet { name }
+        } else {
+            return None;
+        };
+
+        Some(command)
+    }
+}
@@ -1,13 +1,30 @@
-use dirs;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::fs;
 use std::path::PathBuf;
 
+use crate::output::Render;
+use crate::{Error, Result};
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
     pub general: GeneralConfig,
     pub colors: ColorConfig,
     pub paths: PathConfig,
+    #[serde(default)]
+    pub server: ServerConfig,
+    /// Short names for longer command lines, expanded before parsing.
+    #[serde(default)]
+    pub aliases: BTreeMap<String, Alias>,
+}
+
+/// A configuration read from an explicit file.
+#[derive(Debug, Serialize)]
+pub struct LoadedConfig {
+    pub source: PathBuf,
+    #[serde(flatten)]
+    pub config: Config,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -28,6 +45,37 @@ pub struct PathConfig {
     pub download_path: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ServerConfig {
+    /// Let the web dashboard run `clh-<name>` plugins found on PATH.
+    #[serde(default)]
+    pub allow_plugins: bool,
+}
+
+/// An alias expands to one command line, or to a sequence of command lines
+/// that run in order until one fails.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum Alias {
+    Command(String),
+    Sequence(Vec<String>),
+}
+
+impl Alias {
+    pub fn lines(&self) -> &[String] {
+        match self {
+            Alias::Command(line) => std::slice::from_ref(line),
+            Alias::Sequence(lines) => lines,
+        }
+    }
+}
+
+impl fmt::Display for Alias {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.lines().join(" && "))
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Config {
@@ -43,13 +91,15 @@ impl Default for Config {
                 default_output: ".".to_string(),
                 download_path: ".".to_string(),
             },
+            server: ServerConfig::default(),
+            aliases: BTreeMap::new(),
         }
     }
 }
 
 impl Config {
     /// Load configuration from file or create default
-    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn load() -> Result<Self> {
         let config_path = Self::config_path()?;
 
         if config_path.exists() {
@@ -62,14 +112,14 @@ impl Config {
     }
 
     /// Load configuration from a specific file
-    pub fn load_from_file(path: &PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn load_from_file(path: &PathBuf) -> Result<Self> {
         let content = fs::read_to_string(path)?;
         let config: Config = toml::from_str(&content)?;
         Ok(config)
     }
 
     /// Save configuration to file
-    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn save(&self) -> Result<()> {
         let config_path = Self::config_path()?;
 
         // Create config directory if it doesn't exist
@@ -84,14 +134,17 @@ impl Config {
     }
 
     /// Get the configuration file path
-    pub fn config_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
-        let config_dir = dirs::config_dir().ok_or("Could not determine config directory")?;
+    pub fn config_path() -> Result<PathBuf> {
+        let config_dir = dirs::config_dir()
+            .ok_or_else(|| Error::NotFound("Could not determine config directory".to_string()))?;
 
         Ok(config_dir.join("command-line-helper").join("config.toml"))
     }
+}
 
+impl Render for Config {
     /// Display current configuration
-    pub fn display(&self) {
+    fn render(&self) {
         use colored::Colorize;
 
         println!("{}", "Current Configuration".cyan().bold());
@@ -130,7 +183,25 @@ impl Config {
         println!("  Default output: {}", self.paths.default_output.green());
         println!("  Download path:  {}", self.paths.download_path.green());
 
-        if let Ok(config_path) = Self::config_path() {
+        println!("\n{}", "Server:".yellow().bold());
+        println!(
+            "  Plugins:      {}",
+            if self.server.allow_plugins {
+                "allowed".green()
+            } else {
+                "not allowed".red()
+            }
+        );
+
+        println!("\n{}", "Aliases:".yellow().bold());
+        if self.aliases.is_empty() {
+            println!("  (none)");
+        }
+        for (name, alias) in &self.aliases {
+            println!("  {} = {}", name.green(), alias);
+        }
+
+        if let Ok(config_path) = Config::config_path() {
             println!(
                 "\n{} {}",
                 "Config file:".cyan().bold(),
@@ -139,3 +210,10 @@ impl Config {
         }
     }
 }
+
+impl Render for LoadedConfig {
+    fn render(&self) {
+        println!("Configuration loaded from: {}", self.source.display());
+        self.config.render();
+    }
+}
@@ -0,0 +1,133 @@
+// error.rs
+//
+// The error type shared by every library module. Each variant wraps the
+// failure of one of the underlying crates, or describes a problem detected
+// by the helper itself.
+
+use std::fmt;
+use std::io;
+
+use crate::exit_code;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing files, sockets or processes failed.
+    Io(io::Error),
+    /// A git repository operation failed.
+    Git(git2::Error),
+    /// An HTTP request failed.
+    Http(reqwest::Error),
+    /// Input could not be parsed or serialized as JSON.
+    Json(serde_json::Error),
+    /// Input could not be parsed or serialized as YAML.
+    Yaml(serde_yaml::Error),
+    /// A configuration file could not be parsed.
+    TomlDe(toml::de::Error),
+    /// A configuration could not be serialized.
+    TomlSer(toml::ser::Error),
+    /// A zip archive could not be read or written.
+    Zip(zip::result::ZipError),
+    /// A glob pattern was invalid.
+    Pattern(String),
+    /// Input could not be decoded (base64, URL encoding, UTF-8, ...).
+    Decode(String),
+    /// The requested item does not exist.
+    NotFound(String),
+    /// The request itself is invalid, e.g. an unsupported language or action.
+    InvalidInput(String),
+    /// The project type of a directory could not be identified.
+    UnknownProjectType(String),
+    /// An external command could not be started or exited unsuccessfully.
+    Command { command: String, code: Option<i32> },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Git(e) => write!(f, "{}", e.message()),
+            Error::Http(e) => write!(f, "{}", e),
+            Error::Json(e) => write!(f, "{}", e),
+            Error::Yaml(e) => write!(f, "{}", e),
+            Error::TomlDe(e) => write!(f, "{}", e),
+            Error::TomlSer(e) => write!(f, "{}", e),
+            Error::Zip(e) => write!(f, "{}", e),
+            Error::Pattern(message) => write!(f, "Invalid pattern: {}", message),
+            Error::Decode(message) => write!(f, "{}", message),
+            Error::NotFound(message) => write!(f, "{}", message),
+            Error::InvalidInput(message) => write!(f, "{}", message),
+            Error::UnknownProjectType(path) => write!(f, "Unknown project type in {}", path),
+            Error::Command {
+                command,
+                code: Some(code),
+            } => write!(f, "Command '{}' exited with status {}", command, code),
+            Error::Command {
+                command,
+                code: None,
+            } => write!(f, "Command '{}' could not be run", command),
+        }
+    }
+}
+
+impl Error {
+    /// The process exit code that reports this error.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::InvalidInput(_) => exit_code::USAGE,
+            _ => exit_code::FAILURE,
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Git(e) => Some(e),
+            Error::Http(e) => Some(e),
+            Error::Json(e) => Some(e),
+            Error::Yaml(e) => Some(e),
+            Error::TomlDe(e) => Some(e),
+            Error::TomlSer(e) => Some(e),
+            Error::Zip(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! impl_from {
+    ($($source:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$source> for Error {
+                fn from(e: $source) -> Self {
+                    Error::$variant(e)
+                }
+            }
+        )*
+    };
+}
+
+impl_from! {
+    io::Error => Io,
+    git2::Error => Git,
+    reqwest::Error => Http,
+    serde_json::Error => Json,
+    serde_yaml::Error => Yaml,
+    toml::de::Error => TomlDe,
+    toml::ser::Error => TomlSer,
+    zip::result::ZipError => Zip,
+}
+
+impl From<base64::DecodeError> for Error {
+    fn from(e: base64::DecodeError) -> Self {
+        Error::Decode(e.to_string())
+    }
+}
+
+impl From<std::string::FromUtf8Error> for Error {
+    fn from(_: std::string::FromUtf8Error) -> Self {
+        Error::Decode("Decoded data is not valid UTF-8".to_string())
+    }
+}
@@ -0,0 +1,13 @@
+//! Process exit codes used by the `command_line_helper` binary.
+//!
+//! | Code | Meaning                                                              |
+//! |------|----------------------------------------------------------------------|
+//! | 0    | The operation succeeded                                              |
+//! | 1    | The operation failed (I/O, network, git, archive, external command) |
+//! | 2    | Usage error (invalid arguments, unsupported language or action)      |
+//! | 3    | A check ran but did not pass (hash mismatch, invalid JSON/YAML, closed port, unreachable host) |
+
+pub const SUCCESS: u8 = 0;
+pub const FAILURE: u8 = 1;
+pub const USAGE: u8 = 2;
+pub const MISMATCH: u8 = 3;
@@ -0,0 +1,102 @@
+// input.rs
+//
+// Text and format operations accept their input inline, from stdin (`-`) or
+// from a file (`@path`). A leading `@@` stands for a literal `@`.
+
+use std::convert::Infallible;
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::{Error, Result};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+    /// The argument itself is the input.
+    Inline(String),
+    /// Read the input from standard input.
+    Stdin,
+    /// Read the input from a file.
+    File(PathBuf),
+}
+
+impl Input {
+    /// Read the raw bytes of the input, without any decoding.
+    pub fn read_bytes(&self) -> Result<Vec<u8>> {
+        match self {
+            Input::Inline(text) => Ok(text.as_bytes().to_vec()),
+            Input::Stdin => {
+                let mut buffer = Vec::new();
+                io::stdin().read_to_end(&mut buffer)?;
+                Ok(buffer)
+            }
+            Input::File(path) => Ok(fs::read(path)?),
+        }
+    }
+
+    /// Read the input as UTF-8 text.
+    pub fn read_to_string(&self) -> Result<String> {
+        match self {
+            Input::Inline(text) => Ok(text.clone()),
+            _ => String::from_utf8(self.read_bytes()?)
+                .map_err(|_| Error::Decode(format!("{} is not valid UTF-8 text", self))),
+        }
+    }
+}
+
+impl From<&str> for Input {
+    fn from(s: &str) -> Self {
+        if s == "-" {
+            Input::Stdin
+        } else if let Some(literal) = s.strip_prefix("@@") {
+            Input::Inline(format!("@{}", literal))
+        } else if let Some(path) = s.strip_prefix('@') {
+            Input::File(PathBuf::from(path))
+        } else {
+            Input::Inline(s.to_string())
+        }
+    }
+}
+
+impl FromStr for Input {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(Input::from(s))
+    }
+}
+
+impl std::fmt::Display for Input {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Input::Inline(_) => write!(f, "inline input"),
+            Input::Stdin => write!(f, "standard input"),
+            Input::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_input_sources() {
+        assert_eq!(Input::from("-"), Input::Stdin);
+        assert_eq!(Input::from("@data.json"), Input::File("data.json".into()));
+        assert_eq!(Input::from("@@handle"), Input::Inline("@handle".into()));
+        assert_eq!(Input::from("hello"), Input::Inline("hello".into()));
+    }
+
+    #[test]
+    fn test_read_file_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("input.bin");
+        fs::write(&path, [0u8, 159, 146, 150]).unwrap();
+
+        let input = Input::File(path.clone());
+        assert_eq!(input.read_bytes().unwrap(), vec![0u8, 159, 146, 150]);
+        assert!(matches!(input.read_to_string(), Err(Error::Decode(_))));
+    }
+}
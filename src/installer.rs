@@ -187,7 +187,7 @@ impl ToolInstaller {
         // Try winget first
         if which::which("winget").is_ok() {
             let status = Command::new("winget")
-                .args(&["install", "--id", &package, "-e"])
+                .args(["install", "--id", &package, "-e"])
                 .status()
                 .map_err(|e| e.to_string())?;
 
@@ -199,7 +199,7 @@ impl ToolInstaller {
         // Try chocolatey
         if which::which("choco").is_ok() {
             let status = Command::new("choco")
-                .args(&["install", &package, "-y"])
+                .args(["install", &package, "-y"])
                 .status()
                 .map_err(|e| e.to_string())?;
 
@@ -215,7 +215,7 @@ impl ToolInstaller {
         let package = self.get_macos_package_name();
 
         let status = Command::new("brew")
-            .args(&["install", &package])
+            .args(["install", &package])
             .status()
             .map_err(|e| e.to_string())?;
 
@@ -232,7 +232,7 @@ impl ToolInstaller {
         // Try apt (Debian/Ubuntu)
         if which::which("apt").is_ok() {
             let status = Command::new("sudo")
-                .args(&["apt", "install", "-y", &package])
+                .args(["apt", "install", "-y", &package])
                 .status()
                 .map_err(|e| e.to_string())?;
 
@@ -244,7 +244,7 @@ impl ToolInstaller {
         // Try dnf (Fedora)
         if which::which("dnf").is_ok() {
             let status = Command::new("sudo")
-                .args(&["dnf", "install", "-y", &package])
+                .args(["dnf", "install", "-y", &package])
                 .status()
                 .map_err(|e| e.to_string())?;
 
@@ -256,7 +256,7 @@ impl ToolInstaller {
         // Try pacman (Arch)
         if which::which("pacman").is_ok() {
             let status = Command::new("sudo")
-                .args(&["pacman", "-S", "--noconfirm", &package])
+                .args(["pacman", "-S", "--noconfirm", &package])
                 .status()
                 .map_err(|e| e.to_string())?;
 
@@ -0,0 +1,17 @@
+//! Command Line Helper as a library.
+//!
+//! Every module returns typed results (`Result<T, Error>`) instead of printing,
+//! so the hashing, archive, project-detection, search and other helpers can be
+//! reused from Rust. The `command_line_helper` binary is a thin front end that
+//! parses arguments and renders these results through [`output`].
+
+pub mod config;
+pub mod error;
+pub mod exit_code;
+pub mod input;
+pub mod installer;
+pub mod modules;
+pub mod output;
+pub mod utilities;
+
+pub use error::{Error, Result};
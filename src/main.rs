@@ -1,815 +1,1060 @@
 // main.rs
 
-mod config;
-mod installer;
-mod modules;
-mod utilities;
-
-use clap::Parser;
+mod cli;
+mod repl;
+mod script;
+
+use clap::error::ErrorKind;
+use clap::{CommandFactory, FromArgMatches, Parser};
+use colored::Colorize;
+use command_line_helper::config::Alias;
+use command_line_helper::{config, exit_code, modules, output, utilities};
+use std::collections::BTreeMap;
+use std::io;
 use std::path::{Path, PathBuf};
-
+use std::process::ExitCode;
+use std::time::Instant;
+
+use cli::{alias, complete};
+use cli::{
+    ArchiveAction, ArchiveCommand, Args, Command, ConfigCommand, EnvCommand, FormatCommand,
+    GitCommand, HashCommand, IndexCommand, JsonCommand, MatchArgs, NetCommand, PluginsCommand,
+    SystemCommand, TextCommand, YamlCommand,
+};
 use modules::archive_module;
+use modules::duplicate_module::{self, DuplicateOptions};
 use modules::env_module;
+use modules::export_module::{ExportFormat, Exporter};
 use modules::format_module;
+use modules::fuzzy_module;
 use modules::git_module;
-use modules::hash_module::{self, HashAlgorithm};
+use modules::hash_module;
+use modules::index_module;
 use modules::language_identifier_module::identify_project_type;
 use modules::language_module::execute_language_action;
 use modules::logging_module::setup_logging;
 use modules::network_module;
-use modules::os_modules::{copy_file, move_file, search_files};
-use modules::search_data_module::search_data_in_files;
+use modules::os_modules::{search_files, FileSearch};
+use modules::plugin_module;
+use modules::rename_module::{self, RenameOptions};
+use modules::replace_module::{replace_in_files, ReplaceOptions, ReplaceReport};
+use modules::search_data_module::{
+    search_data, search_data_in_files, DataSearch, Matcher, SearchOptions, TextPrinter,
+};
 use modules::server_module;
+use modules::sync_module::{self, SyncOptions};
 use modules::system_module;
 use modules::text_module;
+use modules::transfer_module::{self, TransferOptions};
+use modules::walk_module::WalkOptions;
 use modules::youtube_module::download_video;
+use output::{emit, emit_result, OutputFormat};
+use repl::{Repl, Request};
+use script::{Action, Script, ScriptSummary, StepOutcome, Variables};
 use utilities::{
-    clean_action_string, clean_language_string, is_language_installed, print_colored_path,
-    print_error_message, suggest_installation,
+    clean_action_string, clean_language_string, is_language_installed, print_error_message,
+    suggest_installation,
 };
 
-#[derive(Parser, Debug)]
-#[command(version, about)]
-struct Args {
-    /// Specifies the name to be used in operations like --copy and --move.
-    #[clap(short = 'n', long)]
-    name: Option<String>,
-
-    /// Downloads YouTube videos or shots from the provided URL.
-    #[clap(short = 'u', long)]
-    url: Option<String>,
-
-    /// Sets the quality for YouTube downloads (best, worst, audio).
-    #[clap(long, default_value = "best")]
-    quality: String,
-
-    /// Sets the output path for downloaded videos or destination for --copy/--move operations.
-    #[clap(short = 'o', long, default_value = ".")]
-    output_path: std::path::PathBuf,
-
-    /// Outputs trace logs to a file for debugging purposes.
-    #[clap(short = 'O', long = "logs-out")]
-    log_out: bool,
-
-    /// Enables verbose mode, providing detailed logs during execution.
-    #[clap(short = 'v', long)]
-    verbose: bool,
-
-    /// Searches for files in the specified output path using the provided search pattern.
-    #[clap(short = 's', long)]
-    search: Option<String>,
-
-    /// Search for specific data in files.
-    #[clap(short = 'S', long = "data-search")]
-    data_search: Option<String>,
-
-    /// Limits the number of search results when using the --search/--data-search operation.
-    #[clap(short = 'l', long, default_value = "0")]
-    limit: usize,
-
-    /// Specifies the programming language for targeted actions (enhances performance).
-    #[clap(short = 'L', long)]
-    language: Option<String>,
-
-    /// Navigates to the specified path from within the tool.
-    #[clap(short = 'g', long)]
-    goto: Option<PathBuf>,
-
-    /// Specify the depth of search roots.
-    #[clap(long = "root-level", default_value = "3")]
-    root_level: usize,
-
-    /// Copies files to the specified output path.
-    #[clap(long = "copy")]
-    copy: Option<PathBuf>,
-
-    /// Moves files to the specified output path.
-    #[clap(long = "move")]
-    move_files: Option<PathBuf>,
-
-    /// Specifies the action to perform on the project (e.g., run, build, test).
-    /// Use --goto along with this option for complex project structures.
-    #[clap(short = 'a', long)]
-    action: Option<String>,
-
-    // ========== Environment Variables ==========
-    /// List all environment variables
-    #[clap(long = "env-list")]
-    env_list: bool,
-
-    /// Get specific environment variable
-    #[clap(long = "env-get")]
-    env_get: Option<String>,
-
-    /// Set environment variable (format: KEY=VALUE)
-    #[clap(long = "env-set")]
-    env_set: Option<String>,
-
-    /// Load environment variables from file
-    #[clap(long = "env-load")]
-    env_load: Option<PathBuf>,
-
-    /// Export environment variables to file
-    #[clap(long = "env-export")]
-    env_export: Option<PathBuf>,
-
-    /// Filter for env export (optional)
-    #[clap(long = "env-filter")]
-    env_filter: Option<String>,
-
-    // ========== Hashing ==========
-    /// Calculate hash of a file
-    #[clap(long = "hash-file")]
-    hash_file: Option<PathBuf>,
-
-    /// Calculate hash of a string
-    #[clap(long = "hash-string")]
-    hash_string: Option<String>,
-
-    /// Hash algorithm (md5, sha256, sha512)
-    #[clap(long = "hash-algo", default_value = "sha256")]
-    hash_algo: String,
-
-    /// Verify file hash
-    #[clap(long = "hash-verify")]
-    hash_verify: Option<String>,
-
-    /// Calculate all hashes for a file
-    #[clap(long = "hash-all")]
-    hash_all: Option<PathBuf>,
-
-    // ========== Text Processing ==========
-    /// Base64 encode text
-    #[clap(long = "base64-encode")]
-    base64_encode: Option<String>,
-
-    /// Base64 decode text
-    #[clap(long = "base64-decode")]
-    base64_decode: Option<String>,
-
-    /// URL encode text
-    #[clap(long = "url-encode")]
-    url_encode: Option<String>,
-
-    /// URL decode text
-    #[clap(long = "url-decode")]
-    url_decode: Option<String>,
-
-    /// Convert text to case (upper, lower, title, camel, snake, kebab)
-    #[clap(long = "text-case")]
-    text_case: Option<String>,
-
-    /// Text to convert
-    #[clap(long = "text")]
-    text: Option<String>,
-
-    /// Text statistics
-    #[clap(long = "text-stats")]
-    text_stats: Option<String>,
-
-    // ========== System Information ==========
-    /// Display all system information
-    #[clap(long = "system-info")]
-    system_info: bool,
-
-    /// Display CPU information
-    #[clap(long = "cpu-info")]
-    cpu_info: bool,
-
-    /// Display memory information
-    #[clap(long = "memory-info")]
-    memory_info: bool,
-
-    /// Display disk information
-    #[clap(long = "disk-info")]
-    disk_info: bool,
-
-    /// Display network information
-    #[clap(long = "network-info")]
-    network_info: bool,
-
-    // ========== Git Operations ==========
-    /// Git status
-    #[clap(long = "git-status")]
-    git_status: bool,
-
-    /// Clone git repository
-    #[clap(long = "git-clone")]
-    git_clone: Option<String>,
-
-    /// Create git branch
-    #[clap(long = "git-branch")]
-    git_branch: Option<String>,
-
-    /// Git add all
-    #[clap(long = "git-add")]
-    git_add: bool,
-
-    /// Git commit with message
-    #[clap(long = "git-commit")]
-    git_commit: Option<String>,
-
-    /// List git branches
-    #[clap(long = "git-branches")]
-    git_branches: bool,
-
-    /// Show git log (number of commits)
-    #[clap(long = "git-log")]
-    git_log: Option<usize>,
-
-    // ========== Archive Operations ==========
-    /// Create zip archive
-    #[clap(long = "zip-create")]
-    zip_create: Option<PathBuf>,
-
-    /// Extract zip archive
-    #[clap(long = "zip-extract")]
-    zip_extract: Option<PathBuf>,
-
-    /// List zip contents
-    #[clap(long = "zip-list")]
-    zip_list: Option<PathBuf>,
-
-    /// Create tar.gz archive
-    #[clap(long = "tar-create")]
-    tar_create: Option<PathBuf>,
-
-    /// Extract tar.gz archive
-    #[clap(long = "tar-extract")]
-    tar_extract: Option<PathBuf>,
-
-    /// List tar.gz contents
-    #[clap(long = "tar-list")]
-    tar_list: Option<PathBuf>,
-
-    /// Archive source path
-    #[clap(long = "archive-source")]
-    archive_source: Option<PathBuf>,
-
-    // ========== Format Operations ==========
-    /// Format JSON
-    #[clap(long = "json-format")]
-    json_format: Option<String>,
-
-    /// Minify JSON
-    #[clap(long = "json-minify")]
-    json_minify: Option<String>,
-
-    /// Validate JSON
-    #[clap(long = "json-validate")]
-    json_validate: Option<String>,
-
-    /// Format YAML
-    #[clap(long = "yaml-format")]
-    yaml_format: Option<String>,
-
-    /// Validate YAML
-    #[clap(long = "yaml-validate")]
-    yaml_validate: Option<String>,
-
-    /// Convert JSON to YAML
-    #[clap(long = "json-to-yaml")]
-    json_to_yaml: Option<String>,
-
-    /// Convert YAML to JSON
-    #[clap(long = "yaml-to-json")]
-    yaml_to_json: Option<String>,
-
-    /// Query JSON path
-    #[clap(long = "json-query")]
-    json_query: Option<String>,
-
-    /// JSON query path
-    #[clap(long = "query-path")]
-    query_path: Option<String>,
-
-    // ========== Network Operations ==========
-    /// Check if port is open
-    #[clap(long = "port-check")]
-    port_check: Option<u16>,
-
-    /// Host for port check
-    #[clap(long = "host")]
-    host: Option<String>,
-
-    /// Ping host
-    #[clap(long = "ping")]
-    ping: Option<String>,
-
-    /// Get public IP
-    #[clap(long = "public-ip")]
-    public_ip: bool,
-
-    /// HTTP GET request
-    #[clap(long = "http-get")]
-    http_get: Option<String>,
-
-    /// HTTP POST request
-    #[clap(long = "http-post")]
-    http_post: Option<String>,
-
-    /// POST body
-    #[clap(long = "post-body")]
-    post_body: Option<String>,
-
-    /// DNS lookup
-    #[clap(long = "dns-lookup")]
-    dns_lookup: Option<String>,
-
-    // ========== Configuration ==========
-    /// Show current configuration
-    #[clap(long = "config-show")]
-    config_show: bool,
-
-    /// Load configuration from file
-    #[clap(long = "config-load")]
-    config_load: Option<PathBuf>,
-
-    // ========== Web Server ==========
-    /// Start the web dashboard server
-    #[clap(long = "server-start")]
-    server_start: bool,
-
-    /// Port for the web server
-    #[clap(long = "server-port", default_value = "3000")]
-    server_port: u16,
-}
-
-#[allow(dead_code)]
-fn validate_language_action(
-    language: &Option<String>,
-    action: &Option<String>,
-) -> Result<(), String> {
-    match (language.as_deref(), action.as_deref()) {
-        (Some(lang), Some(act)) => {
-            let lang_lower = lang.to_lowercase();
-            let act_lower = act.to_lowercase();
-
-            if !["java", "python", "dotnet", "rust", "js"].contains(&lang_lower.as_str()) {
-                return Err(format!("Invalid language: {}", lang));
-            }
-
-            if !["run", "build", "test", "clean"].contains(&act_lower.as_str()) {
-                return Err(format!("Invalid action: {}", act));
-            }
-
-            Ok(())
-        }
-        _ => Ok(()), // Both language and action are empty, so skip validation
+#[tokio::main]
+async fn main() -> ExitCode {
+    // Aliases are also offered as completions, so load them first
+    let config = config::Config::load();
+    let aliases = config
+        .as_ref()
+        .map(|config| config.aliases.clone())
+        .unwrap_or_default();
+    complete::handle_completion_request(&aliases);
+    if let Err(e) = &config {
+        eprintln!(
+            "{} Ignoring configuration: {}",
+            "Warning:".yellow().bold(),
+            e
+        );
     }
-}
 
-#[tokio::main]
-async fn main() {
-    let args = Args::parse();
+    let (argv, sequence) =
+        match alias::expand_invocation(std::env::args_os().skip(1).collect(), &aliases) {
+            Ok(expanded) => expanded,
+            Err(e) => {
+                print_error_message(&format!("Error: {}\n", e));
+                return ExitCode::from(e.exit_code());
+            }
+        };
+    let program = std::env::args_os().next().unwrap_or_default();
+    let mut command = alias::command_with_aliases(&aliases);
+    let args = command
+        .try_get_matches_from_mut(std::iter::once(program).chain(argv))
+        .and_then(|mut matches| Args::from_arg_matches_mut(&mut matches))
+        .unwrap_or_else(|err| err.format(&mut command).exit());
 
     setup_logging(args.verbose, args.log_out);
 
-    // No need "execute_language_action" will handle validation !
-    // if let Err(err) = validate_language_action(&args.language, &args.action) {
-    //     print_error_message(&format!("Error: {}\n", err));
-    //     return;
-    // }
-
     // Check if the user provided the --goto option
     if let Some(goto_path) = &args.goto {
-        // Navigate to the specified path
-        if goto_path.is_dir() {
-            // The path is a directory
-            std::env::set_current_dir(goto_path).unwrap_or_else(|err| {
-                eprintln!("Error navigating to {}: {}", goto_path.display(), err);
-            });
-        } else {
-            // The path is a file
-            if let Some(parent_dir) = goto_path.parent() {
-                std::env::set_current_dir(parent_dir).unwrap_or_else(|err| {
-                    eprintln!("Error navigating to {}: {}", parent_dir.display(), err);
-                });
-            } else {
-                eprintln!("Error getting parent directory of {}", goto_path.display());
-            }
+        let code = navigate(goto_path, args.output_format);
+        if code != exit_code::SUCCESS {
+            return ExitCode::from(code);
         }
-        println!(
-            "Navigated to: {}",
-            std::env::current_dir().unwrap().display()
-        );
     }
 
-    // Check if the data_search option is present
-    if let Some(search_data) = &args.data_search {
-        // Search for data in files
-        search_data_in_files(search_data, &args.output_path, args.root_level, args.limit);
+    let navigated = args.goto.is_some();
+    let format = args.output_format;
+    let mut code = match args.into_command().unwrap_or_else(|err| err.exit()) {
+        Some(Command::RunScript {
+            file,
+            keep_going,
+            vars,
+        }) => run_script(&file, keep_going, vars, format).await,
+        Some(Command::Shell) => run_shell(format, &aliases).await,
+        Some(command) => run_command(command, format).await,
+        None if is_interactive() => run_shell(format, &aliases).await,
+        // A bare --goto is a valid (if pointless) invocation
+        None if navigated => exit_code::SUCCESS,
+        None => {
+            print_error_message(
+                "Error: No operation specified. Use --help to see available commands.\n",
+            );
+            exit_code::USAGE
+        }
+    };
+
+    // The remaining commands of a sequence alias, until one fails
+    for line in sequence {
+        if code != exit_code::SUCCESS {
+            break;
+        }
+        code = run_script_step(line, format).await;
     }
 
-    if let (Some(fetched_language), Some(fetched_action)) = (&args.language, &args.action) {
-        let cleaned_language_str = clean_language_string(&fetched_language);
-        let cleaned_action_str = clean_action_string(&fetched_action);
+    ExitCode::from(code)
+}
 
-        if !is_language_installed(&cleaned_language_str) {
-            suggest_installation(&cleaned_language_str);
-        }
-        // Perform the action
-        execute_language_action(&cleaned_language_str, &cleaned_action_str);
-    } else if args.language.is_none() && !args.action.is_none() {
-        // If action is present but language is not, try to dynamically identify the language using the current path
-        match identify_project_type(".") {
-            Ok(project_type) => {
-                println!("Identified project type: {}", project_type);
-                if let Some(fetched_action) = &args.action {
-                    let cleaned_language_str = project_type
-                        .replace("rust", "cargo")
-                        .replace("\"", "")
-                        .replace("js", "npm");
-                    let cleaned_action_str = clean_action_string(fetched_action);
-                    if !is_language_installed(&cleaned_language_str) {
-                        suggest_installation(&cleaned_language_str);
-                    }
-                    // Perform the action
-                    execute_language_action(&cleaned_language_str, &cleaned_action_str);
-                }
+/// Change the working directory to `goto_path`, or to its parent for a file.
+fn navigate(goto_path: &Path, format: OutputFormat) -> u8 {
+    let target = if goto_path.is_dir() {
+        Some(goto_path)
+    } else {
+        goto_path.parent()
+    };
+
+    match target.map(|dir| (dir, std::env::set_current_dir(dir))) {
+        Some((_, Ok(()))) => {
+            if format == OutputFormat::Text {
+                println!(
+                    "Navigated to: {}",
+                    std::env::current_dir().unwrap().display()
+                );
             }
-            Err(error) => eprintln!("Error identifying project type: {}", error),
+            exit_code::SUCCESS
         }
-    } else {
-        // Check if both language and action are empty before printing an error
-        if args.language.is_none() && args.action.is_none() {
-            // Do nothing when both are empty
-        } else {
-            print_error_message("Error: You must provide both --language and --action. Use --help to see the help message.\n");
+        Some((dir, Err(err))) => {
+            eprintln!("Error navigating to {}: {}", dir.display(), err);
+            exit_code::FAILURE
         }
-    }
-
-    // Check if the name is present and neither --copy nor --move are present
-    if let Some(name) = &args.name {
-        if args.copy.is_none() && args.move_files.is_none() {
-            // Print greetings
-            println!("Hello {}!", name);
+        None => {
+            eprintln!("Error getting parent directory of {}", goto_path.display());
+            exit_code::FAILURE
         }
     }
+}
 
-    // Check if the URL is present before attempting to download
-    if let Some(url) = &args.url {
-        // Download the video
-        println!("Downloading video ...");
-        if let Err(err) =
-            download_video(url, &args.output_path.to_string_lossy(), &args.quality).await
-        {
-            print_error_message(&format!("Error: {}\n", err));
+async fn run_script(
+    file: &Path,
+    keep_going: bool,
+    overrides: Vec<(String, String)>,
+    format: OutputFormat,
+) -> u8 {
+    let script = match Script::load(file) {
+        Ok(script) => script,
+        Err(e) => {
+            print_error_message(&format!("Error: Failed to load script: {}\n", e));
+            return e.exit_code();
+        }
+    };
+
+    let mut variables = Variables::new(script.variables);
+    variables.extend(overrides);
+
+    let mut outcomes = Vec::new();
+    let mut stopped = false;
+    for (index, step) in script.steps.iter().enumerate() {
+        let name = step.label();
+        if stopped {
+            outcomes.push(StepOutcome::skipped(index + 1, name));
+            continue;
         }
-    }
-
-    // Check if the search option is present
-    if let Some(pattern) = &args.search {
-        search_files(pattern, &args.output_path, &args.limit);
-    }
 
-    // Perform copy based on arguments
-    if let Some(copy_path) = &args.copy {
-        let _ = copy_file(copy_path, &args.output_path, &args.name);
-    }
+        if format == OutputFormat::Text {
+            println!("\n{} Step {}: {}", "→".cyan(), index + 1, name.yellow());
+        }
 
-    // Perform move based on arguments
-    if let Some(move_path) = &args.move_files {
-        let _ = move_file(move_path, &args.output_path, &args.name);
+        let started = Instant::now();
+        let code = match step.resolve(&variables) {
+            Ok(Action::Run(args)) => run_script_step(args, format).await,
+            Ok(Action::Goto(path)) => navigate(&path, format),
+            Ok(Action::Set(vars)) => {
+                variables.extend(vars);
+                exit_code::SUCCESS
+            }
+            Err(e) => {
+                print_error_message(&format!("Error: {}\n", e));
+                e.exit_code()
+            }
+        };
+        outcomes.push(StepOutcome::finished(
+            index + 1,
+            name,
+            code,
+            started.elapsed(),
+        ));
+
+        if code != exit_code::SUCCESS
+            && !(keep_going || script.continue_on_error || step.continue_on_error)
+        {
+            stopped = true;
+        }
     }
 
-    // ========== Environment Variables ==========
-    if args.env_list {
-        env_module::list_env_vars();
-        return;
-    }
+    emit(
+        &ScriptSummary {
+            script: PathBuf::from(file),
+            steps: outcomes,
+        },
+        format,
+    )
+}
 
-    if let Some(key) = &args.env_get {
-        env_module::get_env_var(key);
-        return;
+/// Parse one script step like a command line and run it.
+async fn run_script_step(args: Vec<String>, format: OutputFormat) -> u8 {
+    match parse_command_line(args, format) {
+        Ok((command, format)) => run_command(command, format).await,
+        Err(code) => code,
     }
+}
 
-    if let Some(value) = &args.env_set {
-        if let Some((key, val)) = value.split_once('=') {
-            env_module::set_env_var(key, val);
-        } else {
-            print_error_message("Error: --env-set requires KEY=VALUE format\n");
+/// Parse a script step or shell line, given without the program name, and
+/// apply its --goto. `Err` holds the exit code when there is nothing to run.
+fn parse_command_line(
+    args: Vec<String>,
+    format: OutputFormat,
+) -> Result<(Command, OutputFormat), u8> {
+    let program = std::env::args().next().unwrap_or_default();
+    let args = match Args::try_parse_from(std::iter::once(program).chain(args)) {
+        Ok(args) => args,
+        Err(err) => {
+            let _ = err.print();
+            return Err(if err.use_stderr() {
+                exit_code::USAGE
+            } else {
+                exit_code::SUCCESS
+            });
         }
-        return;
-    }
+    };
 
-    if let Some(path) = &args.env_load {
-        if let Err(e) = env_module::load_env_file(path) {
-            print_error_message(&format!("Error loading env file: {}\n", e));
+    if let Some(goto_path) = &args.goto {
+        let code = navigate(goto_path, format);
+        if code != exit_code::SUCCESS {
+            return Err(code);
         }
-        return;
     }
 
-    if let Some(path) = &args.env_export {
-        if let Err(e) = env_module::export_env_vars(path, args.env_filter.as_deref()) {
-            print_error_message(&format!("Error exporting env vars: {}\n", e));
+    // Lines inherit the caller's output format unless they ask for another one
+    let format = match args.output_format {
+        OutputFormat::Text => format,
+        other => other,
+    };
+    let navigated = args.goto.is_some();
+    match args.into_command() {
+        Ok(Some(command)) => Ok((command, format)),
+        Ok(None) if navigated => Err(exit_code::SUCCESS),
+        Ok(None) => {
+            print_error_message("Error: No operation specified\n");
+            Err(exit_code::USAGE)
         }
-        return;
-    }
-
-    // ========== Hashing ==========
-    if let Some(path) = &args.hash_all {
-        hash_module::hash_file_all(path);
-        return;
-    }
-
-    if let Some(path) = &args.hash_file {
-        let algo = match args.hash_algo.to_lowercase().as_str() {
-            "md5" => HashAlgorithm::MD5,
-            "sha512" => HashAlgorithm::SHA512,
-            _ => HashAlgorithm::SHA256,
-        };
-
-        if let Some(expected) = &args.hash_verify {
-            hash_module::verify_hash(path, expected, algo);
-        } else {
-            hash_module::display_file_hash(path, algo);
+        Err(err) => {
+            let _ = err.print();
+            Err(exit_code::USAGE)
         }
-        return;
-    }
-
-    if let Some(text) = &args.hash_string {
-        let algo = match args.hash_algo.to_lowercase().as_str() {
-            "md5" => HashAlgorithm::MD5,
-            "sha512" => HashAlgorithm::SHA512,
-            _ => HashAlgorithm::SHA256,
-        };
-        let hash = hash_module::hash_string(text, algo);
-        println!("Hash: {}", hash);
-        return;
     }
+}
 
-    // ========== Text Processing ==========
-    if let Some(text) = &args.base64_encode {
-        text_module::base64_encode(text);
-        return;
-    }
+/// Whether a user is at the terminal to answer prompts.
+fn is_interactive() -> bool {
+    atty::is(atty::Stream::Stdin) && atty::is(atty::Stream::Stderr)
+}
 
-    if let Some(text) = &args.base64_decode {
-        text_module::base64_decode(text);
-        return;
+/// Read and run command lines until the user exits. Returns the exit code
+/// of the last command.
+async fn run_shell(format: OutputFormat, aliases: &BTreeMap<String, Alias>) -> u8 {
+    if !is_interactive() {
+        print_error_message("Error: The shell needs an interactive terminal\n");
+        return exit_code::USAGE;
     }
 
-    if let Some(text) = &args.url_encode {
-        text_module::url_encode(text);
-        return;
+    let mut repl = Repl::new(&Args::command());
+    repl.print_banner();
+
+    let mut code = exit_code::SUCCESS;
+    loop {
+        code = match repl.next_request() {
+            Request::Run(args) => match alias::expand(args, aliases) {
+                Ok(lines) => {
+                    let mut code = exit_code::SUCCESS;
+                    for line in lines {
+                        code = run_shell_line(line, format).await;
+                        if code != exit_code::SUCCESS {
+                            break;
+                        }
+                    }
+                    code
+                }
+                Err(e) => {
+                    print_error_message(&format!("Error: {}\n", e));
+                    e.exit_code()
+                }
+            },
+            Request::Goto(path) => navigate(&path, OutputFormat::Text),
+            Request::Exit => return code,
+        };
     }
+}
 
-    if let Some(text) = &args.url_decode {
-        text_module::url_decode(text);
-        return;
+/// Run one command line typed into the shell.
+async fn run_shell_line(args: Vec<String>, format: OutputFormat) -> u8 {
+    match parse_command_line(args, format) {
+        Ok((
+            Command::RunScript {
+                file,
+                keep_going,
+                vars,
+            },
+            format,
+        )) => run_script(&file, keep_going, vars, format).await,
+        Ok((Command::Shell, _)) => {
+            print_error_message("Error: Already in the shell\n");
+            exit_code::USAGE
+        }
+        Ok((command, format)) => run_command(command, format).await,
+        Err(code) => code,
     }
+}
 
-    if let Some(case_type) = &args.text_case {
-        if let Some(text) = &args.text {
-            match case_type.to_lowercase().as_str() {
-                "upper" => text_module::to_uppercase(text),
-                "lower" => text_module::to_lowercase(text),
-                "title" => text_module::to_titlecase(text),
-                "camel" => text_module::to_camelcase(text),
-                "snake" => text_module::to_snakecase(text),
-                "kebab" => text_module::to_kebabcase(text),
-                _ => print_error_message(
-                    "Error: Invalid case type. Use: upper, lower, title, camel, snake, kebab\n",
-                ),
+async fn run_command(command: Command, format: OutputFormat) -> u8 {
+    match command {
+        Command::Greet { name } => emit(&text_module::greet(&name), format),
+        Command::Download {
+            url,
+            quality,
+            output_path,
+        } => emit_result(
+            download_video(&url, &output_path.to_string_lossy(), &quality).await,
+            format,
+            "Failed to download",
+        ),
+        Command::Search {
+            pattern,
+            output_path,
+            limit,
+            filters,
+            long,
+            export,
+        } => {
+            let search = search_files(&pattern, &output_path, &filters.filters(limit))
+                .map(|search| FileSearch { long, ..search });
+            match export {
+                Some(export) => export_file_search(search, export),
+                None => emit_result(search, format, "Failed to search files"),
             }
-        } else {
-            print_error_message("Error: --text is required with --text-case\n");
         }
-        return;
-    }
-
-    if let Some(text) = &args.text_stats {
-        text_module::text_stats(text);
-        return;
-    }
-
-    // ========== System Information ==========
-    if args.system_info {
-        system_module::display_system_info();
-        return;
-    }
-
-    if args.cpu_info {
-        system_module::display_cpu_info();
-        return;
-    }
-
-    if args.memory_info {
-        system_module::display_memory_info();
-        return;
-    }
-
-    if args.disk_info {
-        system_module::display_disk_info();
-        return;
-    }
-
-    if args.network_info {
-        system_module::display_network_info();
-        return;
-    }
-
-    // ========== Git Operations ==========
-    if args.git_status {
-        git_module::git_status(Path::new("."));
-        return;
-    }
-
-    if let Some(url) = &args.git_clone {
-        git_module::git_clone(url, &args.output_path);
-        return;
-    }
-
-    if let Some(branch_name) = &args.git_branch {
-        git_module::git_create_branch(Path::new("."), branch_name);
-        return;
-    }
-
-    if args.git_add {
-        git_module::git_add_all(Path::new("."));
-        return;
-    }
-
-    if let Some(message) = &args.git_commit {
-        git_module::git_commit(Path::new("."), message);
-        return;
-    }
-
-    if args.git_branches {
-        git_module::git_list_branches(Path::new("."));
-        return;
-    }
-
-    if let Some(count) = args.git_log {
-        git_module::git_log(Path::new("."), count);
-        return;
-    }
-
-    // ========== Archive Operations ==========
-    if let Some(output) = &args.zip_create {
-        if let Some(source) = &args.archive_source {
-            archive_module::create_zip(source, output);
-        } else {
-            print_error_message("Error: --archive-source is required with --zip-create\n");
+        Command::Find {
+            query,
+            output_path,
+            limit,
+            interactive,
+            walk,
+        } => {
+            let options = WalkOptions {
+                max_depth: None,
+                ..walk.options(0)
+            };
+            let query = query.unwrap_or_default();
+            let found = if interactive {
+                fuzzy_module::fuzzy_find_interactive(&query, &output_path, &options)
+            } else {
+                fuzzy_module::fuzzy_find(&query, &output_path, &options, limit)
+            };
+            emit_result(found, format, "Failed to find files")
         }
-        return;
-    }
-
-    if let Some(archive) = &args.zip_extract {
-        archive_module::extract_zip(archive, &args.output_path);
-        return;
-    }
-
-    if let Some(archive) = &args.zip_list {
-        archive_module::list_zip_contents(archive);
-        return;
+        Command::DataSearch {
+            data,
+            matching,
+            output_path,
+            root_level,
+            limit,
+            report,
+            walk,
+            binary,
+            encoding,
+            index,
+            archives,
+        } => {
+            let patterns: Vec<String> = data.into_iter().chain(matching.patterns.clone()).collect();
+            let options = SearchOptions {
+                walk: walk.options(root_level),
+                limit,
+                binary,
+                encoding,
+                archives,
+                ..report.options()
+            };
+            let export = report.export;
+            run_data_search(
+                &patterns,
+                &matching,
+                &output_path,
+                options,
+                index,
+                export,
+                format,
+            )
+        }
+        Command::Replace {
+            find,
+            replacement,
+            matching,
+            dry_run,
+            interactive,
+            output_path,
+            root_level,
+            walk,
+        } => {
+            if matching.invert {
+                return emit_result::<ReplaceReport>(
+                    Err(command_line_helper::Error::InvalidInput(
+                        "--invert cannot be used with replace".to_string(),
+                    )),
+                    format,
+                    "Failed to replace",
+                );
+            }
+            let patterns: Vec<String> = std::iter::once(find)
+                .chain(matching.patterns.clone())
+                .collect();
+            let options = ReplaceOptions {
+                walk: walk.options(root_level),
+                expand: matching.regex,
+                dry_run,
+                interactive,
+            };
+            emit_result(
+                Matcher::new(&patterns, matching.options()).and_then(|matcher| {
+                    replace_in_files(&matcher, &replacement, &output_path, &options)
+                }),
+                format,
+                "Failed to replace",
+            )
+        }
+        Command::Copy {
+            sources,
+            output_path,
+            name,
+            preserve,
+            on_conflict,
+        } => {
+            let options = TransferOptions {
+                name,
+                preserve,
+                on_conflict,
+                progress: format == OutputFormat::Text,
+            };
+            emit_result(
+                transfer_module::copy_paths(&sources, &output_path, &options),
+                format,
+                "Failed to copy",
+            )
+        }
+        Command::Move {
+            sources,
+            output_path,
+            name,
+            on_conflict,
+        } => {
+            let options = TransferOptions {
+                name,
+                on_conflict,
+                progress: format == OutputFormat::Text,
+                ..TransferOptions::default()
+            };
+            emit_result(
+                transfer_module::move_paths(&sources, &output_path, &options),
+                format,
+                "Failed to move",
+            )
+        }
+        Command::Duplicates {
+            path,
+            min_size,
+            action,
+            keep,
+            apply,
+            include,
+            exclude,
+        } => {
+            let options = DuplicateOptions {
+                include,
+                exclude,
+                min_size,
+                action,
+                keep,
+                apply,
+            };
+            emit_result(
+                duplicate_module::find_duplicates(&path, &options),
+                format,
+                "Failed to find duplicates",
+            )
+        }
+        Command::Rename {
+            files,
+            find,
+            replace,
+            template,
+            case,
+            start,
+            dry_run,
+            undo,
+        } => {
+            let result = rename_module::journal_path().and_then(|journal| {
+                if undo {
+                    return rename_module::undo_rename(&journal, dry_run);
+                }
+                let options = RenameOptions {
+                    find,
+                    replace: replace.unwrap_or_default(),
+                    template,
+                    case,
+                    start,
+                    dry_run,
+                };
+                rename_module::rename_files(&files, &options, &journal)
+            });
+            emit_result(result, format, "Failed to rename")
+        }
+        Command::Sync {
+            source,
+            destination,
+            compare,
+            delete,
+            exclude,
+            dry_run,
+        } => {
+            let options = SyncOptions {
+                compare,
+                delete,
+                exclude,
+                dry_run,
+                progress: format == OutputFormat::Text,
+            };
+            emit_result(
+                sync_module::sync_dirs(&source, &destination, &options),
+                format,
+                "Failed to sync",
+            )
+        }
+        Command::Project { action, language } => run_project_action(language, &action),
+        Command::Env(command) => run_env_command(command, format),
+        Command::Hash(command) => run_hash_command(command, format),
+        Command::Text(command) => run_text_command(command, format),
+        Command::System(command) => match command {
+            SystemCommand::Info => emit(&system_module::system_info(), format),
+            SystemCommand::Cpu => emit(&system_module::cpu_info(), format),
+            SystemCommand::Memory => emit(&system_module::memory_info(), format),
+            SystemCommand::Disk => emit(&system_module::disk_info(), format),
+            SystemCommand::Network => emit(&system_module::network_info(), format),
+        },
+        Command::Git(command) => run_git_command(command, format),
+        Command::Archive(command) => run_archive_command(command, format),
+        Command::Index(command) => run_index_command(command, format),
+        Command::Format(command) => run_format_command(command, format),
+        // The network module uses blocking HTTP clients
+        Command::Net(command) => tokio::task::block_in_place(|| run_net_command(command, format)),
+        Command::Config(command) => run_config_command(command, format),
+        Command::Plugins(PluginsCommand::List) => emit(&plugin_module::discover_plugins(), format),
+        Command::External(words) => run_plugin(words, format),
+        // Scripts and the shell are dispatched from `main`, so these are nested in a script
+        Command::RunScript { .. } => {
+            print_error_message("Error: run-script cannot be used inside a script\n");
+            exit_code::USAGE
+        }
+        Command::Shell => {
+            print_error_message("Error: shell cannot be used inside a script\n");
+            exit_code::USAGE
+        }
+        // Both write shell or roff source, so they ignore --output
+        Command::Completions { .. } | Command::Man { .. } => {
+            let mut stdout = std::io::stdout();
+            let result = match command {
+                Command::Completions { shell } => complete::write_registration(shell, &mut stdout),
+                Command::Man { out_dir } => {
+                    complete::write_man_page(out_dir.as_deref(), &mut stdout)
+                }
+                _ => unreachable!(),
+            };
+            match result {
+                Ok(()) => exit_code::SUCCESS,
+                Err(err) => {
+                    print_error_message(&format!("Error: {}\n", err));
+                    err.exit_code()
+                }
+            }
+        }
+        Command::Server { port } => match server_module::start_server(port).await {
+            Ok(()) => exit_code::SUCCESS,
+            Err(e) => {
+                print_error_message(&format!("Server error: {}\n", e));
+                e.exit_code()
+            }
+        },
     }
+}
 
-    if let Some(output) = &args.tar_create {
-        if let Some(source) = &args.archive_source {
-            archive_module::create_tar_gz(source, output);
-        } else {
-            print_error_message("Error: --archive-source is required with --tar-create\n");
+/// Write the entries of a file search in an export format.
+fn export_file_search(search: command_line_helper::Result<FileSearch>, export: ExportFormat) -> u8 {
+    let result = search.and_then(|search| {
+        let description = format!("Entries matching '{}'", search.pattern);
+        let mut exporter = Exporter::new(export, "search", description, io::stdout().lock());
+        for entry in &search.matches {
+            exporter.found_entry(entry)?;
         }
-        return;
-    }
+        Ok(exporter.finish()?)
+    });
+    export_finished(result, "Failed to search files")
+}
 
-    if let Some(archive) = &args.tar_extract {
-        archive_module::extract_tar_gz(archive, &args.output_path);
-        return;
+/// The exit code of an export. A reader that stops early, like `head`,
+/// is not an error.
+fn export_finished(result: command_line_helper::Result<()>, context: &str) -> u8 {
+    match result {
+        Err(command_line_helper::Error::Io(e)) if e.kind() == io::ErrorKind::BrokenPipe => {
+            exit_code::SUCCESS
+        }
+        Err(e) => {
+            print_error_message(&format!("Error: {}: {}\n", context, e));
+            e.exit_code()
+        }
+        Ok(()) => exit_code::SUCCESS,
     }
+}
 
-    if let Some(archive) = &args.tar_list {
-        archive_module::list_tar_gz_contents(archive);
-        return;
+/// Text output is printed file by file while the search runs; the other
+/// formats need the complete result.
+fn run_data_search(
+    patterns: &[String],
+    matching: &MatchArgs,
+    root: &Path,
+    mut options: SearchOptions,
+    use_index: bool,
+    export: Option<ExportFormat>,
+    format: OutputFormat,
+) -> u8 {
+    let matcher = match Matcher::new(patterns, matching.options()) {
+        Ok(matcher) => matcher,
+        Err(e) => return emit_result::<DataSearch>(Err(e), format, "Failed to search data"),
+    };
+    if use_index {
+        match index_module::index_filter(&matcher, root) {
+            Ok(filter) => options.index = Some(filter),
+            Err(e) => return emit_result::<DataSearch>(Err(e), format, "Failed to search data"),
+        }
     }
-
-    // ========== Format Operations ==========
-    if let Some(json) = &args.json_format {
-        format_module::format_json(json);
-        return;
+    let options = &options;
+    if let Some(export) = export {
+        let description = format!("Lines matching '{}'", patterns.join("', '"));
+        let mut exporter = Exporter::new(export, "data-search", description, io::stdout().lock());
+        let mut written = Ok(());
+        let result = search_data(&matcher, root, options, |file| {
+            if written.is_ok() {
+                written = exporter.file_matches(&file);
+            }
+        })
+        .and_then(|()| Ok(written?))
+        .and_then(|()| Ok(exporter.finish()?));
+        return export_finished(result, "Failed to search data");
     }
-
-    if let Some(json) = &args.json_minify {
-        format_module::minify_json(json);
-        return;
+    if format != OutputFormat::Text {
+        return emit_result(
+            search_data_in_files(&matcher, root, options),
+            format,
+            "Failed to search data",
+        );
     }
 
-    if let Some(json) = &args.json_validate {
-        format_module::validate_json(json);
-        return;
+    let mut printer = TextPrinter::new(options.layout());
+    match search_data(&matcher, root, options, |file| printer.print(&file)) {
+        Ok(()) => {
+            printer.finish(patterns);
+            exit_code::SUCCESS
+        }
+        Err(e) => emit_result::<DataSearch>(Err(e), format, "Failed to search data"),
     }
+}
 
-    if let Some(yaml) = &args.yaml_format {
-        format_module::format_yaml(yaml);
-        return;
+/// Run the `clh-<name>` plugin for a command that is not built in.
+fn run_plugin(words: Vec<String>, format: OutputFormat) -> u8 {
+    let Some((name, args)) = words.split_first() else {
+        return exit_code::USAGE;
+    };
+
+    match plugin_module::find_plugin(name) {
+        Some(plugin) => match plugin_module::run_plugin(&plugin, args, format) {
+            Ok(code) => code,
+            Err(e) => {
+                print_error_message(&format!("Error: {}\n", e));
+                e.exit_code()
+            }
+        },
+        None => {
+            let _ = Args::command()
+                .error(
+                    ErrorKind::InvalidSubcommand,
+                    format!(
+                        "unrecognized subcommand '{}' (no {}{} plugin on PATH)",
+                        name,
+                        plugin_module::PLUGIN_PREFIX,
+                        name
+                    ),
+                )
+                .print();
+            exit_code::USAGE
+        }
     }
+}
 
-    if let Some(yaml) = &args.yaml_validate {
-        format_module::validate_yaml(yaml);
-        return;
-    }
+fn run_project_action(language: Option<String>, action: &str) -> u8 {
+    let cleaned_action_str = clean_action_string(action);
 
-    if let Some(json) = &args.json_to_yaml {
-        format_module::json_to_yaml(json);
-        return;
-    }
+    let cleaned_language_str = match language {
+        Some(language) => clean_language_string(&language),
+        // Try to dynamically identify the language using the current path
+        None => match identify_project_type(".") {
+            Ok(project_type) => {
+                println!("Identified project type: {}", project_type);
+                project_type
+                    .replace("rust", "cargo")
+                    .replace('"', "")
+                    .replace("js", "npm")
+            }
+            Err(error) => {
+                print_error_message(&format!("Error identifying project type: {}\n", error));
+                return error.exit_code();
+            }
+        },
+    };
 
-    if let Some(yaml) = &args.yaml_to_json {
-        format_module::yaml_to_json(yaml);
-        return;
+    if !is_language_installed(&cleaned_language_str) {
+        suggest_installation(&cleaned_language_str);
     }
-
-    if let Some(json) = &args.json_query {
-        if let Some(path) = &args.query_path {
-            format_module::json_query(json, path);
-        } else {
-            print_error_message("Error: --query-path is required with --json-query\n");
+    // Perform the action
+    match execute_language_action(&cleaned_language_str, &cleaned_action_str) {
+        Ok(()) => exit_code::SUCCESS,
+        Err(error) => {
+            print_error_message(&format!("Error: {}\n", error));
+            error.exit_code()
         }
-        return;
-    }
-
-    // ========== Network Operations ==========
-    if let Some(port) = args.port_check {
-        let host = args.host.as_deref().unwrap_or("localhost");
-        network_module::check_port(host, port);
-        return;
     }
+}
 
-    if let Some(host) = &args.ping {
-        network_module::ping_host(host);
-        return;
+fn run_env_command(command: EnvCommand, format: OutputFormat) -> u8 {
+    match command {
+        EnvCommand::List => emit(&env_module::list_env_vars(), format),
+        EnvCommand::Get { key } => emit_result(
+            env_module::get_env_var(&key),
+            format,
+            "Failed to get env var",
+        ),
+        EnvCommand::Set { pair: (key, value) } => {
+            emit(&env_module::set_env_var(&key, &value), format)
+        }
+        EnvCommand::Load { file } => emit_result(
+            env_module::load_env_file(&file),
+            format,
+            "Error loading env file",
+        ),
+        EnvCommand::Export { file, filter } => emit_result(
+            env_module::export_env_vars(&file, filter.as_deref()),
+            format,
+            "Error exporting env vars",
+        ),
     }
+}
 
-    if args.public_ip {
-        network_module::get_public_ip();
-        return;
+fn run_hash_command(command: HashCommand, format: OutputFormat) -> u8 {
+    let context = "Failed to calculate hash";
+    match command {
+        HashCommand::File { path, algo, verify } => match verify {
+            Some(expected) => emit_result(
+                hash_module::verify_hash(&path, &expected, algo),
+                format,
+                context,
+            ),
+            None => emit_result(hash_module::file_hash(&path, algo), format, context),
+        },
+        HashCommand::String { text, algo } => emit(&hash_module::string_hash(&text, algo), format),
+        HashCommand::All { path } => {
+            emit_result(hash_module::hash_file_all(&path), format, context)
+        }
     }
+}
 
-    if let Some(url) = &args.http_get {
-        network_module::http_get(url);
-        return;
-    }
+fn run_text_command(command: TextCommand, format: OutputFormat) -> u8 {
+    let context = "Text operation failed";
+    let transform = match command {
+        TextCommand::Base64Encode { text } => text
+            .read_bytes()
+            .map(|bytes| text_module::base64_encode_bytes(&bytes)),
+        TextCommand::Base64Decode {
+            text,
+            out: Some(path),
+        } => {
+            return emit_result(
+                text.read_to_string()
+                    .and_then(|text| text_module::base64_decode_to_file(&text, &path)),
+                format,
+                context,
+            )
+        }
+        TextCommand::Base64Decode { text, out: None } => text
+            .read_to_string()
+            .and_then(|text| text_module::base64_decode(&text)),
+        TextCommand::UrlEncode { text } => text
+            .read_to_string()
+            .map(|text| text_module::url_encode(&text)),
+        TextCommand::UrlDecode { text } => text
+            .read_to_string()
+            .and_then(|text| text_module::url_decode(&text)),
+        TextCommand::Case { case, text } => text
+            .read_to_string()
+            .map(|text| text_module::to_case(&text, case)),
+        TextCommand::Stats { text } => {
+            return emit_result(
+                text.read_to_string()
+                    .map(|text| text_module::text_stats(&text)),
+                format,
+                context,
+            )
+        }
+        TextCommand::Replace {
+            find,
+            replace,
+            text,
+        } => {
+            return emit_result(
+                text.read_to_string()
+                    .map(|text| text_module::find_replace(&text, &find, &replace)),
+                format,
+                context,
+            )
+        }
+    };
 
-    if let Some(url) = &args.http_post {
-        let body = args.post_body.as_deref().unwrap_or("");
-        network_module::http_post(url, body, None);
-        return;
-    }
+    emit_result(transform, format, context)
+}
 
-    if let Some(domain) = &args.dns_lookup {
-        network_module::dns_lookup(domain);
-        return;
+fn run_git_command(command: GitCommand, format: OutputFormat) -> u8 {
+    let repo = Path::new(".");
+    let context = "Git operation failed";
+    match command {
+        GitCommand::Status => emit_result(git_module::git_status(repo), format, context),
+        GitCommand::Clone { url, output_path } => emit_result(
+            git_module::git_clone(&url, &output_path),
+            format,
+            "Failed to clone repository",
+        ),
+        GitCommand::Branch { name } => emit_result(
+            git_module::git_create_branch(repo, &name),
+            format,
+            "Failed to create branch",
+        ),
+        GitCommand::Add => emit_result(
+            git_module::git_add_all(repo),
+            format,
+            "Failed to stage changes",
+        ),
+        GitCommand::Commit { message } => emit_result(
+            git_module::git_commit(repo, &message),
+            format,
+            "Failed to commit",
+        ),
+        GitCommand::Branches => emit_result(git_module::git_list_branches(repo), format, context),
+        GitCommand::Log { count } => emit_result(git_module::git_log(repo, count), format, context),
     }
+}
 
-    // ========== Configuration ==========
-    if args.config_show {
-        match config::Config::load() {
-            Ok(cfg) => cfg.display(),
-            Err(e) => print_error_message(&format!("Error loading config: {}\n", e)),
+fn run_index_command(command: IndexCommand, format: OutputFormat) -> u8 {
+    let context = "Index error";
+    match command {
+        IndexCommand::Build { path } => {
+            emit_result(index_module::build_index(&path), format, context)
+        }
+        IndexCommand::Status { path } => {
+            emit_result(index_module::index_status(&path), format, context)
+        }
+        IndexCommand::Remove { path } => {
+            emit_result(index_module::remove_index(&path), format, context)
         }
-        return;
     }
+}
 
-    if let Some(path) = &args.config_load {
-        match config::Config::load_from_file(path) {
-            Ok(cfg) => {
-                println!("Configuration loaded from: {}", path.display());
-                cfg.display();
+fn run_archive_command(command: ArchiveCommand, format: OutputFormat) -> u8 {
+    let context = "Archive error";
+    match command {
+        ArchiveCommand::Zip(action) => match action {
+            ArchiveAction::Create { source, output } => emit_result(
+                archive_module::create_zip(&source, &output),
+                format,
+                context,
+            ),
+            ArchiveAction::Extract {
+                archive,
+                output_path,
+            } => emit_result(
+                archive_module::extract_zip(&archive, &output_path),
+                format,
+                context,
+            ),
+            ArchiveAction::List { archive } => {
+                emit_result(archive_module::list_zip_contents(&archive), format, context)
             }
-            Err(e) => print_error_message(&format!("Error loading config: {}\n", e)),
-        }
-        return;
+        },
+        ArchiveCommand::Tar(action) => match action {
+            ArchiveAction::Create { source, output } => emit_result(
+                archive_module::create_tar_gz(&source, &output),
+                format,
+                context,
+            ),
+            ArchiveAction::Extract {
+                archive,
+                output_path,
+            } => emit_result(
+                archive_module::extract_tar_gz(&archive, &output_path),
+                format,
+                context,
+            ),
+            ArchiveAction::List { archive } => emit_result(
+                archive_module::list_tar_gz_contents(&archive),
+                format,
+                context,
+            ),
+        },
     }
+}
 
-    // ========== Web Server ==========
-    if args.server_start {
-        if let Err(e) = server_module::start_server(args.server_port).await {
-            print_error_message(&format!("Server error: {}\n", e));
-        }
-        return;
+fn run_format_command(command: FormatCommand, format: OutputFormat) -> u8 {
+    let context = "Invalid input";
+    match command {
+        FormatCommand::Json(command) => match command {
+            JsonCommand::Pretty { input } => emit_result(
+                input
+                    .read_to_string()
+                    .and_then(|input| format_module::format_json(&input)),
+                format,
+                context,
+            ),
+            JsonCommand::Minify { input } => emit_result(
+                input
+                    .read_to_string()
+                    .and_then(|input| format_module::minify_json(&input)),
+                format,
+                context,
+            ),
+            JsonCommand::Validate { input } => emit_result(
+                input
+                    .read_to_string()
+                    .map(|input| format_module::validate_json(&input)),
+                format,
+                context,
+            ),
+            JsonCommand::ToYaml { input } => emit_result(
+                input
+                    .read_to_string()
+                    .and_then(|input| format_module::json_to_yaml(&input)),
+                format,
+                context,
+            ),
+            JsonCommand::Query { input, path } => emit_result(
+                input
+                    .read_to_string()
+                    .and_then(|input| format_module::json_query(&input, &path)),
+                format,
+                context,
+            ),
+        },
+        FormatCommand::Yaml(command) => match command {
+            YamlCommand::Pretty { input } => emit_result(
+                input
+                    .read_to_string()
+                    .and_then(|input| format_module::format_yaml(&input)),
+                format,
+                context,
+            ),
+            YamlCommand::Validate { input } => emit_result(
+                input
+                    .read_to_string()
+                    .map(|input| format_module::validate_yaml(&input)),
+                format,
+                context,
+            ),
+            YamlCommand::ToJson { input } => emit_result(
+                input
+                    .read_to_string()
+                    .and_then(|input| format_module::yaml_to_json(&input)),
+                format,
+                context,
+            ),
+        },
     }
+}
 
-    // Final check - update to include new features
-    if args.name.is_none()
-        && args.url.is_none()
-        && args.search.is_none()
-        && args.copy.is_none()
-        && args.move_files.is_none()
-        && args.language.is_none()
-        && args.action.is_none()
-        && args.goto.is_none()
-        && args.data_search.is_none()
-        && !args.env_list
-        && !args.system_info
-        && !args.git_status
-        && !args.public_ip
-        && !args.config_show
-        && !args.server_start
-    {
-        print_error_message(
-            "Error: No operation specified. Use --help to see available options.\n",
-        );
+fn run_net_command(command: NetCommand, format: OutputFormat) -> u8 {
+    let context = "Request failed";
+    match command {
+        NetCommand::Port { port, host } => emit_result(
+            network_module::check_port(&host, port),
+            format,
+            "Failed to resolve address",
+        ),
+        NetCommand::Ping { host } => emit_result(network_module::ping_host(&host), format, context),
+        NetCommand::PublicIp => emit_result(
+            network_module::get_public_ip(),
+            format,
+            "Failed to fetch IP",
+        ),
+        NetCommand::Get { url } => emit_result(network_module::http_get(&url), format, context),
+        NetCommand::Post { url, body } => emit_result(
+            network_module::http_post(&url, &body, None),
+            format,
+            context,
+        ),
+        NetCommand::Dns { domain } => emit_result(
+            network_module::dns_lookup(&domain),
+            format,
+            "DNS lookup failed",
+        ),
+    }
+}
+
+fn run_config_command(command: ConfigCommand, format: OutputFormat) -> u8 {
+    let context = "Error loading config";
+    match command {
+        ConfigCommand::Show => emit_result(config::Config::load(), format, context),
+        ConfigCommand::Load { file } => emit_result(
+            config::Config::load_from_file(&file).map(|config| config::LoadedConfig {
+                source: file,
+                config,
+            }),
+            format,
+            context,
+        ),
     }
 }
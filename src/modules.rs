@@ -7,11 +7,21 @@ pub mod youtube_module;
 
 // New feature modules
 pub mod archive_module;
+pub mod duplicate_module;
 pub mod env_module;
+pub mod export_module;
 pub mod format_module;
+pub mod fuzzy_module;
 pub mod git_module;
 pub mod hash_module;
+pub mod index_module;
 pub mod network_module;
+pub mod plugin_module;
+pub mod rename_module;
+pub mod replace_module;
 pub mod server_module;
+pub mod sync_module;
 pub mod system_module;
 pub mod text_module;
+pub mod transfer_module;
+pub mod walk_module;
@@ -1,18 +1,164 @@
+use chrono::{Local, NaiveDate};
 use colored::Colorize;
 use flate2::read::GzDecoder;
 use flate2::write::GzEncoder;
 use flate2::Compression;
+use serde::Serialize;
+use std::ffi::OsString;
 use std::fs::{self, File};
 use std::io::{self, Read, Write};
-use std::path::Path;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tar::Archive;
 use walkdir::WalkDir;
 use zip::write::FileOptions;
 use zip::{ZipArchive, ZipWriter};
 
+use crate::output::Render;
+use crate::utilities::format_size;
+use crate::Result;
+
+#[derive(Debug, Serialize)]
+pub struct ArchiveCreated {
+    pub archive: PathBuf,
+    pub source: PathBuf,
+    pub entries: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArchiveExtracted {
+    pub archive: PathBuf,
+    pub destination: PathBuf,
+    pub entries: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArchiveListing {
+    pub archive: PathBuf,
+    pub entries: Vec<ArchiveEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+/// Archive formats that can be read in place by the search commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    TarGz,
+}
+
+impl ArchiveKind {
+    /// The kind of archive `path` is, judging by its extension.
+    pub fn of(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        if name.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else {
+            None
+        }
+    }
+}
+
+/// A file or directory stored in an archive.
+#[derive(Debug, Clone)]
+pub struct ArchiveMember {
+    /// Path inside the archive, without a trailing `/` for directories.
+    pub name: String,
+    pub size: u64,
+    pub is_dir: bool,
+    pub modified: Option<SystemTime>,
+}
+
+/// How a member is shown in search results: `archive.zip!inner/path`.
+pub fn member_path(archive: &Path, name: &str) -> PathBuf {
+    let mut path = OsString::from(archive.as_os_str());
+    path.push("!");
+    path.push(name);
+    PathBuf::from(path)
+}
+
+/// Call `visit` with each file and directory of the archive at `path` and a
+/// reader of its contents, until it returns `Break`. Links and other
+/// special entries are skipped.
+pub fn read_archive<F>(path: &Path, kind: ArchiveKind, mut visit: F) -> Result<()>
+where
+    F: FnMut(&ArchiveMember, &mut dyn Read) -> ControlFlow<()>,
+{
+    let file = File::open(path)?;
+    match kind {
+        ArchiveKind::Zip => {
+            let mut archive = ZipArchive::new(file)?;
+            for i in 0..archive.len() {
+                let mut entry = archive.by_index(i)?;
+                let modified = entry.last_modified();
+                let member = ArchiveMember {
+                    name: entry.name().trim_end_matches('/').to_string(),
+                    size: entry.size(),
+                    is_dir: entry.is_dir(),
+                    // Zip stores local time without a zone
+                    modified: NaiveDate::from_ymd_opt(
+                        modified.year().into(),
+                        modified.month().into(),
+                        modified.day().into(),
+                    )
+                    .and_then(|date| {
+                        date.and_hms_opt(
+                            modified.hour().into(),
+                            modified.minute().into(),
+                            modified.second().into(),
+                        )
+                    })
+                    .and_then(|time| time.and_local_timezone(Local).single())
+                    .map(SystemTime::from),
+                };
+                if visit(&member, &mut entry).is_break() {
+                    break;
+                }
+            }
+        }
+        ArchiveKind::TarGz => {
+            let mut archive = Archive::new(GzDecoder::new(file));
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                let entry_type = entry.header().entry_type();
+                if !entry_type.is_file() && !entry_type.is_dir() {
+                    continue;
+                }
+                let member = ArchiveMember {
+                    name: entry
+                        .path()?
+                        .to_string_lossy()
+                        .trim_end_matches('/')
+                        .to_string(),
+                    size: entry.size(),
+                    is_dir: entry_type.is_dir(),
+                    modified: entry
+                        .header()
+                        .mtime()
+                        .ok()
+                        .map(|seconds| UNIX_EPOCH + Duration::from_secs(seconds)),
+                };
+                if visit(&member, &mut entry).is_break() {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Create a zip archive from a directory or file
-pub fn create_zip(source: &Path, output: &Path) -> io::Result<()> {
+pub fn create_zip(source: &Path, output: &Path) -> Result<ArchiveCreated> {
     let file = File::create(output)?;
+    let mut entries = Vec::new();
     let mut zip = ZipWriter::new(file);
     let options = FileOptions::default()
         .compression_method(zip::CompressionMethod::Deflated)
@@ -25,7 +171,7 @@ pub fn create_zip(source: &Path, output: &Path) -> io::Result<()> {
         let mut buffer = Vec::new();
         f.read_to_end(&mut buffer)?;
         zip.write_all(&buffer)?;
-        println!("{} Added: {}", "✓".green(), name.yellow());
+        entries.push(name.to_string());
     } else if source.is_dir() {
         let walkdir = WalkDir::new(source);
         let it = walkdir.into_iter().filter_map(|e| e.ok());
@@ -40,11 +186,7 @@ pub fn create_zip(source: &Path, output: &Path) -> io::Result<()> {
                 let mut buffer = Vec::new();
                 f.read_to_end(&mut buffer)?;
                 zip.write_all(&buffer)?;
-                println!(
-                    "{} Added: {}",
-                    "✓".green(),
-                    name.display().to_string().yellow()
-                );
+                entries.push(name.to_string_lossy().to_string());
             } else if !name.as_os_str().is_empty() {
                 zip.add_directory(name.to_string_lossy().as_ref(), options)?;
             }
@@ -52,18 +194,18 @@ pub fn create_zip(source: &Path, output: &Path) -> io::Result<()> {
     }
 
     zip.finish()?;
-    println!(
-        "\n{} Created archive: {}",
-        "Success:".green().bold(),
-        output.display()
-    );
-    Ok(())
+    Ok(ArchiveCreated {
+        archive: output.to_path_buf(),
+        source: source.to_path_buf(),
+        entries,
+    })
 }
 
 /// Extract a zip archive
-pub fn extract_zip(archive_path: &Path, output_dir: &Path) -> io::Result<()> {
+pub fn extract_zip(archive_path: &Path, output_dir: &Path) -> Result<ArchiveExtracted> {
     let file = File::open(archive_path)?;
     let mut archive = ZipArchive::new(file)?;
+    let mut entries = Vec::new();
 
     fs::create_dir_all(output_dir)?;
 
@@ -84,141 +226,158 @@ pub fn extract_zip(archive_path: &Path, output_dir: &Path) -> io::Result<()> {
             }
             let mut outfile = File::create(&outpath)?;
             io::copy(&mut file, &mut outfile)?;
-            println!(
-                "{} Extracted: {}",
-                "✓".green(),
-                outpath.display().to_string().yellow()
-            );
+            entries.push(outpath.display().to_string());
         }
     }
 
-    println!(
-        "\n{} Extracted to: {}",
-        "Success:".green().bold(),
-        output_dir.display()
-    );
-    Ok(())
+    Ok(ArchiveExtracted {
+        archive: archive_path.to_path_buf(),
+        destination: output_dir.to_path_buf(),
+        entries,
+    })
 }
 
 /// Create a tar.gz archive
-pub fn create_tar_gz(source: &Path, output: &Path) -> io::Result<()> {
+pub fn create_tar_gz(source: &Path, output: &Path) -> Result<ArchiveCreated> {
     let tar_gz = File::create(output)?;
     let enc = GzEncoder::new(tar_gz, Compression::default());
     let mut tar = tar::Builder::new(enc);
+    let mut entries = Vec::new();
 
     if source.is_file() {
-        tar.append_path_with_name(source, source.file_name().unwrap())?;
-        println!(
-            "{} Added: {}",
-            "✓".green(),
-            source.file_name().unwrap().to_string_lossy().yellow()
-        );
+        let name = source.file_name().unwrap();
+        tar.append_path_with_name(source, name)?;
+        entries.push(name.to_string_lossy().to_string());
     } else if source.is_dir() {
         tar.append_dir_all(".", source)?;
-        println!(
-            "{} Added directory: {}",
-            "✓".green(),
-            source.display().to_string().yellow()
-        );
+        for entry in WalkDir::new(source).into_iter().filter_map(|e| e.ok()) {
+            if entry.path().is_file() {
+                let name = entry.path().strip_prefix(source).unwrap();
+                entries.push(name.to_string_lossy().to_string());
+            }
+        }
     }
 
     tar.finish()?;
-    println!(
-        "\n{} Created archive: {}",
-        "Success:".green().bold(),
-        output.display()
-    );
-    Ok(())
+    Ok(ArchiveCreated {
+        archive: output.to_path_buf(),
+        source: source.to_path_buf(),
+        entries,
+    })
 }
 
 /// Extract a tar.gz archive
-pub fn extract_tar_gz(archive_path: &Path, output_dir: &Path) -> io::Result<()> {
+pub fn extract_tar_gz(archive_path: &Path, output_dir: &Path) -> Result<ArchiveExtracted> {
     let tar_gz = File::open(archive_path)?;
     let tar = GzDecoder::new(tar_gz);
     let mut archive = Archive::new(tar);
+    let mut entries = Vec::new();
 
     fs::create_dir_all(output_dir)?;
-    archive.unpack(output_dir)?;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let is_file = entry.header().entry_type().is_file();
+        let path = output_dir.join(entry.path()?);
+        if entry.unpack_in(output_dir)? && is_file {
+            entries.push(path.display().to_string());
+        }
+    }
 
-    println!(
-        "{} Extracted to: {}",
-        "Success:".green().bold(),
-        output_dir.display()
-    );
-    Ok(())
+    Ok(ArchiveExtracted {
+        archive: archive_path.to_path_buf(),
+        destination: output_dir.to_path_buf(),
+        entries,
+    })
 }
 
 /// List contents of a zip archive
-pub fn list_zip_contents(archive_path: &Path) -> io::Result<()> {
+pub fn list_zip_contents(archive_path: &Path) -> Result<ArchiveListing> {
     let file = File::open(archive_path)?;
     let mut archive = ZipArchive::new(file)?;
-
-    println!("{}", "Archive Contents:".cyan().bold());
-    println!("{}", "=".repeat(80).cyan());
-    println!("{:60} {:>15}", "Name".yellow(), "Size".yellow());
-    println!("{}", "-".repeat(80));
+    let mut entries = Vec::new();
 
     for i in 0..archive.len() {
         let file = archive.by_index(i)?;
-        let size = file.size();
-        let name = file.name();
-
-        if name.ends_with('/') {
-            println!("{:60} {:>15}", name.cyan(), "-");
-        } else {
-            println!("{:60} {:>15}", name.green(), format_size(size));
-        }
+        entries.push(ArchiveEntry {
+            name: file.name().to_string(),
+            size: file.size(),
+            is_dir: file.is_dir(),
+        });
     }
 
-    println!("\n{} Total files: {}", "Info:".cyan().bold(), archive.len());
-    Ok(())
+    Ok(ArchiveListing {
+        archive: archive_path.to_path_buf(),
+        entries,
+    })
 }
 
 /// List contents of a tar.gz archive
-pub fn list_tar_gz_contents(archive_path: &Path) -> io::Result<()> {
+pub fn list_tar_gz_contents(archive_path: &Path) -> Result<ArchiveListing> {
     let tar_gz = File::open(archive_path)?;
     let tar = GzDecoder::new(tar_gz);
     let mut archive = Archive::new(tar);
+    let mut entries = Vec::new();
 
-    println!("{}", "Archive Contents:".cyan().bold());
-    println!("{}", "=".repeat(80).cyan());
-    println!("{:60} {:>15}", "Name".yellow(), "Size".yellow());
-    println!("{}", "-".repeat(80));
-
-    let mut count = 0;
     for entry in archive.entries()? {
         let entry = entry?;
-        let path = entry.path()?;
-        let size = entry.size();
+        entries.push(ArchiveEntry {
+            name: entry.path()?.display().to_string(),
+            size: entry.size(),
+            is_dir: entry.header().entry_type().is_dir(),
+        });
+    }
 
-        if path.to_string_lossy().ends_with('/') {
-            println!("{:60} {:>15}", path.display().to_string().cyan(), "-");
-        } else {
-            println!(
-                "{:60} {:>15}",
-                path.display().to_string().green(),
-                format_size(size)
-            );
+    Ok(ArchiveListing {
+        archive: archive_path.to_path_buf(),
+        entries,
+    })
+}
+
+impl Render for ArchiveCreated {
+    fn render(&self) {
+        for name in &self.entries {
+            println!("{} Added: {}", "✓".green(), name.yellow());
         }
-        count += 1;
+        println!(
+            "\n{} Created archive: {}",
+            "Success:".green().bold(),
+            self.archive.display()
+        );
     }
+}
 
-    println!("\n{} Total entries: {}", "Info:".cyan().bold(), count);
-    Ok(())
+impl Render for ArchiveExtracted {
+    fn render(&self) {
+        for path in &self.entries {
+            println!("{} Extracted: {}", "✓".green(), path.yellow());
+        }
+        println!(
+            "\n{} Extracted to: {}",
+            "Success:".green().bold(),
+            self.destination.display()
+        );
+    }
 }
 
-fn format_size(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
+impl Render for ArchiveListing {
+    fn render(&self) {
+        println!("{}", "Archive Contents:".cyan().bold());
+        println!("{}", "=".repeat(80).cyan());
+        println!("{:60} {:>15}", "Name".yellow(), "Size".yellow());
+        println!("{}", "-".repeat(80));
+
+        for entry in &self.entries {
+            if entry.is_dir {
+                println!("{:60} {:>15}", entry.name.cyan(), "-");
+            } else {
+                println!("{:60} {:>15}", entry.name.green(), format_size(entry.size));
+            }
+        }
 
-    if bytes >= GB {
-        format!("{:.2} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.2} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.2} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} B", bytes)
+        println!(
+            "\n{} Total entries: {}",
+            "Info:".cyan().bold(),
+            self.entries.len()
+        );
     }
 }
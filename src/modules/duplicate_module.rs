@@ -0,0 +1,447 @@
+// duplicate_module.rs
+//
+// Finding files with identical contents. Candidates are narrowed down in
+// three steps that get more expensive: files of the same size, then the same
+// hash of their first few kilobytes, then the same hash of all of it. Only
+// the last step reads whole files. Duplicates can then be deleted or
+// replaced with hard links to the copy that is kept.
+
+use clap::ValueEnum;
+use colored::Colorize;
+use log::debug;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::Read;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use crate::exit_code;
+use crate::modules::hash_module::{hash_file, HashAlgorithm};
+use crate::modules::walk_module::{walk_files, WalkOptions};
+use crate::output::Render;
+use crate::utilities::format_size;
+use crate::Result;
+
+/// Bytes read from the start of each file for the partial hash.
+const PARTIAL_BYTES: u64 = 4096;
+
+/// What to do with the duplicates of each group.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateAction {
+    /// Delete them.
+    Delete,
+    /// Replace them with hard links to the kept file.
+    Link,
+}
+
+/// Which file of a group is kept.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Keep {
+    /// The least recently modified file.
+    #[default]
+    Oldest,
+    /// The most recently modified file.
+    Newest,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DuplicateOptions {
+    /// Only look at files matching one of these globs (gitignore syntax).
+    pub include: Vec<String>,
+    /// Files and directories to leave out. Everything else is scanned,
+    /// including hidden and git-ignored files such as build outputs.
+    pub exclude: Vec<String>,
+    /// Files smaller than this are ignored; empty files are never reported.
+    pub min_size: u64,
+    pub action: Option<DuplicateAction>,
+    pub keep: Keep,
+    /// Carry out the action; without this it is only reported.
+    pub apply: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateReport {
+    /// Files looked at, after the size filter.
+    pub files: usize,
+    /// Largest waste first.
+    pub groups: Vec<DuplicateGroup>,
+    /// Bytes taken up by all copies but one of each group.
+    pub wasted: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<DuplicateAction>,
+    pub applied: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateGroup {
+    pub size: u64,
+    /// SHA-256 of the contents.
+    pub hash: String,
+    pub keep: PathBuf,
+    pub duplicates: Vec<Duplicate>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Duplicate {
+    pub path: PathBuf,
+    /// Why the action failed for this file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+struct Candidate {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+/// Find groups of identical files below `root` and, if asked to, delete or
+/// link all but one file of each group.
+pub fn find_duplicates(root: &Path, options: &DuplicateOptions) -> Result<DuplicateReport> {
+    let candidates = Mutex::new(Vec::new());
+    let seen = Mutex::new(HashSet::new());
+    let walk = WalkOptions {
+        hidden: true,
+        no_ignore: true,
+        include: options.include.clone(),
+        exclude: options.exclude.clone(),
+        ..WalkOptions::default()
+    };
+    walk_files(root, &walk, |path| {
+        let metadata = match fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                debug!("Skipping {}: {}", path.display(), e);
+                return ControlFlow::Continue(());
+            }
+        };
+        if metadata.len() == 0 || metadata.len() < options.min_size {
+            return ControlFlow::Continue(());
+        }
+        // Hard links to one file take no extra space
+        if let Some(id) = file_id(&metadata) {
+            if !seen.lock().unwrap().insert(id) {
+                return ControlFlow::Continue(());
+            }
+        }
+        candidates.lock().unwrap().push(Candidate {
+            path: path.to_path_buf(),
+            size: metadata.len(),
+            modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+        });
+        ControlFlow::Continue(())
+    })?;
+    let candidates = candidates.into_inner().unwrap();
+    let files = candidates.len();
+
+    let mut groups = Vec::new();
+    for (_, same_size) in group_by(candidates, |candidate| Some(candidate.size)) {
+        let partial = group_by(same_size, |candidate| partial_hash(&candidate.path));
+        for (_, same_start) in partial {
+            let full = group_by(same_start, |candidate| {
+                hash_file(&candidate.path, HashAlgorithm::SHA256)
+                    .map_err(|e| debug!("Skipping {}: {}", candidate.path.display(), e))
+                    .ok()
+            });
+            for (hash, same_contents) in full {
+                groups.push(duplicate_group(hash, same_contents, options.keep));
+            }
+        }
+    }
+    groups.sort_by(|a, b| {
+        let waste = |group: &DuplicateGroup| group.size * group.duplicates.len() as u64;
+        waste(b).cmp(&waste(a)).then_with(|| a.keep.cmp(&b.keep))
+    });
+
+    let apply = options.apply && options.action.is_some();
+    if let Some(action) = options.action.filter(|_| apply) {
+        for group in &mut groups {
+            for duplicate in &mut group.duplicates {
+                let result = match action {
+                    DuplicateAction::Delete => fs::remove_file(&duplicate.path),
+                    DuplicateAction::Link => replace_with_link(&group.keep, &duplicate.path),
+                };
+                duplicate.error = result.err().map(|e| e.to_string());
+            }
+        }
+    }
+
+    Ok(DuplicateReport {
+        files,
+        wasted: groups
+            .iter()
+            .map(|group| group.size * group.duplicates.len() as u64)
+            .sum(),
+        groups,
+        action: options.action,
+        applied: apply,
+    })
+}
+
+/// Split `candidates` by `key` and keep the groups of two or more. Files
+/// without a key, e.g. because they could not be read, are dropped.
+fn group_by<K, F>(candidates: Vec<Candidate>, key: F) -> Vec<(K, Vec<Candidate>)>
+where
+    K: Eq + std::hash::Hash,
+    F: Fn(&Candidate) -> Option<K>,
+{
+    let mut groups: HashMap<K, Vec<Candidate>> = HashMap::new();
+    for candidate in candidates {
+        if let Some(key) = key(&candidate) {
+            groups.entry(key).or_default().push(candidate);
+        }
+    }
+    groups
+        .into_iter()
+        .filter(|(_, group)| group.len() > 1)
+        .collect()
+}
+
+/// Hash of the first [`PARTIAL_BYTES`] of a file.
+fn partial_hash(path: &Path) -> Option<Vec<u8>> {
+    let mut start = Vec::new();
+    let read = File::open(path).and_then(|file| file.take(PARTIAL_BYTES).read_to_end(&mut start));
+    if let Err(e) = read {
+        debug!("Skipping {}: {}", path.display(), e);
+        return None;
+    }
+    Some(Sha256::digest(&start).to_vec())
+}
+
+fn duplicate_group(hash: String, mut files: Vec<Candidate>, keep: Keep) -> DuplicateGroup {
+    files.sort_by(|a, b| {
+        a.modified
+            .cmp(&b.modified)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    if keep == Keep::Newest {
+        files.reverse();
+    }
+    let mut files = files.into_iter();
+    let kept = files.next().expect("groups have two or more files");
+    DuplicateGroup {
+        size: kept.size,
+        hash,
+        keep: kept.path,
+        duplicates: files
+            .map(|file| Duplicate {
+                path: file.path,
+                error: None,
+            })
+            .collect(),
+    }
+}
+
+/// Replace `duplicate` with a hard link to `original`. The link is made
+/// under a temporary name first, so `duplicate` is never missing.
+fn replace_with_link(original: &Path, duplicate: &Path) -> std::io::Result<()> {
+    let name = duplicate.file_name().unwrap_or_default().to_string_lossy();
+    let temp = duplicate.with_file_name(format!(".{}.clh-{}.tmp", name, std::process::id()));
+    fs::hard_link(original, &temp)?;
+    fs::rename(&temp, duplicate).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })
+}
+
+#[cfg(unix)]
+fn file_id(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+impl Render for DuplicateReport {
+    fn render(&self) {
+        let marker = match (self.action, self.applied) {
+            (Some(DuplicateAction::Delete), true) => "deleted",
+            (Some(DuplicateAction::Delete), false) => "would delete",
+            (Some(DuplicateAction::Link), true) => "linked",
+            (Some(DuplicateAction::Link), false) => "would link",
+            (None, _) => "duplicate",
+        };
+        for group in &self.groups {
+            println!(
+                "{} × {} ({} wasted) {}",
+                group.duplicates.len() + 1,
+                format_size(group.size).cyan(),
+                format_size(group.size * group.duplicates.len() as u64).yellow(),
+                &group.hash[..12].dimmed()
+            );
+            println!("  {:<12} {}", "keep".green(), group.keep.display());
+            for duplicate in &group.duplicates {
+                match &duplicate.error {
+                    Some(error) => println!(
+                        "  {:<12} {}: {}",
+                        "failed".red(),
+                        duplicate.path.display(),
+                        error
+                    ),
+                    None => println!("  {:<12} {}", marker.yellow(), duplicate.path.display()),
+                }
+            }
+            println!();
+        }
+
+        let duplicates: usize = self.groups.iter().map(|group| group.duplicates.len()).sum();
+        println!(
+            "{} {} duplicate files in {} groups, {} wasted ({} files scanned)",
+            "Found".bold(),
+            duplicates,
+            self.groups.len(),
+            format_size(self.wasted),
+            self.files
+        );
+        match self.action {
+            Some(action) if self.applied => {
+                let (done, freed) = self
+                    .groups
+                    .iter()
+                    .flat_map(|group| group.duplicates.iter().map(move |d| (d, group.size)))
+                    .filter(|(duplicate, _)| duplicate.error.is_none())
+                    .fold((0, 0), |(done, freed), (_, size)| (done + 1, freed + size));
+                let verb = match action {
+                    DuplicateAction::Delete => "Deleted",
+                    DuplicateAction::Link => "Linked",
+                };
+                println!(
+                    "{} {} {} files, freeing {}",
+                    "Success:".green().bold(),
+                    verb,
+                    done,
+                    format_size(freed)
+                );
+            }
+            Some(action) if duplicates > 0 => {
+                let verb = match action {
+                    DuplicateAction::Delete => "delete",
+                    DuplicateAction::Link => "link",
+                };
+                println!(
+                    "{} Nothing was changed; run again with --apply to {} them.",
+                    "Dry run:".yellow().bold(),
+                    verb
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn exit_code(&self) -> u8 {
+        let failed = self
+            .groups
+            .iter()
+            .flat_map(|group| &group.duplicates)
+            .any(|duplicate| duplicate.error.is_some());
+        if failed {
+            exit_code::FAILURE
+        } else {
+            exit_code::SUCCESS
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn set_modified(path: &Path, time: SystemTime) {
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_full_hash_decides() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        // Same size and start, different end: only the full hash tells
+        let long = "x".repeat(PARTIAL_BYTES as usize);
+        fs::write(root.join("a.txt"), format!("{}a", long)).unwrap();
+        fs::write(root.join("a2.txt"), format!("{}a", long)).unwrap();
+        fs::write(root.join("b.txt"), format!("{}b", long)).unwrap();
+        fs::write(root.join("empty1"), "").unwrap();
+        fs::write(root.join("empty2"), "").unwrap();
+
+        let report = find_duplicates(root, &DuplicateOptions::default()).unwrap();
+        assert_eq!((report.files, report.groups.len()), (3, 1));
+        assert_eq!(report.wasted, PARTIAL_BYTES + 1);
+    }
+
+    #[test]
+    fn test_hidden_files_are_scanned_and_oldest_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join(".copies")).unwrap();
+        fs::write(root.join("a.txt"), "a").unwrap();
+        fs::write(root.join(".copies").join("a.txt"), "a").unwrap();
+        set_modified(
+            &root.join(".copies").join("a.txt"),
+            SystemTime::now() - Duration::from_secs(3600),
+        );
+
+        let report = find_duplicates(root, &DuplicateOptions::default()).unwrap();
+        let group = &report.groups[0];
+        assert_eq!(group.keep, root.join(".copies").join("a.txt"));
+        assert_eq!(group.duplicates[0].path, root.join("a.txt"));
+    }
+
+    #[test]
+    fn test_action_needs_apply() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("a.txt"), "a").unwrap();
+        fs::write(root.join("b.txt"), "a").unwrap();
+
+        let options = DuplicateOptions {
+            action: Some(DuplicateAction::Delete),
+            ..DuplicateOptions::default()
+        };
+        let report = find_duplicates(root, &options).unwrap();
+        assert!(!report.applied);
+        assert!(root.join("a.txt").exists() && root.join("b.txt").exists());
+    }
+
+    #[test]
+    fn test_link_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("a.txt"), "a").unwrap();
+        fs::write(root.join("b.txt"), "a").unwrap();
+
+        let options = DuplicateOptions {
+            action: Some(DuplicateAction::Link),
+            apply: true,
+            ..DuplicateOptions::default()
+        };
+        let linked = find_duplicates(root, &options).unwrap();
+        assert!(linked.applied && linked.groups[0].duplicates[0].error.is_none());
+        assert_eq!(fs::read_to_string(root.join("b.txt")).unwrap(), "a");
+        // Hard links are one file, so nothing is left to report
+        assert!(find_duplicates(root, &options).unwrap().groups.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hard_links_count_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("a.txt"), "a").unwrap();
+        fs::hard_link(root.join("a.txt"), root.join("b.txt")).unwrap();
+
+        let report = find_duplicates(root, &DuplicateOptions::default()).unwrap();
+        assert_eq!((report.files, report.groups.len()), (1, 0));
+    }
+}
@@ -1,43 +1,67 @@
 use colored::Colorize;
+use serde::Serialize;
 use std::env;
 use std::fs;
 use std::io::Write;
 use std::path::PathBuf;
 
-/// List all environment variables
-pub fn list_env_vars() {
-    println!("{}", "Environment Variables:".cyan().bold());
-    println!("{}", "=".repeat(80).cyan());
+use crate::output::Render;
+use crate::{Error, Result};
 
-    let mut vars: Vec<_> = env::vars().collect();
-    vars.sort_by(|a, b| a.0.cmp(&b.0));
+#[derive(Debug, Serialize)]
+pub struct EnvVar {
+    pub key: String,
+    pub value: String,
+}
 
-    for (key, value) in vars {
-        println!("{} = {}", key.green(), value.yellow());
-    }
+#[derive(Debug, Serialize)]
+pub struct EnvVarList {
+    pub vars: Vec<EnvVar>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EnvVarSet {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EnvFileLoaded {
+    pub file: PathBuf,
+    pub vars: Vec<EnvVar>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EnvExported {
+    pub file: PathBuf,
+    pub count: usize,
+}
+
+/// List all environment variables
+pub fn list_env_vars() -> EnvVarList {
+    let mut vars: Vec<_> = env::vars()
+        .map(|(key, value)| EnvVar { key, value })
+        .collect();
+    vars.sort_by(|a, b| a.key.cmp(&b.key));
+
+    EnvVarList { vars }
 }
 
 /// Get a specific environment variable
-pub fn get_env_var(key: &str) {
-    match env::var(key) {
-        Ok(value) => {
-            println!("{} = {}", key.green(), value.yellow());
-        }
-        Err(_) => {
-            eprintln!(
-                "{} Environment variable '{}' not found",
-                "Error:".red().bold(),
-                key
-            );
-        }
-    }
+pub fn get_env_var(key: &str) -> Result<EnvVar> {
+    let value = env::var(key)
+        .map_err(|_| Error::NotFound(format!("Environment variable '{}' not found", key)))?;
+    Ok(EnvVar {
+        key: key.to_string(),
+        value,
+    })
 }
 
 /// Load environment variables from a .env file
-pub fn load_env_file(file_path: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+pub fn load_env_file(file_path: &PathBuf) -> Result<EnvFileLoaded> {
     let content = fs::read_to_string(file_path)?;
 
-    let mut count = 0;
+    let mut vars = Vec::new();
     for line in content.lines() {
         let line = line.trim();
 
@@ -55,30 +79,21 @@ pub fn load_env_file(file_path: &PathBuf) -> Result<(), Box<dyn std::error::Erro
             let value = value.trim_matches('"').trim_matches('\'');
 
             env::set_var(key, value);
-            println!(
-                "{} Loaded: {} = {}",
-                "✓".green(),
-                key.cyan(),
-                value.yellow()
-            );
-            count += 1;
+            vars.push(EnvVar {
+                key: key.to_string(),
+                value: value.to_string(),
+            });
         }
     }
 
-    println!(
-        "\n{} Loaded {} environment variables from {}",
-        "Success:".green().bold(),
-        count,
-        file_path.display()
-    );
-    Ok(())
+    Ok(EnvFileLoaded {
+        file: file_path.clone(),
+        vars,
+    })
 }
 
 /// Export environment variables to a file
-pub fn export_env_vars(
-    file_path: &PathBuf,
-    filter: Option<&str>,
-) -> Result<(), Box<dyn std::error::Error>> {
+pub fn export_env_vars(file_path: &PathBuf, filter: Option<&str>) -> Result<EnvExported> {
     let mut file = fs::File::create(file_path)?;
 
     let mut vars: Vec<_> = env::vars().collect();
@@ -99,21 +114,80 @@ pub fn export_env_vars(
         count += 1;
     }
 
-    println!(
-        "{} Exported {} environment variables to {}",
-        "Success:".green().bold(),
+    Ok(EnvExported {
+        file: file_path.clone(),
         count,
-        file_path.display()
-    );
-    Ok(())
+    })
 }
 
 /// Set an environment variable (session only)
-pub fn set_env_var(key: &str, value: &str) {
+pub fn set_env_var(key: &str, value: &str) -> EnvVarSet {
     env::set_var(key, value);
-    println!("{} Set {} = {}", "✓".green(), key.cyan(), value.yellow());
-    println!(
-        "{} This change is only for the current session",
-        "Note:".yellow().bold()
-    );
+    EnvVarSet {
+        key: key.to_string(),
+        value: value.to_string(),
+    }
+}
+
+impl Render for EnvVar {
+    fn render(&self) {
+        println!("{} = {}", self.key.green(), self.value.yellow());
+    }
+}
+
+impl Render for EnvVarList {
+    fn render(&self) {
+        println!("{}", "Environment Variables:".cyan().bold());
+        println!("{}", "=".repeat(80).cyan());
+
+        for var in &self.vars {
+            var.render();
+        }
+    }
+}
+
+impl Render for EnvVarSet {
+    fn render(&self) {
+        println!(
+            "{} Set {} = {}",
+            "✓".green(),
+            self.key.cyan(),
+            self.value.yellow()
+        );
+        println!(
+            "{} This change is only for the current session",
+            "Note:".yellow().bold()
+        );
+    }
+}
+
+impl Render for EnvFileLoaded {
+    fn render(&self) {
+        for var in &self.vars {
+            println!(
+                "{} Loaded: {} = {}",
+                "✓".green(),
+                var.key.cyan(),
+                var.value.yellow()
+            );
+        }
+
+        println!(
+            "\n{} Loaded {} environment variables from {}",
+            "Success:".green().bold(),
+            self.vars.len(),
+            self.file.display()
+        );
+    }
+}
+
+impl Render for EnvExported {
+    fn render(&self) {
+        println!(
+            "{} Exported {} environment variables to {}",
+            "Success:".green().bold(),
+            self.count,
+            self.file.display()
+        );
+    }
 }
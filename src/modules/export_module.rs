@@ -0,0 +1,293 @@
+// export_module.rs
+//
+// Search results in formats meant for other tools rather than terminals:
+// JSON lines for scripts, vimgrep lines (`path:line:column:text`) for
+// editor quickfix lists and SARIF for code-review and code-scanning tools.
+// Exports are never colored. JSON lines and vimgrep are written as results
+// arrive; SARIF is a single document written at the end.
+
+use clap::ValueEnum;
+use serde_json::{json, Value};
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::modules::os_modules::FoundEntry;
+use crate::modules::search_data_module::FileMatches;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One JSON object per line.
+    Jsonl,
+    /// `path:line:column:text` for each match, as `vim -q` reads it.
+    Vimgrep,
+    /// A SARIF 2.1.0 log.
+    Sarif,
+}
+
+/// Writes search results to `out` in an export format.
+pub struct Exporter<W: Write> {
+    format: ExportFormat,
+    out: W,
+    /// SARIF rule the results are reported under, e.g. `data-search`.
+    rule: &'static str,
+    /// What the search looked for, e.g. `Lines matching 'TODO'`; the SARIF
+    /// rule description and the message of file search results.
+    description: String,
+    sarif_results: Vec<Value>,
+}
+
+impl<W: Write> Exporter<W> {
+    pub fn new(format: ExportFormat, rule: &'static str, description: String, out: W) -> Self {
+        Exporter {
+            format,
+            out,
+            rule,
+            description,
+            sarif_results: Vec::new(),
+        }
+    }
+
+    /// Export the lines of one file of a data search. Files reported
+    /// without their lines, such as binary files or counts, give a single
+    /// entry at line 1.
+    pub fn file_matches(&mut self, file: &FileMatches) -> io::Result<()> {
+        if file.lines.is_empty() {
+            return match self.format {
+                ExportFormat::Jsonl => self.json_line(json!({
+                    "type": "file",
+                    "path": file.path,
+                    "count": file.count,
+                    "binary": file.binary,
+                })),
+                ExportFormat::Vimgrep => writeln!(self.out, "{}:1:1:", file.path.display()),
+                ExportFormat::Sarif => {
+                    let message = if file.binary {
+                        "Binary file matches".to_string()
+                    } else {
+                        format!("{} matching lines", file.count)
+                    };
+                    self.sarif_result(&file.path, message, None);
+                    Ok(())
+                }
+            };
+        }
+
+        for line in &file.lines {
+            match self.format {
+                ExportFormat::Jsonl => self.json_line(json!({
+                    "type": if line.context { "context" } else { "match" },
+                    "path": file.path,
+                    "line_number": line.line_number,
+                    "line": line.line,
+                    "matches": line.matches,
+                }))?,
+                ExportFormat::Vimgrep if line.context => {}
+                ExportFormat::Vimgrep => {
+                    // Inverted matches select a line without a span
+                    let starts: Vec<usize> = if line.matches.is_empty() {
+                        vec![0]
+                    } else {
+                        line.matches.iter().map(|span| span.start).collect()
+                    };
+                    for start in starts {
+                        writeln!(
+                            self.out,
+                            "{}:{}:{}:{}",
+                            file.path.display(),
+                            line.line_number,
+                            start + 1,
+                            line.line
+                        )?;
+                    }
+                }
+                ExportFormat::Sarif if line.context => {}
+                ExportFormat::Sarif => {
+                    let whole_line = 0..line.line.len();
+                    let spans = if line.matches.is_empty() {
+                        vec![whole_line]
+                    } else {
+                        line.matches.clone()
+                    };
+                    for span in spans {
+                        // SARIF columns count characters from 1
+                        let column = |byte: usize| line.line[..byte].chars().count() + 1;
+                        let region = json!({
+                            "startLine": line.line_number,
+                            "startColumn": column(span.start),
+                            "endColumn": column(span.end),
+                            "snippet": { "text": line.line },
+                        });
+                        let message = format!("Found '{}'", &line.line[span]);
+                        self.sarif_result(&file.path, message, Some(region));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Export one entry found by a file search.
+    pub fn found_entry(&mut self, entry: &FoundEntry) -> io::Result<()> {
+        match self.format {
+            ExportFormat::Jsonl => {
+                let mut record = serde_json::to_value(entry)?;
+                record["type"] = json!("entry");
+                self.json_line(record)
+            }
+            ExportFormat::Vimgrep => writeln!(self.out, "{}:1:1:", entry.path.display()),
+            ExportFormat::Sarif => {
+                let message = self.description.clone();
+                self.sarif_result(&entry.path, message, None);
+                Ok(())
+            }
+        }
+    }
+
+    /// Write whatever is still buffered; the SARIF log is only complete
+    /// after this.
+    pub fn finish(mut self) -> io::Result<()> {
+        if self.format == ExportFormat::Sarif {
+            let log = json!({
+                "$schema": SARIF_SCHEMA,
+                "version": "2.1.0",
+                "runs": [{
+                    "tool": {
+                        "driver": {
+                            "name": env!("CARGO_PKG_NAME"),
+                            "version": env!("CARGO_PKG_VERSION"),
+                            "rules": [{
+                                "id": self.rule,
+                                "shortDescription": { "text": self.description },
+                            }],
+                        },
+                    },
+                    "columnKind": "unicodeCodePoints",
+                    "results": self.sarif_results,
+                }],
+            });
+            serde_json::to_writer_pretty(&mut self.out, &log)?;
+            writeln!(self.out)?;
+        }
+        self.out.flush()
+    }
+
+    fn json_line(&mut self, record: Value) -> io::Result<()> {
+        serde_json::to_writer(&mut self.out, &record)?;
+        writeln!(self.out)
+    }
+
+    fn sarif_result(&mut self, path: &Path, message: String, region: Option<Value>) {
+        let mut location = json!({ "artifactLocation": { "uri": sarif_uri(path) } });
+        if let Some(region) = region {
+            location["region"] = region;
+        }
+        self.sarif_results.push(json!({
+            "ruleId": self.rule,
+            "level": "warning",
+            "message": { "text": message },
+            "locations": [{ "physicalLocation": location }],
+        }));
+    }
+}
+
+/// A `file://` URI for an absolute `path`, a relative reference without the
+/// leading `./` otherwise. Everything but unreserved characters and slashes
+/// is percent-encoded, including the `!` before an archive member.
+fn sarif_uri(path: &Path) -> String {
+    let text = path.to_string_lossy().replace('\\', "/");
+    let text = text.trim_start_matches("./");
+    let mut uri = String::new();
+    if path.is_absolute() {
+        uri.push_str("file://");
+        // Windows paths start with the drive letter
+        if !text.starts_with('/') {
+            uri.push('/');
+        }
+    }
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(byte as char)
+            }
+            b':' if path.is_absolute() => uri.push(':'),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::search_data_module::LineMatch;
+    use std::path::PathBuf;
+
+    fn export(format: ExportFormat) -> String {
+        let file = FileMatches {
+            path: PathBuf::from("./src/main.rs"),
+            encoding: "UTF-8",
+            binary: false,
+            count: 1,
+            lines: vec![
+                LineMatch {
+                    line_number: 1,
+                    line: "fn main() {".to_string(),
+                    matches: Vec::new(),
+                    context: true,
+                },
+                LineMatch {
+                    line_number: 2,
+                    line: "    // TODO: é TODO".to_string(),
+                    matches: vec![7..11, 16..20],
+                    context: false,
+                },
+            ],
+        };
+        let mut out = Vec::new();
+        let mut exporter = Exporter::new(format, "data-search", "Matches 'TODO'".into(), &mut out);
+        exporter.file_matches(&file).unwrap();
+        exporter.finish().unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_export_formats() {
+        assert_eq!(
+            export(ExportFormat::Vimgrep),
+            "./src/main.rs:2:8:    // TODO: é TODO\n./src/main.rs:2:17:    // TODO: é TODO\n"
+        );
+
+        let lines: Vec<Value> = export(ExportFormat::Jsonl)
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["type"], "context");
+        assert_eq!(lines[1]["matches"][0]["start"], 7);
+
+        let sarif: Value = serde_json::from_str(&export(ExportFormat::Sarif)).unwrap();
+        let results = &sarif["runs"][0]["results"];
+        assert_eq!(results.as_array().unwrap().len(), 2);
+        let location = &results[1]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "src/main.rs");
+        // Columns count characters, so the two-byte é counts once
+        assert_eq!(location["region"]["startColumn"], 16);
+    }
+
+    #[test]
+    fn test_sarif_uri() {
+        assert_eq!(sarif_uri(Path::new("./src/main.rs")), "src/main.rs");
+        assert_eq!(
+            sarif_uri(Path::new("out/build 1.zip!inner/a.txt")),
+            "out/build%201.zip%21inner/a.txt"
+        );
+        if cfg!(unix) {
+            assert_eq!(
+                sarif_uri(Path::new("/tmp/build.zip!inner/é.txt")),
+                "file:///tmp/build.zip%21inner/%C3%A9.txt"
+            );
+        }
+    }
+}
@@ -1,124 +1,154 @@
 use colored::Colorize;
+use serde::Serialize;
 use serde_json::{self, Value};
 use serde_yaml;
 
-/// Pretty print JSON
-pub fn format_json(input: &str) {
-    match serde_json::from_str::<Value>(input) {
-        Ok(json) => match serde_json::to_string_pretty(&json) {
-            Ok(pretty) => {
-                println!("{}", "Formatted JSON:".cyan().bold());
-                println!("{}", pretty.green());
-            }
-            Err(e) => eprintln!("{} Failed to format JSON: {}", "Error:".red().bold(), e),
-        },
-        Err(e) => eprintln!("{} Invalid JSON: {}", "Error:".red().bold(), e),
+use crate::exit_code;
+use crate::output::Render;
+use crate::{Error, Result};
+
+#[derive(Debug, Serialize)]
+pub struct Formatted {
+    pub operation: &'static str,
+    pub output: String,
+    /// Heading shown above the output in text mode.
+    #[serde(skip)]
+    pub heading: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Validation {
+    pub format: &'static str,
+    pub valid: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryResult {
+    pub path: String,
+    pub value: Value,
+}
+
+fn formatted(operation: &'static str, heading: &'static str, output: String) -> Formatted {
+    Formatted {
+        operation,
+        output,
+        heading,
     }
 }
 
+/// Pretty print JSON
+pub fn format_json(input: &str) -> Result<Formatted> {
+    let json = serde_json::from_str::<Value>(input)?;
+    let pretty = serde_json::to_string_pretty(&json)?;
+    Ok(formatted("json-format", "Formatted JSON:", pretty))
+}
+
 /// Minify JSON
-pub fn minify_json(input: &str) {
-    match serde_json::from_str::<Value>(input) {
-        Ok(json) => match serde_json::to_string(&json) {
-            Ok(minified) => {
-                println!("{}", "Minified JSON:".cyan().bold());
-                println!("{}", minified.green());
-            }
-            Err(e) => eprintln!("{} Failed to minify JSON: {}", "Error:".red().bold(), e),
-        },
-        Err(e) => eprintln!("{} Invalid JSON: {}", "Error:".red().bold(), e),
-    }
+pub fn minify_json(input: &str) -> Result<Formatted> {
+    let json = serde_json::from_str::<Value>(input)?;
+    let minified = serde_json::to_string(&json)?;
+    Ok(formatted("json-minify", "Minified JSON:", minified))
 }
 
 /// Validate JSON
-pub fn validate_json(input: &str) {
-    match serde_json::from_str::<Value>(input) {
-        Ok(_) => println!("{} JSON is valid", "✓".green().bold()),
-        Err(e) => {
-            eprintln!("{} Invalid JSON", "✗".red().bold());
-            eprintln!("  Error: {}", e.to_string().yellow());
-        }
+pub fn validate_json(input: &str) -> Validation {
+    let error = serde_json::from_str::<Value>(input)
+        .err()
+        .map(|e| e.to_string());
+    Validation {
+        format: "json",
+        valid: error.is_none(),
+        error,
     }
 }
 
 /// Format YAML
-pub fn format_yaml(input: &str) {
-    match serde_yaml::from_str::<Value>(input) {
-        Ok(yaml) => match serde_yaml::to_string(&yaml) {
-            Ok(formatted) => {
-                println!("{}", "Formatted YAML:".cyan().bold());
-                println!("{}", formatted.green());
-            }
-            Err(e) => eprintln!("{} Failed to format YAML: {}", "Error:".red().bold(), e),
-        },
-        Err(e) => eprintln!("{} Invalid YAML: {}", "Error:".red().bold(), e),
-    }
+pub fn format_yaml(input: &str) -> Result<Formatted> {
+    let yaml = serde_yaml::from_str::<Value>(input)?;
+    let formatted_yaml = serde_yaml::to_string(&yaml)?;
+    Ok(formatted("yaml-format", "Formatted YAML:", formatted_yaml))
 }
 
 /// Validate YAML
-pub fn validate_yaml(input: &str) {
-    match serde_yaml::from_str::<Value>(input) {
-        Ok(_) => println!("{} YAML is valid", "✓".green().bold()),
-        Err(e) => {
-            eprintln!("{} Invalid YAML", "✗".red().bold());
-            eprintln!("  Error: {}", e.to_string().yellow());
-        }
+pub fn validate_yaml(input: &str) -> Validation {
+    let error = serde_yaml::from_str::<Value>(input)
+        .err()
+        .map(|e| e.to_string());
+    Validation {
+        format: "yaml",
+        valid: error.is_none(),
+        error,
     }
 }
 
 /// Convert JSON to YAML
-pub fn json_to_yaml(input: &str) {
-    match serde_json::from_str::<Value>(input) {
-        Ok(json) => match serde_yaml::to_string(&json) {
-            Ok(yaml) => {
-                println!("{}", "Converted to YAML:".cyan().bold());
-                println!("{}", yaml.green());
-            }
-            Err(e) => eprintln!("{} Failed to convert to YAML: {}", "Error:".red().bold(), e),
-        },
-        Err(e) => eprintln!("{} Invalid JSON input: {}", "Error:".red().bold(), e),
-    }
+pub fn json_to_yaml(input: &str) -> Result<Formatted> {
+    let json = serde_json::from_str::<Value>(input)?;
+    let yaml = serde_yaml::to_string(&json)?;
+    Ok(formatted("json-to-yaml", "Converted to YAML:", yaml))
 }
 
 /// Convert YAML to JSON
-pub fn yaml_to_json(input: &str) {
-    match serde_yaml::from_str::<Value>(input) {
-        Ok(yaml) => match serde_json::to_string_pretty(&yaml) {
-            Ok(json) => {
-                println!("{}", "Converted to JSON:".cyan().bold());
-                println!("{}", json.green());
-            }
-            Err(e) => eprintln!("{} Failed to convert to JSON: {}", "Error:".red().bold(), e),
-        },
-        Err(e) => eprintln!("{} Invalid YAML input: {}", "Error:".red().bold(), e),
-    }
+pub fn yaml_to_json(input: &str) -> Result<Formatted> {
+    let yaml = serde_yaml::from_str::<Value>(input)?;
+    let json = serde_json::to_string_pretty(&yaml)?;
+    Ok(formatted("yaml-to-json", "Converted to JSON:", json))
 }
 
 /// Get value from JSON path
-pub fn json_query(input: &str, path: &str) {
-    match serde_json::from_str::<Value>(input) {
-        Ok(json) => {
-            let parts: Vec<&str> = path.split('.').collect();
-            let mut current = &json;
-
-            for part in parts {
-                match current.get(part) {
-                    Some(value) => current = value,
-                    None => {
-                        eprintln!("{} Path not found: {}", "Error:".red().bold(), part);
-                        return;
-                    }
-                }
-            }
+pub fn json_query(input: &str, path: &str) -> Result<QueryResult> {
+    let json = serde_json::from_str::<Value>(input)?;
+    let mut current = &json;
+
+    for part in path.split('.') {
+        current = current
+            .get(part)
+            .ok_or_else(|| Error::NotFound(format!("Path not found: {}", part)))?;
+    }
+
+    Ok(QueryResult {
+        path: path.to_string(),
+        value: current.clone(),
+    })
+}
 
-            match serde_json::to_string_pretty(current) {
-                Ok(result) => {
-                    println!("{}", "Query Result:".cyan().bold());
-                    println!("{}", result.green());
-                }
-                Err(e) => eprintln!("{} Failed to format result: {}", "Error:".red().bold(), e),
+impl Render for Formatted {
+    fn render(&self) {
+        println!("{}", self.heading.cyan().bold());
+        println!("{}", self.output.green());
+    }
+}
+
+impl Render for Validation {
+    fn render(&self) {
+        let format = self.format.to_uppercase();
+        match &self.error {
+            None => println!("{} {} is valid", "✓".green().bold(), format),
+            Some(error) => {
+                eprintln!("{} Invalid {}", "✗".red().bold(), format);
+                eprintln!("  Error: {}", error.yellow());
             }
         }
-        Err(e) => eprintln!("{} Invalid JSON: {}", "Error:".red().bold(), e),
+    }
+
+    fn exit_code(&self) -> u8 {
+        if self.valid {
+            exit_code::SUCCESS
+        } else {
+            exit_code::MISMATCH
+        }
+    }
+}
+
+impl Render for QueryResult {
+    fn render(&self) {
+        println!("{}", "Query Result:".cyan().bold());
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&self.value)
+                .unwrap_or_default()
+                .green()
+        );
     }
 }
@@ -0,0 +1,282 @@
+// fuzzy_module.rs
+//
+// Fuzzy file finder. Every file below a directory is scored against the
+// query as a subsequence, fzf style: matches right after a path separator,
+// a word separator or a camelCase hump score extra, consecutive matches
+// score extra, and gaps between matches cost a little.
+
+use colored::Colorize;
+use dialoguer::console::{Key, Term};
+use serde::Serialize;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::modules::walk_module::{walk_files, WalkOptions};
+use crate::output::Render;
+use crate::utilities::print_colored_path;
+use crate::Result;
+
+const SCORE_MATCH: i64 = 16;
+const BONUS_SEPARATOR: i64 = 10;
+const BONUS_BOUNDARY: i64 = 8;
+const BONUS_CAMEL: i64 = 8;
+const BONUS_CONSECUTIVE: i64 = 5;
+const PENALTY_GAP: i64 = 1;
+
+/// Lines of results shown below the prompt in interactive mode.
+const INTERACTIVE_ROWS: usize = 15;
+
+#[derive(Debug, Serialize)]
+pub struct FuzzyFind {
+    pub query: String,
+    pub root: PathBuf,
+    pub matches: Vec<FuzzyMatch>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FuzzyMatch {
+    pub path: PathBuf,
+    pub score: i64,
+}
+
+/// Score `candidate` against `query`, or `None` if the query is not a
+/// subsequence of it. The match ignores case unless the query has an
+/// uppercase letter.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let case_sensitive = query.chars().any(char::is_uppercase);
+    let fold = |c: char| {
+        if case_sensitive {
+            c
+        } else {
+            c.to_ascii_lowercase()
+        }
+    };
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(fold)
+        .collect();
+    let chars: Vec<char> = candidate.chars().collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    // Matches in the file name count a little more than in the directories
+    let name_start = chars
+        .iter()
+        .rposition(|&c| c == '/' || c == '\\')
+        .map_or(0, |position| position + 1);
+    let bonus: Vec<i64> = (0..chars.len())
+        .map(|index| {
+            let boundary = match index.checked_sub(1).map(|previous| chars[previous]) {
+                None => BONUS_BOUNDARY,
+                Some('/' | '\\') => BONUS_SEPARATOR,
+                Some('_' | '-' | '.' | ' ') => BONUS_BOUNDARY,
+                Some(previous) if previous.is_lowercase() && chars[index].is_uppercase() => {
+                    BONUS_CAMEL
+                }
+                Some(previous) if !previous.is_ascii_digit() && chars[index].is_ascii_digit() => {
+                    BONUS_CAMEL / 2
+                }
+                _ => 0,
+            };
+            boundary + if index >= name_start { 2 } else { 0 }
+        })
+        .collect();
+
+    // best[j]: best score with the query so far matched and its last
+    // character at position j of the candidate
+    let mut best: Vec<Option<i64>> = chars
+        .iter()
+        .enumerate()
+        .map(|(j, &c)| (fold(c) == query[0]).then(|| SCORE_MATCH + bonus[j]))
+        .collect();
+
+    for &wanted in &query[1..] {
+        let mut next = vec![None; chars.len()];
+        // Best score of an earlier match, less the gap up to position j
+        let mut running: Option<i64> = None;
+        for j in 1..chars.len() {
+            running = match (running, best[j - 1]) {
+                (Some(gap), Some(previous)) => Some((gap - PENALTY_GAP).max(previous)),
+                (Some(gap), None) => Some(gap - PENALTY_GAP),
+                (None, previous) => previous,
+            };
+            if fold(chars[j]) != wanted {
+                continue;
+            }
+            let consecutive = best[j - 1].map(|previous| previous + BONUS_CONSECUTIVE);
+            next[j] = running
+                .max(consecutive)
+                .map(|score| score + SCORE_MATCH + bonus[j]);
+        }
+        best = next;
+    }
+
+    best.into_iter().flatten().max()
+}
+
+/// Rank `candidates` against `query`, best first: ties go to the shorter
+/// path, then to alphabetical order.
+pub fn rank(query: &str, candidates: &[PathBuf], limit: usize) -> Vec<FuzzyMatch> {
+    let mut matches: Vec<FuzzyMatch> = candidates
+        .iter()
+        .filter_map(|path| {
+            fuzzy_score(query, &path.to_string_lossy()).map(|score| FuzzyMatch {
+                path: path.clone(),
+                score,
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.path.as_os_str().len().cmp(&b.path.as_os_str().len()))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    if limit > 0 {
+        matches.truncate(limit);
+    }
+    matches
+}
+
+/// Every file below `root` that `options` lets through, relative to `root`.
+pub fn list_files(root: &Path, options: &WalkOptions) -> Result<Vec<PathBuf>> {
+    let files = Mutex::new(Vec::new());
+    walk_files(root, options, |path| {
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        files.lock().unwrap().push(relative.to_path_buf());
+        ControlFlow::Continue(())
+    })?;
+    Ok(files.into_inner().unwrap())
+}
+
+/// Rank the files below `root` against `query` and keep the best `limit`
+/// (0 = all).
+pub fn fuzzy_find(
+    query: &str,
+    root: &Path,
+    options: &WalkOptions,
+    limit: usize,
+) -> Result<FuzzyFind> {
+    let files = list_files(root, options)?;
+    Ok(FuzzyFind {
+        query: query.to_string(),
+        root: root.to_path_buf(),
+        matches: with_root(root, rank(query, &files, limit)),
+    })
+}
+
+/// Let the user narrow the files below `root` by typing, starting from
+/// `query`. Up and Down move the selection, Enter picks it and Esc
+/// cancels, which leaves `matches` empty. The interface is drawn on stderr
+/// so the result can be piped.
+pub fn fuzzy_find_interactive(
+    query: &str,
+    root: &Path,
+    options: &WalkOptions,
+) -> Result<FuzzyFind> {
+    let files = list_files(root, options)?;
+    let term = Term::stderr();
+    let mut query = query.to_string();
+    let mut selected = 0;
+    let mut drawn = 0;
+
+    let picked = loop {
+        let matches = rank(&query, &files, INTERACTIVE_ROWS);
+        selected = selected.min(matches.len().saturating_sub(1));
+
+        term.clear_last_lines(drawn)?;
+        term.write_line(&format!(
+            "{} {}  {}",
+            ">".cyan().bold(),
+            query,
+            format!("({} files)", files.len()).dimmed()
+        ))?;
+        for (index, found) in matches.iter().enumerate() {
+            let line = found.path.display().to_string();
+            if index == selected {
+                term.write_line(&format!("{} {}", "▶".yellow(), line.green().bold()))?;
+            } else {
+                term.write_line(&format!("  {}", line))?;
+            }
+        }
+        drawn = matches.len() + 1;
+
+        match term.read_key()? {
+            Key::Enter => break matches.get(selected).cloned(),
+            Key::Escape => break None,
+            Key::ArrowUp => selected = selected.saturating_sub(1),
+            Key::ArrowDown => selected += 1,
+            Key::Backspace => {
+                query.pop();
+                selected = 0;
+            }
+            Key::Char(c) if !c.is_control() => {
+                query.push(c);
+                selected = 0;
+            }
+            _ => {}
+        }
+    };
+    term.clear_last_lines(drawn)?;
+
+    Ok(FuzzyFind {
+        query,
+        root: root.to_path_buf(),
+        matches: with_root(root, picked.into_iter().collect()),
+    })
+}
+
+/// Turn paths relative to `root` into paths that can be opened from the
+/// current directory.
+fn with_root(root: &Path, matches: Vec<FuzzyMatch>) -> Vec<FuzzyMatch> {
+    matches
+        .into_iter()
+        .map(|found| FuzzyMatch {
+            path: root.join(found.path),
+            score: found.score,
+        })
+        .collect()
+}
+
+impl Render for FuzzyFind {
+    fn render(&self) {
+        if self.matches.is_empty() {
+            println!("{} No files match '{}'", "ℹ".cyan(), self.query);
+            return;
+        }
+        for found in &self.matches {
+            print_colored_path(&found.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score_bonuses() {
+        assert_eq!(fuzzy_score("xyz", "src/main.rs"), None);
+        assert!(fuzzy_score("fb", "FooBar.txt") > fuzzy_score("fb", "fab.txt"));
+        assert!(
+            fuzzy_score("sdm", "src/search_data_module.rs")
+                > fuzzy_score("sdm", "src/modules/sysdm.rs")
+        );
+        // Uppercase in the query makes it case-sensitive
+        assert_eq!(fuzzy_score("FB", "fab.txt"), None);
+    }
+
+    #[test]
+    fn test_rank_prefers_file_names() {
+        let candidates: Vec<PathBuf> = ["src/domain/rules.rs", "src/main.rs", "README.md"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let ranked = rank("mainrs", &candidates, 0);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].path, PathBuf::from("src/main.rs"));
+    }
+}
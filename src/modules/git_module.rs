@@ -1,240 +1,324 @@
 use colored::Colorize;
-use git2::{BranchType, IndexAddOption, Repository, Signature};
-use std::path::Path;
-
-/// Display git status for current directory
-pub fn git_status(path: &Path) {
-    match Repository::open(path) {
-        Ok(repo) => {
-            println!("{}", "Git Status".cyan().bold());
-            println!("{}", "=".repeat(80).cyan());
-
-            // Get HEAD reference
-            match repo.head() {
-                Ok(head) => {
-                    let branch_name = head.shorthand().unwrap_or("(detached)");
-                    println!("{} {}", "Branch:".yellow().bold(), branch_name.green());
-                }
-                Err(_) => println!(
-                    "{} {}",
-                    "Branch:".yellow().bold(),
-                    "(no commits yet)".yellow()
-                ),
-            }
+use git2::{BranchType, IndexAddOption, Repository, Signature, Status};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 
-            // Get status
-            match repo.statuses(None) {
-                Ok(statuses) => {
-                    if statuses.is_empty() {
-                        println!("\n{} Working tree clean", "✓".green().bold());
-                    } else {
-                        println!("\n{}", "Changes:".yellow().bold());
-                        for entry in statuses.iter() {
-                            let status = entry.status();
-                            let path = entry.path().unwrap_or("unknown");
-
-                            if status.is_wt_new() {
-                                println!("  {} {}", "??".red(), path);
-                            } else if status.is_wt_modified() {
-                                println!("  {} {}", "M ".yellow(), path);
-                            } else if status.is_wt_deleted() {
-                                println!("  {} {}", "D ".red(), path);
-                            } else if status.is_index_new() {
-                                println!("  {} {}", "A ".green(), path);
-                            } else if status.is_index_modified() {
-                                println!("  {} {}", "M ".green(), path);
-                            }
-                        }
-                    }
-                }
-                Err(e) => eprintln!("{} Failed to get status: {}", "Error:".red().bold(), e),
-            }
+use crate::output::Render;
+use crate::Result;
+
+#[derive(Debug, Serialize)]
+pub struct GitStatus {
+    /// Current branch, `None` when the repository has no commits yet.
+    pub branch: Option<String>,
+    pub changes: Vec<StatusEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatusEntry {
+    /// Short status code as printed by `git status --short` (e.g. `??`, `M `).
+    pub code: String,
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Cloned {
+    pub url: String,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BranchCreated {
+    pub branch: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChangesStaged {
+    pub entries: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Committed {
+    pub id: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BranchList {
+    pub branches: Vec<BranchEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BranchEntry {
+    pub name: String,
+    pub is_head: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GitLog {
+    pub commits: Vec<CommitEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommitEntry {
+    pub id: String,
+    pub author: String,
+    /// Commit time in seconds since the Unix epoch.
+    pub time: i64,
+    pub message: String,
+}
+
+/// Get git status for a repository
+pub fn git_status(path: &Path) -> Result<GitStatus> {
+    let repo = Repository::open(path)?;
+
+    // Get HEAD reference
+    let branch = repo
+        .head()
+        .ok()
+        .map(|head| head.shorthand().unwrap_or("(detached)").to_string());
+
+    let mut changes = Vec::new();
+    for entry in repo.statuses(None)?.iter() {
+        if let Some(code) = status_code(entry.status()) {
+            changes.push(StatusEntry {
+                code: code.to_string(),
+                path: entry.path().unwrap_or("unknown").to_string(),
+            });
         }
-        Err(e) => eprintln!("{} Not a git repository: {}", "Error:".red().bold(), e),
     }
+
+    Ok(GitStatus { branch, changes })
 }
 
-/// Clone a git repository
-pub fn git_clone(url: &str, path: &Path) {
-    println!("{} Cloning repository...", "→".cyan());
-    println!("  URL:  {}", url.yellow());
-    println!("  Path: {}", path.display().to_string().yellow());
-
-    match Repository::clone(url, path) {
-        Ok(_) => println!("\n{} Repository cloned successfully!", "✓".green().bold()),
-        Err(e) => eprintln!(
-            "{} Failed to clone repository: {}",
-            "Error:".red().bold(),
-            e
-        ),
+fn status_code(status: Status) -> Option<&'static str> {
+    if status.is_wt_new() {
+        Some("??")
+    } else if status.is_wt_modified() {
+        Some(" M")
+    } else if status.is_wt_deleted() {
+        Some(" D")
+    } else if status.is_index_new() {
+        Some("A ")
+    } else if status.is_index_modified() {
+        Some("M ")
+    } else {
+        None
     }
 }
 
+/// Clone a git repository
+pub fn git_clone(url: &str, path: &Path) -> Result<Cloned> {
+    Repository::clone(url, path)?;
+    Ok(Cloned {
+        url: url.to_string(),
+        path: path.to_path_buf(),
+    })
+}
+
 /// Create a new branch
-pub fn git_create_branch(path: &Path, branch_name: &str) {
-    match Repository::open(path) {
-        Ok(repo) => match repo.head() {
-            Ok(head) => match head.peel_to_commit() {
-                Ok(commit) => match repo.branch(branch_name, &commit, false) {
-                    Ok(_) => println!(
-                        "{} Created branch: {}",
-                        "✓".green().bold(),
-                        branch_name.green()
-                    ),
-                    Err(e) => eprintln!("{} Failed to create branch: {}", "Error:".red().bold(), e),
-                },
-                Err(e) => eprintln!("{} Failed to get commit: {}", "Error:".red().bold(), e),
-            },
-            Err(e) => eprintln!("{} Failed to get HEAD: {}", "Error:".red().bold(), e),
-        },
-        Err(e) => eprintln!("{} Not a git repository: {}", "Error:".red().bold(), e),
-    }
+pub fn git_create_branch(path: &Path, branch_name: &str) -> Result<BranchCreated> {
+    let repo = Repository::open(path)?;
+    let commit = repo.head()?.peel_to_commit()?;
+    repo.branch(branch_name, &commit, false)?;
+
+    Ok(BranchCreated {
+        branch: branch_name.to_string(),
+    })
 }
 
 /// Add all files to staging
-pub fn git_add_all(path: &Path) {
-    match Repository::open(path) {
-        Ok(repo) => match repo.index() {
-            Ok(mut index) => match index.add_all(["."].iter(), IndexAddOption::DEFAULT, None) {
-                Ok(_) => match index.write() {
-                    Ok(_) => println!("{} All changes staged", "✓".green().bold()),
-                    Err(e) => eprintln!("{} Failed to write index: {}", "Error:".red().bold(), e),
-                },
-                Err(e) => eprintln!("{} Failed to add files: {}", "Error:".red().bold(), e),
-            },
-            Err(e) => eprintln!("{} Failed to get index: {}", "Error:".red().bold(), e),
-        },
-        Err(e) => eprintln!("{} Not a git repository: {}", "Error:".red().bold(), e),
-    }
+pub fn git_add_all(path: &Path) -> Result<ChangesStaged> {
+    let repo = Repository::open(path)?;
+    let mut index = repo.index()?;
+    index.add_all(["."].iter(), IndexAddOption::DEFAULT, None)?;
+    index.write()?;
+
+    Ok(ChangesStaged {
+        entries: index.len(),
+    })
 }
 
 /// Commit changes
-pub fn git_commit(path: &Path, message: &str) {
-    match Repository::open(path) {
-        Ok(repo) => {
-            let signature = match Signature::now("CLI Helper", "cli@helper.local") {
-                Ok(sig) => sig,
-                Err(e) => {
-                    eprintln!(
-                        "{} Failed to create signature: {}",
-                        "Error:".red().bold(),
-                        e
-                    );
-                    return;
+pub fn git_commit(path: &Path, message: &str) -> Result<Committed> {
+    let repo = Repository::open(path)?;
+    let signature = Signature::now("CLI Helper", "cli@helper.local")?;
+
+    let tree_id = repo.index()?.write_tree()?;
+    let tree = repo.find_tree(tree_id)?;
+
+    let parent_commit = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let parents = if let Some(ref p) = parent_commit {
+        vec![p]
+    } else {
+        vec![]
+    };
+
+    let id = repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &parents,
+    )?;
+
+    Ok(Committed {
+        id: id.to_string(),
+        message: message.to_string(),
+    })
+}
+
+/// List branches
+pub fn git_list_branches(path: &Path) -> Result<BranchList> {
+    let repo = Repository::open(path)?;
+
+    let branches = repo
+        .branches(Some(BranchType::Local))?
+        .flatten()
+        .map(|(branch, _)| BranchEntry {
+            name: branch
+                .name()
+                .unwrap_or(Some("unknown"))
+                .unwrap_or("unknown")
+                .to_string(),
+            is_head: branch.is_head(),
+        })
+        .collect();
+
+    Ok(BranchList { branches })
+}
+
+/// Show recent commits
+pub fn git_log(path: &Path, count: usize) -> Result<GitLog> {
+    let repo = Repository::open(path)?;
+    let mut commits = Vec::new();
+
+    // No HEAD means no commits yet
+    if let Ok(head) = repo.head() {
+        let mut commit = head.peel_to_commit()?;
+        for i in 0..count {
+            commits.push(CommitEntry {
+                id: commit.id().to_string(),
+                author: commit.author().name().unwrap_or("unknown").to_string(),
+                time: commit.time().seconds(),
+                message: commit
+                    .message()
+                    .unwrap_or("(no message)")
+                    .trim()
+                    .to_string(),
+            });
+
+            if i < count - 1 {
+                match commit.parent(0) {
+                    Ok(parent) => commit = parent,
+                    Err(_) => break,
                 }
-            };
-
-            match repo.index() {
-                Ok(mut index) => match index.write_tree() {
-                    Ok(tree_id) => match repo.find_tree(tree_id) {
-                        Ok(tree) => {
-                            let parent_commit =
-                                repo.head().ok().and_then(|h| h.peel_to_commit().ok());
-                            let parents = if let Some(ref p) = parent_commit {
-                                vec![p]
-                            } else {
-                                vec![]
-                            };
-
-                            match repo.commit(
-                                Some("HEAD"),
-                                &signature,
-                                &signature,
-                                message,
-                                &tree,
-                                &parents,
-                            ) {
-                                Ok(_) => println!(
-                                    "{} Committed: {}",
-                                    "✓".green().bold(),
-                                    message.yellow()
-                                ),
-                                Err(e) => {
-                                    eprintln!("{} Failed to commit: {}", "Error:".red().bold(), e)
-                                }
-                            }
-                        }
-                        Err(e) => eprintln!("{} Failed to find tree: {}", "Error:".red().bold(), e),
-                    },
-                    Err(e) => eprintln!("{} Failed to write tree: {}", "Error:".red().bold(), e),
-                },
-                Err(e) => eprintln!("{} Failed to get index: {}", "Error:".red().bold(), e),
             }
         }
-        Err(e) => eprintln!("{} Not a git repository: {}", "Error:".red().bold(), e),
     }
+
+    Ok(GitLog { commits })
 }
 
-/// List branches
-pub fn git_list_branches(path: &Path) {
-    match Repository::open(path) {
-        Ok(repo) => {
-            println!("{}", "Branches:".cyan().bold());
-
-            match repo.branches(Some(BranchType::Local)) {
-                Ok(branches) => {
-                    for branch in branches {
-                        if let Ok((branch, _)) = branch {
-                            let name = branch
-                                .name()
-                                .unwrap_or(Some("unknown"))
-                                .unwrap_or("unknown");
-                            if branch.is_head() {
-                                println!("  {} {}", "*".green(), name.green().bold());
-                            } else {
-                                println!("    {}", name);
-                            }
-                        }
-                    }
-                }
-                Err(e) => eprintln!("{} Failed to list branches: {}", "Error:".red().bold(), e),
+impl Render for GitStatus {
+    fn render(&self) {
+        println!("{}", "Git Status".cyan().bold());
+        println!("{}", "=".repeat(80).cyan());
+
+        match &self.branch {
+            Some(branch) => println!("{} {}", "Branch:".yellow().bold(), branch.green()),
+            None => println!(
+                "{} {}",
+                "Branch:".yellow().bold(),
+                "(no commits yet)".yellow()
+            ),
+        }
+
+        if self.changes.is_empty() {
+            println!("\n{} Working tree clean", "✓".green().bold());
+        } else {
+            println!("\n{}", "Changes:".yellow().bold());
+            for change in &self.changes {
+                let code = match change.code.as_str() {
+                    "??" | " D" => change.code.red(),
+                    " M" => change.code.yellow(),
+                    _ => change.code.green(),
+                };
+                println!("  {} {}", code, change.path);
             }
         }
-        Err(e) => eprintln!("{} Not a git repository: {}", "Error:".red().bold(), e),
     }
 }
 
-/// Show recent commits
-pub fn git_log(path: &Path, count: usize) {
-    match Repository::open(path) {
-        Ok(repo) => {
-            println!("{}", "Recent Commits:".cyan().bold());
-            println!("{}", "=".repeat(80).cyan());
-
-            match repo.head() {
-                Ok(head) => match head.peel_to_commit() {
-                    Ok(mut commit) => {
-                        for i in 0..count {
-                            let id = commit.id().to_string();
-                            let short_id = &id[..7];
-                            let message = commit.message().unwrap_or("(no message)");
-                            let author_name =
-                                commit.author().name().unwrap_or("unknown").to_string();
-
-                            println!("\n{} {}", "Commit:".yellow().bold(), short_id.green());
-                            println!("Author: {}", author_name);
-                            println!("Date:   {}", format_timestamp(commit.time().seconds()));
-                            println!("\n    {}", message.trim());
-
-                            if i < count - 1 {
-                                match commit.parent(0) {
-                                    Ok(parent) => commit = parent,
-                                    Err(_) => break,
-                                }
-                            }
-                        }
-                    }
-                    Err(e) => eprintln!("{} Failed to get commit: {}", "Error:".red().bold(), e),
-                },
-                Err(_) => println!("{} No commits yet", "Info:".yellow().bold()),
+impl Render for Cloned {
+    fn render(&self) {
+        println!("{} Repository cloned successfully!", "✓".green().bold());
+        println!("  URL:  {}", self.url.yellow());
+        println!("  Path: {}", self.path.display().to_string().yellow());
+    }
+}
+
+impl Render for BranchCreated {
+    fn render(&self) {
+        println!(
+            "{} Created branch: {}",
+            "✓".green().bold(),
+            self.branch.green()
+        );
+    }
+}
+
+impl Render for ChangesStaged {
+    fn render(&self) {
+        println!("{} All changes staged", "✓".green().bold());
+    }
+}
+
+impl Render for Committed {
+    fn render(&self) {
+        println!(
+            "{} Committed {}: {}",
+            "✓".green().bold(),
+            self.id[..7].green(),
+            self.message.yellow()
+        );
+    }
+}
+
+impl Render for BranchList {
+    fn render(&self) {
+        println!("{}", "Branches:".cyan().bold());
+
+        for branch in &self.branches {
+            if branch.is_head {
+                println!("  {} {}", "*".green(), branch.name.green().bold());
+            } else {
+                println!("    {}", branch.name);
             }
         }
-        Err(e) => eprintln!("{} Not a git repository: {}", "Error:".red().bold(), e),
+    }
+}
+
+impl Render for GitLog {
+    fn render(&self) {
+        println!("{}", "Recent Commits:".cyan().bold());
+        println!("{}", "=".repeat(80).cyan());
+
+        if self.commits.is_empty() {
+            println!("{} No commits yet", "Info:".yellow().bold());
+        }
+
+        for commit in &self.commits {
+            println!("\n{} {}", "Commit:".yellow().bold(), commit.id[..7].green());
+            println!("Author: {}", commit.author);
+            println!("Date:   {}", format_timestamp(commit.time));
+            println!("\n    {}", commit.message);
+        }
     }
 }
 
 fn format_timestamp(seconds: i64) -> String {
-    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use std::time::{Duration, UNIX_EPOCH};
     let time = UNIX_EPOCH + Duration::from_secs(seconds as u64);
     match time.duration_since(UNIX_EPOCH) {
         Ok(duration) => {
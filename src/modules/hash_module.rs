@@ -1,27 +1,41 @@
 use colored::Colorize;
 use md5::{Digest as Md5Digest, Md5};
-use sha2::{Digest, Sha256, Sha512};
+use serde::Serialize;
+use sha2::{Sha256, Sha512};
 use std::fs::File;
-use std::io::{self, Read};
-use std::path::PathBuf;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
-#[derive(Debug, Clone, Copy)]
+use crate::exit_code;
+use crate::output::Render;
+use crate::Result;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum HashAlgorithm {
     MD5,
     SHA256,
     SHA512,
 }
 
-impl HashAlgorithm {
-    pub fn from_str(s: &str) -> Option<Self> {
+impl FromStr for HashAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
-            "md5" => Some(HashAlgorithm::MD5),
-            "sha256" => Some(HashAlgorithm::SHA256),
-            "sha512" => Some(HashAlgorithm::SHA512),
-            _ => None,
+            "md5" => Ok(HashAlgorithm::MD5),
+            "sha256" => Ok(HashAlgorithm::SHA256),
+            "sha512" => Ok(HashAlgorithm::SHA512),
+            _ => Err(format!(
+                "unknown hash algorithm '{}' (expected md5, sha256 or sha512)",
+                s
+            )),
         }
     }
+}
 
+impl HashAlgorithm {
     pub fn name(&self) -> &str {
         match self {
             HashAlgorithm::MD5 => "MD5",
@@ -31,26 +45,24 @@ impl HashAlgorithm {
     }
 }
 
-/// Calculate hash of a file
-pub fn hash_file(file_path: &PathBuf, algorithm: HashAlgorithm) -> Result<String, io::Error> {
+/// Calculate hash of a file, reading it in chunks
+pub fn hash_file(file_path: &Path, algorithm: HashAlgorithm) -> Result<String> {
     let mut file = File::open(file_path)?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)?;
 
     let hash = match algorithm {
         HashAlgorithm::MD5 => {
             let mut hasher = Md5::new();
-            hasher.update(&buffer);
+            io::copy(&mut file, &mut hasher)?;
             format!("{:x}", hasher.finalize())
         }
         HashAlgorithm::SHA256 => {
             let mut hasher = Sha256::new();
-            hasher.update(&buffer);
+            io::copy(&mut file, &mut hasher)?;
             format!("{:x}", hasher.finalize())
         }
         HashAlgorithm::SHA512 => {
             let mut hasher = Sha512::new();
-            hasher.update(&buffer);
+            io::copy(&mut file, &mut hasher)?;
             format!("{:x}", hasher.finalize())
         }
     };
@@ -79,84 +91,147 @@ pub fn hash_string(input: &str, algorithm: HashAlgorithm) -> String {
     }
 }
 
-/// Display hash for a file
-pub fn display_file_hash(file_path: &PathBuf, algorithm: HashAlgorithm) {
-    println!(
-        "{} Calculating {} hash for: {}",
-        "→".cyan(),
-        algorithm.name().yellow(),
-        file_path.display().to_string().green()
-    );
+#[derive(Debug, Serialize)]
+pub struct FileHash {
+    pub path: PathBuf,
+    pub algorithm: HashAlgorithm,
+    pub hash: String,
+}
 
-    match hash_file(file_path, algorithm) {
-        Ok(hash) => {
-            println!("{} {}", "Hash:".cyan().bold(), hash.bright_white());
-        }
-        Err(e) => {
-            eprintln!("{} Failed to calculate hash: {}", "Error:".red().bold(), e);
-        }
+#[derive(Debug, Serialize)]
+pub struct StringHash {
+    pub algorithm: HashAlgorithm,
+    pub hash: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HashVerification {
+    pub path: PathBuf,
+    pub algorithm: HashAlgorithm,
+    pub expected: String,
+    pub calculated: String,
+    pub matches: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AllHashes {
+    pub path: PathBuf,
+    pub hashes: Vec<FileHash>,
+}
+
+/// Calculate the hash of a file as a result record
+pub fn file_hash(file_path: &Path, algorithm: HashAlgorithm) -> Result<FileHash> {
+    Ok(FileHash {
+        path: file_path.to_path_buf(),
+        algorithm,
+        hash: hash_file(file_path, algorithm)?,
+    })
+}
+
+/// Calculate the hash of a string as a result record
+pub fn string_hash(input: &str, algorithm: HashAlgorithm) -> StringHash {
+    StringHash {
+        algorithm,
+        hash: hash_string(input, algorithm),
     }
 }
 
 /// Verify file hash against expected value
-pub fn verify_hash(file_path: &PathBuf, expected_hash: &str, algorithm: HashAlgorithm) {
-    println!(
-        "{} Verifying {} hash for: {}",
-        "→".cyan(),
-        algorithm.name().yellow(),
-        file_path.display().to_string().green()
-    );
-
-    match hash_file(file_path, algorithm) {
-        Ok(calculated_hash) => {
-            let expected_lower = expected_hash.to_lowercase();
-            let calculated_lower = calculated_hash.to_lowercase();
-
-            if expected_lower == calculated_lower {
-                println!("{} Hash verification successful!", "✓".green().bold());
-                println!("  Expected:   {}", expected_lower.bright_white());
-                println!("  Calculated: {}", calculated_lower.bright_white());
-            } else {
-                println!("{} Hash verification failed!", "✗".red().bold());
-                println!("  Expected:   {}", expected_lower.yellow());
-                println!("  Calculated: {}", calculated_lower.red());
-            }
-        }
-        Err(e) => {
-            eprintln!("{} Failed to calculate hash: {}", "Error:".red().bold(), e);
-        }
-    }
+pub fn verify_hash(
+    file_path: &Path,
+    expected_hash: &str,
+    algorithm: HashAlgorithm,
+) -> Result<HashVerification> {
+    let expected = expected_hash.to_lowercase();
+    let calculated = hash_file(file_path, algorithm)?.to_lowercase();
+
+    Ok(HashVerification {
+        path: file_path.to_path_buf(),
+        algorithm,
+        matches: expected == calculated,
+        expected,
+        calculated,
+    })
 }
 
 /// Calculate all common hashes for a file
-pub fn hash_file_all(file_path: &PathBuf) {
-    println!(
-        "{} Calculating all hashes for: {}\n",
-        "→".cyan(),
-        file_path.display().to_string().green()
-    );
-
-    for algo in [
+pub fn hash_file_all(file_path: &Path) -> Result<AllHashes> {
+    let hashes = [
         HashAlgorithm::MD5,
         HashAlgorithm::SHA256,
         HashAlgorithm::SHA512,
-    ] {
-        match hash_file(file_path, algo) {
-            Ok(hash) => {
-                println!(
-                    "{:8} {}",
-                    format!("{}:", algo.name()).cyan().bold(),
-                    hash.bright_white()
-                );
-            }
-            Err(e) => {
-                eprintln!(
-                    "{} Failed to calculate {} hash: {}",
-                    "Error:".red().bold(),
-                    algo.name(),
-                    e
-                );
-            }
+    ]
+    .into_iter()
+    .map(|algo| file_hash(file_path, algo))
+    .collect::<Result<Vec<_>>>()?;
+
+    Ok(AllHashes {
+        path: file_path.to_path_buf(),
+        hashes,
+    })
+}
+
+impl Render for FileHash {
+    fn render(&self) {
+        println!(
+            "{} Calculated {} hash for: {}",
+            "→".cyan(),
+            self.algorithm.name().yellow(),
+            self.path.display().to_string().green()
+        );
+        println!("{} {}", "Hash:".cyan().bold(), self.hash.bright_white());
+    }
+}
+
+impl Render for StringHash {
+    fn render(&self) {
+        println!("Hash: {}", self.hash);
+    }
+}
+
+impl Render for HashVerification {
+    fn render(&self) {
+        println!(
+            "{} Verifying {} hash for: {}",
+            "→".cyan(),
+            self.algorithm.name().yellow(),
+            self.path.display().to_string().green()
+        );
+
+        if self.matches {
+            println!("{} Hash verification successful!", "✓".green().bold());
+            println!("  Expected:   {}", self.expected.bright_white());
+            println!("  Calculated: {}", self.calculated.bright_white());
+        } else {
+            println!("{} Hash verification failed!", "✗".red().bold());
+            println!("  Expected:   {}", self.expected.yellow());
+            println!("  Calculated: {}", self.calculated.red());
+        }
+    }
+
+    fn exit_code(&self) -> u8 {
+        if self.matches {
+            exit_code::SUCCESS
+        } else {
+            exit_code::MISMATCH
+        }
+    }
+}
+
+impl Render for AllHashes {
+    fn render(&self) {
+        println!(
+            "{} Calculated all hashes for: {}\n",
+            "→".cyan(),
+            self.path.display().to_string().green()
+        );
+
+        for file_hash in &self.hashes {
+            println!(
+                "{:8} {}",
+                format!("{}:", file_hash.algorithm.name()).cyan().bold(),
+                file_hash.hash.bright_white()
+            );
         }
     }
 }
@@ -0,0 +1,645 @@
+// index_module.rs
+//
+// Trigram index for data searches that run over the same tree again and
+// again. For every file it records which three-character sequences occur
+// on its lines (ASCII only, lowercased). A search needs every trigram of
+// the literal text its patterns require, so files missing one of them
+// cannot match and are never read; the remaining candidates are searched
+// as usual, so the results are the same as without the index. Indexes live
+// in the user cache directory, one per root, and are updated by comparing
+// sizes and modification times.
+
+use colored::Colorize;
+use log::{debug, info};
+use regex_syntax::hir::{Class, Hir, HirKind};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+
+use crate::modules::search_data_module::{decode_text, Matcher};
+use crate::modules::walk_module::{walk_files, WalkOptions};
+use crate::output::Render;
+use crate::utilities::{format_size, write_atomic};
+use crate::{Error, Result};
+
+/// Bumped whenever the stored layout changes; older indexes are rebuilt.
+const INDEX_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize)]
+pub struct IndexReport {
+    pub root: PathBuf,
+    pub index_path: PathBuf,
+    pub files: usize,
+    pub trigrams: usize,
+    /// Size of the index on disk in bytes.
+    pub size: u64,
+    /// What the last build changed; absent when only showing the status.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changes: Option<IndexChanges>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct IndexChanges {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IndexRemoved {
+    pub root: PathBuf,
+    pub index_path: PathBuf,
+}
+
+/// The files below a search root that the index rules out.
+#[derive(Debug, Clone)]
+pub struct IndexFilter {
+    root: PathBuf,
+    indexed: Arc<HashSet<PathBuf>>,
+    /// `None` when the patterns require no trigram, so nothing is ruled out.
+    candidates: Option<Arc<HashSet<PathBuf>>>,
+}
+
+impl IndexFilter {
+    /// Whether the file at `path` (below the search root) may contain a
+    /// match. Files the index does not know, such as hidden ones, always may.
+    pub fn may_match(&self, path: &Path) -> bool {
+        let Some(candidates) = &self.candidates else {
+            return true;
+        };
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        !self.indexed.contains(relative) || candidates.contains(relative)
+    }
+}
+
+/// The index as stored: file ids are positions in `files`.
+#[derive(Serialize, Deserialize)]
+struct StoredIndex {
+    version: u32,
+    root: PathBuf,
+    files: Vec<IndexedFile>,
+    postings: BTreeMap<u32, Vec<u32>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct IndexedFile {
+    /// Relative to the root.
+    path: PathBuf,
+    size: u64,
+    mtime_ns: u64,
+}
+
+/// The index in memory: the trigrams of each file, sorted.
+#[derive(Default)]
+struct Index {
+    files: Vec<IndexedFile>,
+    trigrams: Vec<Vec<u32>>,
+}
+
+impl Index {
+    fn from_stored(stored: StoredIndex) -> Self {
+        let mut trigrams = vec![Vec::new(); stored.files.len()];
+        for (trigram, ids) in stored.postings {
+            for id in ids {
+                if let Some(file) = trigrams.get_mut(id as usize) {
+                    file.push(trigram);
+                }
+            }
+        }
+        Index {
+            files: stored.files,
+            trigrams,
+        }
+    }
+
+    fn postings(&self) -> BTreeMap<u32, Vec<u32>> {
+        let mut postings: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+        for (id, trigrams) in self.trigrams.iter().enumerate() {
+            for &trigram in trigrams {
+                postings.entry(trigram).or_default().push(id as u32);
+            }
+        }
+        postings
+    }
+}
+
+/// Build the index of `root`, or bring an existing one up to date by
+/// re-reading only files that are new or whose size or modification time
+/// changed.
+pub fn build_index(root: &Path) -> Result<IndexReport> {
+    let root = canonical_root(root)?;
+    let index_path = index_path(&root)?;
+    let old = load(&root, &index_path)?.unwrap_or_default();
+    let (index, changes) = update(&root, old)?;
+    let size = save(&root, &index_path, &index)?;
+    info!(
+        "Indexed {} files below {} ({} added, {} updated, {} removed)",
+        index.files.len(),
+        root.display(),
+        changes.added,
+        changes.updated,
+        changes.removed
+    );
+    Ok(report(root, index_path, &index, size, Some(changes)))
+}
+
+/// Describe the index of `root` without updating it.
+pub fn index_status(root: &Path) -> Result<IndexReport> {
+    let root = canonical_root(root)?;
+    let index_path = index_path(&root)?;
+    let index = load(&root, &index_path)?.ok_or_else(|| missing_index(&root))?;
+    let size = fs::metadata(&index_path)?.len();
+    Ok(report(root, index_path, &index, size, None))
+}
+
+/// Delete the index of `root`.
+pub fn remove_index(root: &Path) -> Result<IndexRemoved> {
+    let root = canonical_root(root)?;
+    let index_path = index_path(&root)?;
+    if !index_path.exists() {
+        return Err(missing_index(&root));
+    }
+    fs::remove_file(&index_path)?;
+    Ok(IndexRemoved { root, index_path })
+}
+
+/// Update the index of `root` and work out which of its files can contain
+/// a match for `matcher`. The index must have been built before.
+pub fn index_filter(matcher: &Matcher, root: &Path) -> Result<IndexFilter> {
+    let canonical = canonical_root(root)?;
+    let index_path = index_path(&canonical)?;
+    let old = load(&canonical, &index_path)?.ok_or_else(|| missing_index(&canonical))?;
+    let (index, changes) = update(&canonical, old)?;
+    if changes.added + changes.updated + changes.removed > 0 {
+        save(&canonical, &index_path, &index)?;
+    }
+
+    let candidates = plan_matcher(matcher).and_then(|query| {
+        let postings = index.postings();
+        let ids = evaluate(&query, &postings)?;
+        let paths: HashSet<PathBuf> = ids
+            .into_iter()
+            .map(|id| index.files[id as usize].path.clone())
+            .collect();
+        Some(Arc::new(paths))
+    });
+    if let Some(candidates) = &candidates {
+        debug!(
+            "Index leaves {} of {} files to search",
+            candidates.len(),
+            index.files.len()
+        );
+    }
+
+    Ok(IndexFilter {
+        root: root.to_path_buf(),
+        indexed: Arc::new(index.files.into_iter().map(|file| file.path).collect()),
+        candidates,
+    })
+}
+
+fn canonical_root(root: &Path) -> Result<PathBuf> {
+    if !root.is_dir() {
+        return Err(Error::NotFound(format!(
+            "Directory not found: {}",
+            root.display()
+        )));
+    }
+    Ok(fs::canonicalize(root)?)
+}
+
+/// Where the index of the canonical `root` is stored.
+fn index_path(root: &Path) -> Result<PathBuf> {
+    let cache_dir = dirs::cache_dir()
+        .ok_or_else(|| Error::NotFound("Could not determine the cache directory".to_string()))?;
+    let digest = Sha256::digest(root.as_os_str().as_encoded_bytes());
+    let name: String = digest[..8]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    Ok(cache_dir
+        .join("command-line-helper")
+        .join("index")
+        .join(format!("{}.json", name)))
+}
+
+fn missing_index(root: &Path) -> Error {
+    Error::NotFound(format!(
+        "No index for {}; build one with `index build {}`",
+        root.display(),
+        root.display()
+    ))
+}
+
+/// The stored index of `root`, or `None` if there is none or it was
+/// written by another version.
+fn load(root: &Path, index_path: &Path) -> Result<Option<Index>> {
+    let bytes = match fs::read(index_path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    match serde_json::from_slice::<StoredIndex>(&bytes) {
+        Ok(stored) if stored.version == INDEX_VERSION && stored.root == root => {
+            Ok(Some(Index::from_stored(stored)))
+        }
+        Ok(_) => Ok(None),
+        Err(e) => {
+            debug!("Ignoring unreadable index {}: {}", index_path.display(), e);
+            Ok(None)
+        }
+    }
+}
+
+/// Write the index and return its size in bytes.
+fn save(root: &Path, index_path: &Path, index: &Index) -> Result<u64> {
+    let stored = StoredIndex {
+        version: INDEX_VERSION,
+        root: root.to_path_buf(),
+        files: index.files.clone(),
+        postings: index.postings(),
+    };
+    let json = serde_json::to_vec(&stored)?;
+    if let Some(dir) = index_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    write_atomic(index_path, &json)?;
+    Ok(json.len() as u64)
+}
+
+fn report(
+    root: PathBuf,
+    index_path: PathBuf,
+    index: &Index,
+    size: u64,
+    changes: Option<IndexChanges>,
+) -> IndexReport {
+    let trigrams: HashSet<u32> = index.trigrams.iter().flatten().copied().collect();
+    IndexReport {
+        root,
+        index_path,
+        files: index.files.len(),
+        trigrams: trigrams.len(),
+        size,
+        changes,
+    }
+}
+
+/// Walk `root` as a default data search does and re-read the files that
+/// changed since `old` was built.
+fn update(root: &Path, old: Index) -> Result<(Index, IndexChanges)> {
+    let known: HashMap<&Path, usize> = old
+        .files
+        .iter()
+        .enumerate()
+        .map(|(id, file)| (file.path.as_path(), id))
+        .collect();
+    let seen = Mutex::new(Vec::new());
+
+    walk_files(root, &WalkOptions::default(), |path| {
+        let Ok(metadata) = fs::metadata(path) else {
+            return ControlFlow::Continue(());
+        };
+        let file = IndexedFile {
+            path: path.strip_prefix(root).unwrap_or(path).to_path_buf(),
+            size: metadata.len(),
+            mtime_ns: metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |age| age.as_nanos() as u64),
+        };
+        let previous = known.get(file.path.as_path()).copied();
+        let trigrams = match previous {
+            Some(id) if old.files[id] == file => None,
+            _ => match fs::read(path) {
+                Ok(bytes) => Some(file_trigrams(&bytes)),
+                Err(e) => {
+                    debug!("Not indexing {}: {}", path.display(), e);
+                    return ControlFlow::Continue(());
+                }
+            },
+        };
+        seen.lock().unwrap().push((file, previous, trigrams));
+        ControlFlow::Continue(())
+    })?;
+
+    let mut seen = seen.into_inner().unwrap();
+    seen.sort_by(|a, b| a.0.path.cmp(&b.0.path));
+
+    let mut changes = IndexChanges {
+        removed: old.files.len(),
+        ..IndexChanges::default()
+    };
+    let mut index = Index::default();
+    for (file, previous, trigrams) in seen {
+        if previous.is_some() {
+            changes.removed -= 1;
+        }
+        let trigrams = match (previous, trigrams) {
+            (Some(id), None) => old.trigrams[id].clone(),
+            (Some(_), Some(trigrams)) => {
+                changes.updated += 1;
+                trigrams
+            }
+            (None, trigrams) => {
+                changes.added += 1;
+                trigrams.unwrap_or_default()
+            }
+        };
+        index.files.push(file);
+        index.trigrams.push(trigrams);
+    }
+
+    Ok((index, changes))
+}
+
+/// Fold a character the way the index stores it: ASCII letters in lower
+/// case, plus the two non-ASCII characters that case-insensitive matching
+/// equates with ASCII letters. Anything else is `None`.
+fn fold(c: char) -> Option<u8> {
+    match c {
+        '\u{212A}' => Some(b'k'),
+        '\u{17F}' => Some(b's'),
+        c if c.is_ascii() => Some(c.to_ascii_lowercase() as u8),
+        _ => None,
+    }
+}
+
+/// The trigrams of a run of folded characters.
+fn trigrams(run: &[Option<u8>]) -> impl Iterator<Item = u32> + '_ {
+    run.windows(3).filter_map(|window| {
+        window.iter().try_fold(0u32, |trigram, byte| {
+            Some(trigram << 8 | u32::from((*byte)?))
+        })
+    })
+}
+
+/// The sorted trigrams on the lines of a file, decoded as a data search
+/// decodes it.
+fn file_trigrams(bytes: &[u8]) -> Vec<u32> {
+    let decoded = decode_text(bytes, None);
+    let mut found = BTreeSet::new();
+    for line in decoded.text.lines() {
+        let run: Vec<Option<u8>> = line.chars().map(fold).collect();
+        found.extend(trigrams(&run));
+    }
+    found.into_iter().collect()
+}
+
+/// Which trigrams a file needs to possibly match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Query {
+    /// Any file may match.
+    All,
+    /// Files with all of these trigrams.
+    Trigrams(Vec<u32>),
+    And(Vec<Query>),
+    Or(Vec<Query>),
+}
+
+impl Query {
+    fn literal(run: &[Option<u8>]) -> Query {
+        let trigrams: Vec<u32> = trigrams(run).collect();
+        if trigrams.is_empty() {
+            Query::All
+        } else {
+            Query::Trigrams(trigrams)
+        }
+    }
+
+    fn and(parts: Vec<Query>) -> Query {
+        let mut parts: Vec<Query> = parts.into_iter().filter(|p| *p != Query::All).collect();
+        match parts.len() {
+            0 => Query::All,
+            1 => parts.remove(0),
+            _ => Query::And(parts),
+        }
+    }
+
+    fn or(mut parts: Vec<Query>) -> Query {
+        if parts.is_empty() || parts.contains(&Query::All) {
+            Query::All
+        } else if parts.len() == 1 {
+            parts.remove(0)
+        } else {
+            Query::Or(parts)
+        }
+    }
+}
+
+/// The query for the patterns of `matcher`, or `None` if it rules nothing
+/// out. Inverted matches select lines without the patterns, so every file
+/// is a candidate.
+fn plan_matcher(matcher: &Matcher) -> Option<Query> {
+    if matcher.is_inverted() {
+        return None;
+    }
+    // Case is folded in the index, so case-insensitivity can be ignored here
+    let hir = regex_syntax::Parser::new()
+        .parse(matcher.regex().as_str())
+        .ok()?;
+    match plan(&hir) {
+        Query::All => None,
+        query => Some(query),
+    }
+}
+
+fn plan(hir: &Hir) -> Query {
+    match hir.kind() {
+        HirKind::Concat(parts) => {
+            let mut queries = Vec::new();
+            let mut run = Vec::new();
+            for part in parts {
+                if let Some(folded) = folded_literal(part) {
+                    run.extend(folded);
+                } else if !matches!(part.kind(), HirKind::Look(_) | HirKind::Empty) {
+                    queries.push(Query::literal(&run));
+                    run.clear();
+                    queries.push(plan(part));
+                }
+            }
+            queries.push(Query::literal(&run));
+            Query::and(queries)
+        }
+        HirKind::Alternation(parts) => Query::or(parts.iter().map(plan).collect()),
+        HirKind::Repetition(repetition) if repetition.min > 0 => plan(&repetition.sub),
+        HirKind::Capture(capture) => plan(&capture.sub),
+        _ => folded_literal(hir).map_or(Query::All, |run| Query::literal(&run)),
+    }
+}
+
+/// The folded characters matched by a literal, or by a class that only
+/// differs in case from a single letter as `(?i)` produces.
+fn folded_literal(hir: &Hir) -> Option<Vec<Option<u8>>> {
+    match hir.kind() {
+        HirKind::Literal(literal) => Some(
+            String::from_utf8_lossy(&literal.0)
+                .chars()
+                .map(fold)
+                .collect(),
+        ),
+        HirKind::Class(class) => {
+            let members: Vec<char> = match class {
+                Class::Unicode(class) => class
+                    .ranges()
+                    .iter()
+                    .flat_map(|range| range.start()..=range.end())
+                    .take(5)
+                    .collect(),
+                Class::Bytes(class) => class
+                    .ranges()
+                    .iter()
+                    .flat_map(|range| range.start()..=range.end())
+                    .take(5)
+                    .map(char::from)
+                    .collect(),
+            };
+            if members.len() > 4 {
+                return None;
+            }
+            let first = fold(*members.first()?)?;
+            members
+                .iter()
+                .all(|&c| fold(c) == Some(first))
+                .then(|| vec![Some(first)])
+        }
+        _ => None,
+    }
+}
+
+/// The ids of the files matching `query`, or `None` for all files.
+fn evaluate(query: &Query, postings: &BTreeMap<u32, Vec<u32>>) -> Option<BTreeSet<u32>> {
+    match query {
+        Query::All => None,
+        Query::Trigrams(trigrams) => {
+            let mut lists: Vec<&[u32]> = trigrams
+                .iter()
+                .map(|trigram| postings.get(trigram).map_or(&[][..], Vec::as_slice))
+                .collect();
+            lists.sort_by_key(|list| list.len());
+            let mut ids: BTreeSet<u32> = lists[0].iter().copied().collect();
+            for list in &lists[1..] {
+                ids.retain(|id| list.binary_search(id).is_ok());
+            }
+            Some(ids)
+        }
+        Query::And(parts) => parts
+            .iter()
+            .filter_map(|part| evaluate(part, postings))
+            .reduce(|ids, other| ids.intersection(&other).copied().collect()),
+        Query::Or(parts) => parts.iter().try_fold(BTreeSet::new(), |mut ids, part| {
+            ids.extend(evaluate(part, postings)?);
+            Some(ids)
+        }),
+    }
+}
+
+impl Render for IndexReport {
+    fn render(&self) {
+        match self.changes {
+            Some(changes) => println!(
+                "{} Indexed {} files in {} ({} added, {} updated, {} removed)",
+                "✓".green(),
+                self.files,
+                self.root.display().to_string().yellow(),
+                changes.added,
+                changes.updated,
+                changes.removed
+            ),
+            None => println!(
+                "{} Index of {}: {} files",
+                "ℹ".cyan(),
+                self.root.display().to_string().yellow(),
+                self.files
+            ),
+        }
+        println!(
+            "  {} distinct trigrams, {} in {}",
+            self.trigrams,
+            format_size(self.size),
+            self.index_path.display()
+        );
+    }
+}
+
+impl Render for IndexRemoved {
+    fn render(&self) {
+        println!(
+            "{} Removed the index of {}",
+            "✓".green(),
+            self.root.display().to_string().yellow()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::search_data_module::MatchOptions;
+
+    fn query(patterns: &[&str], options: MatchOptions) -> Option<Query> {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        plan_matcher(&Matcher::new(&patterns, options).unwrap())
+    }
+
+    fn literal(text: &str) -> Query {
+        let run: Vec<Option<u8>> = text.chars().map(fold).collect();
+        Query::literal(&run)
+    }
+
+    #[test]
+    fn test_plan_queries() {
+        let regex = MatchOptions {
+            regex: true,
+            ..MatchOptions::default()
+        };
+        assert_eq!(
+            query(&["Hello", "world"], MatchOptions::default()),
+            Some(Query::Or(vec![literal("hello"), literal("world")]))
+        );
+        assert_eq!(
+            query(&[r"fn \w+_test\("], regex),
+            Some(Query::And(vec![literal("fn "), literal("_test(")]))
+        );
+        assert_eq!(query(&["(?i)key"], regex), Some(literal("key")));
+        // Too short, optional or inverted patterns rule nothing out
+        assert_eq!(query(&["ab"], MatchOptions::default()), None);
+        assert_eq!(query(&["(foo)?bar|x+"], regex), None);
+        let invert = MatchOptions {
+            invert: true,
+            ..MatchOptions::default()
+        };
+        assert_eq!(query(&["hello"], invert), None);
+    }
+
+    #[test]
+    fn test_update_and_evaluate() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("a.txt"), "Needle in a haystack\n").unwrap();
+        fs::write(root.join("b.txt"), "only hay\n").unwrap();
+
+        let (index, changes) = update(root, Index::default()).unwrap();
+        assert_eq!(changes.added, 2);
+        let needle = |index: &Index| {
+            evaluate(&literal("needle"), &index.postings())
+                .unwrap()
+                .into_iter()
+                .map(|id| index.files[id as usize].path.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(needle(&index), vec![PathBuf::from("a.txt")]);
+
+        fs::remove_file(root.join("a.txt")).unwrap();
+        fs::write(root.join("c.txt"), "a NEEDLE\n").unwrap();
+        let (index, changes) = update(root, index).unwrap();
+        assert_eq!((changes.added, changes.updated, changes.removed), (1, 0, 1));
+        assert_eq!(needle(&index), vec![PathBuf::from("c.txt")]);
+    }
+}
@@ -1,10 +1,15 @@
 use glob::glob;
 use log::{info, warn};
 
-pub fn identify_project_type(path: &str) -> Result<&'static str, &'static str> {
+use crate::{Error, Result};
+
+pub fn identify_project_type(path: &str) -> Result<&'static str> {
     let project_types = ["rust", "gradle", "mvn", "python", "js", "dotnet", "java"];
 
-    let file_name = path.rsplit('/').next().ok_or("Invalid path")?;
+    let file_name = path
+        .rsplit('/')
+        .next()
+        .ok_or_else(|| Error::UnknownProjectType(path.to_string()))?;
 
     for &project_type in &project_types {
         if file_name.contains(project_type) || contains_project_files(path, project_type) {
@@ -14,7 +19,7 @@ pub fn identify_project_type(path: &str) -> Result<&'static str, &'static str> {
     }
 
     warn!("Unknown project type");
-    Err("Unknown project type")
+    Err(Error::UnknownProjectType(path.to_string()))
 }
 
 fn contains_project_files(path: &str, project_type: &str) -> bool {
@@ -1,9 +1,10 @@
-use crate::print_error_message;
 use log::warn;
 use std::io;
-use std::process::{exit, Command};
+use std::process::Command;
 
-pub fn execute_language_action(language: &str, action: &str) {
+use crate::{Error, Result};
+
+pub fn execute_language_action(language: &str, action: &str) -> Result<()> {
     let language_actions = map_language_actions();
 
     if let Some(actions) = language_actions.get(language) {
@@ -84,22 +85,28 @@ pub fn execute_language_action(language: &str, action: &str) {
                 ("npm", "update") => execute_command("npm", &["update"]),
                 _ => {
                     warn!("Unsupported action {} for language {}", action, language);
-                    println!("Unsupported action {} for language {}", action, language);
+                    Err(Error::InvalidInput(format!(
+                        "Unsupported action {} for language {}",
+                        action, language
+                    )))
                 }
             }
         } else {
-            println!(
+            Err(Error::InvalidInput(format!(
                 "Invalid action {} for language {}. Supported actions: {:?}",
                 action, language, actions
-            );
+            )))
         }
     } else {
         warn!("Unsupported language: {}", language);
-        println!("Unsupported language: {}", language);
+        Err(Error::InvalidInput(format!(
+            "Unsupported language: {}",
+            language
+        )))
     }
 }
 
-fn execute_command(command: &str, args: &[&str]) {
+fn execute_command(command: &str, args: &[&str]) -> Result<()> {
     let mut cmd = command.to_owned();
     if cfg!(windows) {
         // On Windows, check if the command has a `.cmd` suffix
@@ -115,29 +122,27 @@ fn execute_command(command: &str, args: &[&str]) {
 
     println!("Executing command: {} {:?}", cmd, args); // Print the command being executed
 
-    let status = Command::new(&cmd).args(args).status();
-
-    match status {
-        Ok(exit_status) => {
-            if !exit_status.success() {
-                let error_message = format!("Error executing command: {} {:?}", cmd, args);
-                print_error_message(&error_message);
-                exit(exit_status.code().unwrap_or(1));
-            } else {
-                println!("Command executed successfully.");
-            }
-        }
-        Err(err) => {
-            let error_message = format!("Error executing command: {} {:?}", cmd, args);
-            print_error_message(&error_message);
-            let error_message = format!("Error executing command: {}", err);
-            print_error_message(&error_message);
-            exit(1);
+    let status = Command::new(&cmd).args(args).status().map_err(|err| {
+        warn!("Error executing command: {} {:?}: {}", cmd, args, err);
+        Error::Command {
+            command: cmd.clone(),
+            code: None,
         }
+    })?;
+
+    if !status.success() {
+        return Err(Error::Command {
+            command: format!("{} {}", cmd, args.join(" ")),
+            code: status.code(),
+        });
     }
+
+    println!("Command executed successfully.");
+    Ok(())
 }
 
-fn map_language_actions() -> std::collections::HashMap<&'static str, Vec<&'static str>> {
+/// Supported languages and the actions each one accepts.
+pub fn map_language_actions() -> std::collections::HashMap<&'static str, Vec<&'static str>> {
     let mut map = std::collections::HashMap::new();
 
     map.insert("java", vec!["run", "build", "test"]);
@@ -180,20 +185,20 @@ mod tests {
     fn test_execute_valid_command() {
         // Test with a valid language and action
         let result = execute_language_action("cargo", "format");
-        assert_eq!(result, ());
+        assert!(result.is_ok());
     }
 
     #[test]
     fn test_execute_invalid_action() {
         // Test with a valid language but an invalid action
         let result = execute_language_action("python", "invalid_action");
-        assert_eq!(result, ());
+        assert!(matches!(result, Err(Error::InvalidInput(_))));
     }
 
     #[test]
     fn test_execute_invalid_language() {
         // Test with an invalid language
         let result = execute_language_action("invalid_language", "run");
-        assert_eq!(result, ());
+        assert!(matches!(result, Err(Error::InvalidInput(_))));
     }
 }
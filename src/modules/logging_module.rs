@@ -1,7 +1,7 @@
 use log::{info, LevelFilter};
 use log4rs;
 
-use crate::print_error_message;
+use crate::utilities::print_error_message;
 
 pub fn setup_logging(verbose: bool, out: bool) {
     // Initialize logging using log4rs programmatically
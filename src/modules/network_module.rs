@@ -1,195 +1,250 @@
 use colored::Colorize;
-use reqwest::blocking::Client;
+use reqwest::blocking::{Client, Response};
+use serde::Serialize;
 use std::net::{TcpStream, ToSocketAddrs};
 use std::time::Duration;
 
-/// Check if a port is open on a host
-pub fn check_port(host: &str, port: u16) {
-    println!(
-        "{} Checking {}:{}",
-        "→".cyan(),
-        host.yellow(),
-        port.to_string().yellow()
-    );
+use crate::exit_code;
+use crate::output::Render;
+use crate::{Error, Result};
+
+#[derive(Debug, Serialize)]
+pub struct PortCheck {
+    pub host: String,
+    pub port: u16,
+    pub open: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PingResult {
+    pub host: String,
+    pub reachable: bool,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PublicIp {
+    pub ip: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HttpResponse {
+    pub method: &'static str,
+    pub url: String,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
 
+#[derive(Debug, Serialize)]
+pub struct DnsLookup {
+    pub hostname: String,
+    pub addresses: Vec<String>,
+}
+
+/// Number of body characters shown for GET responses in text mode.
+const GET_BODY_PREVIEW: usize = 1000;
+
+/// Check if a port is open on a host
+pub fn check_port(host: &str, port: u16) -> Result<PortCheck> {
     let address = format!("{}:{}", host, port);
-    match address.to_socket_addrs() {
-        Ok(mut addrs) => {
-            if let Some(addr) = addrs.next() {
-                match TcpStream::connect_timeout(&addr, Duration::from_secs(3)) {
-                    Ok(_) => println!(
-                        "{} Port {} is OPEN",
-                        "✓".green().bold(),
-                        port.to_string().green()
-                    ),
-                    Err(_) => println!(
-                        "{} Port {} is CLOSED",
-                        "✗".red().bold(),
-                        port.to_string().red()
-                    ),
-                }
-            } else {
-                eprintln!("{} Could not resolve address", "Error:".red().bold());
-            }
-        }
-        Err(e) => eprintln!("{} Failed to resolve address: {}", "Error:".red().bold(), e),
-    }
+    let addr = address
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| Error::NotFound(format!("Could not resolve address {}", address)))?;
+
+    Ok(PortCheck {
+        host: host.to_string(),
+        port,
+        open: TcpStream::connect_timeout(&addr, Duration::from_secs(3)).is_ok(),
+    })
 }
 
 /// Ping a host (using HTTP request as fallback)
-pub fn ping_host(host: &str) {
-    println!("{} Pinging {}", "→".cyan(), host.yellow());
-
+pub fn ping_host(host: &str) -> Result<PingResult> {
     let url = if host.starts_with("http://") || host.starts_with("https://") {
         host.to_string()
     } else {
         format!("http://{}", host)
     };
 
-    let client = Client::builder()
-        .timeout(Duration::from_secs(5))
-        .build()
-        .unwrap();
+    let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
 
-    match client.head(&url).send() {
-        Ok(response) => {
-            let status = response.status();
-            println!("{} Host is reachable", "✓".green().bold());
-            println!("  Status: {}", status.to_string().green());
-        }
-        Err(e) => {
-            eprintln!("{} Host is unreachable", "✗".red().bold());
-            eprintln!("  Error: {}", e.to_string().yellow());
-        }
-    }
+    Ok(match client.head(&url).send() {
+        Ok(response) => PingResult {
+            host: host.to_string(),
+            reachable: true,
+            status: Some(response.status().as_u16()),
+            error: None,
+        },
+        Err(e) => PingResult {
+            host: host.to_string(),
+            reachable: false,
+            status: None,
+            error: Some(e.to_string()),
+        },
+    })
 }
 
 /// Get public IP address
-pub fn get_public_ip() {
-    println!("{} Fetching public IP address...", "→".cyan());
-
-    let client = Client::builder()
-        .timeout(Duration::from_secs(5))
-        .build()
-        .unwrap();
-
-    match client.get("https://api.ipify.org").send() {
-        Ok(response) => match response.text() {
-            Ok(ip) => println!("{} Your public IP: {}", "✓".green().bold(), ip.green()),
-            Err(e) => eprintln!("{} Failed to read response: {}", "Error:".red().bold(), e),
-        },
-        Err(e) => eprintln!("{} Failed to fetch IP: {}", "Error:".red().bold(), e),
-    }
+pub fn get_public_ip() -> Result<PublicIp> {
+    let client = Client::builder().timeout(Duration::from_secs(5)).build()?;
+    let ip = client.get("https://api.ipify.org").send()?.text()?;
+    Ok(PublicIp { ip })
 }
 
 /// Perform HTTP GET request
-pub fn http_get(url: &str) {
-    println!("{} GET {}", "→".cyan(), url.yellow());
+pub fn http_get(url: &str) -> Result<HttpResponse> {
+    let client = Client::builder().timeout(Duration::from_secs(10)).build()?;
+
+    into_http_response("GET", url, client.get(url).send()?)
+}
+
+/// Perform HTTP POST request
+pub fn http_post(url: &str, body: &str, content_type: Option<&str>) -> Result<HttpResponse> {
+    let client = Client::builder().timeout(Duration::from_secs(10)).build()?;
+
+    let response = client
+        .post(url)
+        .header("Content-Type", content_type.unwrap_or("application/json"))
+        .body(body.to_string())
+        .send()?;
 
-    let client = Client::builder()
-        .timeout(Duration::from_secs(10))
-        .build()
-        .unwrap();
+    into_http_response("POST", url, response)
+}
 
-    match client.get(url).send() {
-        Ok(response) => {
-            let status = response.status();
+fn into_http_response(method: &'static str, url: &str, response: Response) -> Result<HttpResponse> {
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .map(|(key, value)| {
+            (
+                key.as_str().to_string(),
+                value.to_str().unwrap_or("(binary)").to_string(),
+            )
+        })
+        .collect();
+
+    Ok(HttpResponse {
+        method,
+        url: url.to_string(),
+        status,
+        headers,
+        body: response.text()?,
+    })
+}
+
+/// DNS lookup
+pub fn dns_lookup(hostname: &str) -> Result<DnsLookup> {
+    let addresses = format!("{}:80", hostname)
+        .to_socket_addrs()?
+        .map(|addr| addr.ip().to_string())
+        .collect();
+
+    Ok(DnsLookup {
+        hostname: hostname.to_string(),
+        addresses,
+    })
+}
+
+impl Render for PortCheck {
+    fn render(&self) {
+        println!(
+            "{} Checking {}:{}",
+            "→".cyan(),
+            self.host.yellow(),
+            self.port.to_string().yellow()
+        );
+        if self.open {
             println!(
-                "\n{} {}",
-                "Status:".cyan().bold(),
-                status.to_string().green()
+                "{} Port {} is OPEN",
+                "✓".green().bold(),
+                self.port.to_string().green()
             );
+        } else {
+            println!(
+                "{} Port {} is CLOSED",
+                "✗".red().bold(),
+                self.port.to_string().red()
+            );
+        }
+    }
 
-            // Print headers
-            println!("\n{}", "Headers:".cyan().bold());
-            for (key, value) in response.headers() {
-                println!(
-                    "  {}: {}",
-                    key.as_str().yellow(),
-                    value.to_str().unwrap_or("(binary)")
-                );
-            }
-
-            // Print body
-            match response.text() {
-                Ok(body) => {
-                    println!("\n{}", "Body:".cyan().bold());
-                    let preview = if body.len() > 1000 {
-                        format!("{}... (truncated)", &body[..1000])
-                    } else {
-                        body
-                    };
-                    println!("{}", preview.green());
-                }
-                Err(e) => eprintln!("{} Failed to read body: {}", "Error:".red().bold(), e),
-            }
+    fn exit_code(&self) -> u8 {
+        if self.open {
+            exit_code::SUCCESS
+        } else {
+            exit_code::MISMATCH
         }
-        Err(e) => eprintln!("{} Request failed: {}", "Error:".red().bold(), e),
     }
 }
 
-/// Perform HTTP POST request
-pub fn http_post(url: &str, body: &str, content_type: Option<&str>) {
-    println!("{} POST {}", "→".cyan(), url.yellow());
-
-    let client = Client::builder()
-        .timeout(Duration::from_secs(10))
-        .build()
-        .unwrap();
-
-    let mut request = client.post(url);
+impl Render for PingResult {
+    fn render(&self) {
+        println!("{} Pinging {}", "→".cyan(), self.host.yellow());
+        if let Some(status) = self.status {
+            println!("{} Host is reachable", "✓".green().bold());
+            println!("  Status: {}", status.to_string().green());
+        } else {
+            eprintln!("{} Host is unreachable", "✗".red().bold());
+            if let Some(error) = &self.error {
+                eprintln!("  Error: {}", error.yellow());
+            }
+        }
+    }
 
-    if let Some(ct) = content_type {
-        request = request.header("Content-Type", ct);
-    } else {
-        request = request.header("Content-Type", "application/json");
+    fn exit_code(&self) -> u8 {
+        if self.reachable {
+            exit_code::SUCCESS
+        } else {
+            exit_code::MISMATCH
+        }
     }
+}
 
-    request = request.body(body.to_string());
+impl Render for PublicIp {
+    fn render(&self) {
+        println!("{} Your public IP: {}", "✓".green().bold(), self.ip.green());
+    }
+}
 
-    match request.send() {
-        Ok(response) => {
-            let status = response.status();
-            println!(
-                "\n{} {}",
-                "Status:".cyan().bold(),
-                status.to_string().green()
-            );
+impl Render for HttpResponse {
+    fn render(&self) {
+        println!("{} {} {}", "→".cyan(), self.method, self.url.yellow());
+        println!(
+            "\n{} {}",
+            "Status:".cyan().bold(),
+            self.status.to_string().green()
+        );
+
+        // Print headers
+        println!("\n{}", "Headers:".cyan().bold());
+        for (key, value) in &self.headers {
+            println!("  {}: {}", key.yellow(), value);
+        }
 
-            // Print headers
-            println!("\n{}", "Headers:".cyan().bold());
-            for (key, value) in response.headers() {
+        // Print body
+        println!("\n{}", "Body:".cyan().bold());
+        match self.body.char_indices().nth(GET_BODY_PREVIEW) {
+            Some((end, _)) if self.method == "GET" => {
                 println!(
-                    "  {}: {}",
-                    key.as_str().yellow(),
-                    value.to_str().unwrap_or("(binary)")
-                );
-            }
-
-            // Print body
-            match response.text() {
-                Ok(body) => {
-                    println!("\n{}", "Body:".cyan().bold());
-                    println!("{}", body.green());
-                }
-                Err(e) => eprintln!("{} Failed to read body: {}", "Error:".red().bold(), e),
+                    "{}",
+                    format!("{}... (truncated)", &self.body[..end]).green()
+                )
             }
+            _ => println!("{}", self.body.green()),
         }
-        Err(e) => eprintln!("{} Request failed: {}", "Error:".red().bold(), e),
     }
 }
 
-/// DNS lookup
-pub fn dns_lookup(hostname: &str) {
-    println!("{} Looking up {}", "→".cyan(), hostname.yellow());
-
-    match format!("{}:80", hostname).to_socket_addrs() {
-        Ok(addrs) => {
-            println!("\n{}", "IP Addresses:".cyan().bold());
-            for addr in addrs {
-                println!("  {}", addr.ip().to_string().green());
-            }
+impl Render for DnsLookup {
+    fn render(&self) {
+        println!("{} Looking up {}", "→".cyan(), self.hostname.yellow());
+        println!("\n{}", "IP Addresses:".cyan().bold());
+        for address in &self.addresses {
+            println!("  {}", address.green());
         }
-        Err(e) => eprintln!("{} DNS lookup failed: {}", "Error:".red().bold(), e),
     }
 }
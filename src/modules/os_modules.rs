@@ -1,100 +1,471 @@
-use globwalk::GlobWalkerBuilder;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime};
+use clap::ValueEnum;
+use globwalk::{FileType, GlobWalkerBuilder};
 use log::{trace, warn};
-use std::fs::{copy, rename};
-use std::io;
+use serde::Serialize;
+use std::fs;
+use std::ops::ControlFlow;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use crate::{print_colored_path, print_error_message};
-
-// Function to copy a file
-pub fn copy_file(src: &PathBuf, dest: &Path, custom_name: &Option<String>) -> io::Result<()> {
-    let src_str = src.to_str().expect("Invalid source file path");
-    let dest_file_name = custom_name.as_deref().unwrap_or_else(|| {
-        Path::new(src_str)
-            .file_name()
-            .unwrap_or_default()
-            .to_str()
-            .unwrap_or_default()
-    });
-    let dest_path = dest.join(dest_file_name);
-
-    if let Err(e) = copy(src, &dest_path) {
-        let error_message = format!(
-            "Error copying file: {} to {}: {}",
-            src.display(),
-            dest_path.display(),
-            e
-        );
-        print_error_message(&error_message);
-        warn!("{}", error_message);
-        return Err(e);
-    }
+use crate::modules::archive_module::{member_path, read_archive, ArchiveKind};
+use crate::output::Render;
+use crate::utilities::{format_size, print_colored_path, print_error_message};
+use crate::{Error, Result};
 
-    println!(
-        "File copied from {} to {}",
-        src.display(),
-        dest_path.display()
-    );
-    Ok(())
+#[derive(Debug, Serialize)]
+pub struct FileSearch {
+    pub pattern: String,
+    pub root: PathBuf,
+    pub matches: Vec<FoundEntry>,
+    /// Show the size and modification time of each entry in text mode.
+    #[serde(skip)]
+    pub long: bool,
 }
 
-// Function to move a file
-pub fn move_file(src: &PathBuf, dest: &Path, custom_name: &Option<String>) -> io::Result<()> {
-    let src_str = src.to_str().expect("Invalid source file path");
-    let dest_file_name = custom_name.as_deref().unwrap_or_else(|| {
-        Path::new(src_str)
-            .file_name()
-            .unwrap_or_default()
-            .to_str()
-            .unwrap_or_default()
-    });
-    let dest_path = dest.join(dest_file_name);
-
-    if let Err(e) = rename(src, &dest_path) {
-        let error_message = format!(
-            "Error moving file: {} to {}: {}",
-            src.display(),
-            dest_path.display(),
-            e
-        );
-        print_error_message(&error_message);
-        warn!("{}", error_message);
-        return Err(e);
-    }
+#[derive(Debug, Serialize)]
+pub struct FoundEntry {
+    pub path: PathBuf,
+    pub kind: EntryType,
+    /// Size in bytes; only set for files.
+    pub size: Option<u64>,
+    /// Modification time in seconds since the Unix epoch.
+    pub modified: Option<i64>,
+}
 
-    println!(
-        "File moved from {} to {}",
-        src.display(),
-        dest_path.display()
-    );
-    Ok(())
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryType {
+    File,
+    Dir,
+    Symlink,
 }
 
-// Function to search for files
-pub fn search_files(pattern: &String, s_path: &PathBuf, size: &usize) {
-    let search_path = PathBuf::from(s_path);
-    let walker = GlobWalkerBuilder::from_patterns(search_path, &[pattern])
-        .max_depth(if *size > 0 { *size } else { usize::MAX })
-        .build()
-        .unwrap();
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortKey {
+    Name,
+    Size,
+    Mtime,
+}
+
+/// An inclusive range of file sizes in bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SizeRange {
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+}
+
+/// Which entries `search_files` reports, and in what order.
+#[derive(Debug, Clone, Default)]
+pub struct FileFilters {
+    /// Deepest level to descend to; 1 is the entries of the searched
+    /// directory itself (0 = unlimited).
+    pub max_depth: usize,
+    /// Maximum number of results, taken after sorting (0 = unlimited).
+    pub limit: usize,
+    pub entry_type: Option<EntryType>,
+    /// Only files whose size is in this range.
+    pub size: Option<SizeRange>,
+    pub modified_since: Option<SystemTime>,
+    pub modified_before: Option<SystemTime>,
+    /// Extensions to keep, compared without case; empty keeps all.
+    pub extensions: Vec<String>,
+    /// Globs of entries to leave out.
+    pub exclude: Vec<String>,
+    pub sort: Option<SortKey>,
+    pub reverse: bool,
+    /// Also report the entries inside `.zip` and `.tar.gz` archives, as
+    /// `archive.zip!inner/path`.
+    pub archives: bool,
+}
+
+/// Search `s_path` for entries matching `pattern` that pass `filters`.
+pub fn search_files(pattern: &str, s_path: &Path, filters: &FileFilters) -> Result<FileSearch> {
+    let patterns: Vec<String> = std::iter::once(pattern.to_string())
+        .chain(filters.exclude.iter().map(|glob| format!("!{}", glob)))
+        .collect();
+    let mut builder =
+        GlobWalkerBuilder::from_patterns(s_path, &patterns).max_depth(max_depth(filters));
+    if let Some(entry_type) = filters.entry_type {
+        builder = builder.file_type(match entry_type {
+            EntryType::File => FileType::FILE,
+            EntryType::Dir => FileType::DIR,
+            EntryType::Symlink => FileType::SYMLINK,
+        });
+    }
+    let walker = builder.build().map_err(|e| Error::Pattern(e.to_string()))?;
 
-    let mut count = 0;
+    // Without sorting, the walk can stop as soon as there are enough hits
+    let early_limit = if filters.sort.is_none() && filters.limit > 0 {
+        filters.limit
+    } else {
+        usize::MAX
+    };
+
+    let mut matches = Vec::new();
     for entry in walker {
         match entry {
             Ok(entry) => {
-                if *size > 0 && count >= *size {
+                if matches.len() >= early_limit {
                     break;
                 }
-                print_colored_path(entry.path());
-                // println!("{}", entry.path().display());
-                count += 1;
-                trace!("Found file: {}", entry.path().display());
+                let Some(found) = found_entry(entry.path(), filters) else {
+                    continue;
+                };
+                trace!("Found file: {}", found.path.display());
+                matches.push(found);
             }
             Err(e) => {
-                let error_message = format!("Error during file search: {}", e);
+                let error_message = format!("Error during file search: {}\n", e);
                 print_error_message(&error_message);
-                warn!("{}", error_message);
+                warn!("{}", error_message.trim_end());
+            }
+        }
+    }
+    if filters.archives && matches.len() < early_limit {
+        search_archives(pattern, s_path, filters, early_limit, &mut matches)?;
+    }
+
+    if let Some(sort) = filters.sort {
+        matches.sort_by(|a, b| match sort {
+            SortKey::Name => a.path.cmp(&b.path),
+            SortKey::Size => a.size.cmp(&b.size).then_with(|| a.path.cmp(&b.path)),
+            SortKey::Mtime => a
+                .modified
+                .cmp(&b.modified)
+                .then_with(|| a.path.cmp(&b.path)),
+        });
+    }
+    if filters.reverse {
+        matches.reverse();
+    }
+    if filters.limit > 0 {
+        matches.truncate(filters.limit);
+    }
+
+    Ok(FileSearch {
+        pattern: pattern.to_string(),
+        root: s_path.to_path_buf(),
+        matches,
+        long: false,
+    })
+}
+
+/// Add the entries of the archives below `s_path` that match `pattern`
+/// and pass `filters`, until there are `limit` matches. As for files, a
+/// pattern without a `/` is compared with the entry name only.
+fn search_archives(
+    pattern: &str,
+    s_path: &Path,
+    filters: &FileFilters,
+    limit: usize,
+    matches: &mut Vec<FoundEntry>,
+) -> Result<()> {
+    let glob = glob::Pattern::new(pattern).map_err(|e| Error::Pattern(e.to_string()))?;
+    let by_name = !pattern.contains('/');
+    let options = glob::MatchOptions {
+        require_literal_separator: true,
+        ..glob::MatchOptions::new()
+    };
+
+    let patterns: Vec<String> = ["*.zip", "*.tar.gz", "*.tgz"]
+        .iter()
+        .map(|glob| glob.to_string())
+        .chain(filters.exclude.iter().map(|glob| format!("!{}", glob)))
+        .collect();
+    let walker = GlobWalkerBuilder::from_patterns(s_path, &patterns)
+        .max_depth(max_depth(filters))
+        .case_insensitive(true)
+        .file_type(FileType::FILE)
+        .build()
+        .map_err(|e| Error::Pattern(e.to_string()))?;
+
+    for entry in walker.filter_map(|entry| entry.ok()) {
+        let archive = entry.path();
+        let Some(kind) = ArchiveKind::of(archive) else {
+            continue;
+        };
+        let result = read_archive(archive, kind, |member, _| {
+            if matches.len() >= limit {
+                return ControlFlow::Break(());
+            }
+            let name = Path::new(&member.name);
+            let is_match = if by_name {
+                name.file_name()
+                    .is_some_and(|name| glob.matches_with(&name.to_string_lossy(), options))
+            } else {
+                glob.matches_path_with(name, options)
+            };
+            let kind = if member.is_dir {
+                EntryType::Dir
+            } else {
+                EntryType::File
+            };
+            if let Some(found) = is_match
+                .then(|| {
+                    filtered_entry(
+                        member_path(archive, &member.name),
+                        kind,
+                        member.size,
+                        member.modified,
+                        filters,
+                    )
+                })
+                .flatten()
+            {
+                trace!("Found archive entry: {}", found.path.display());
+                matches.push(found);
+            }
+            ControlFlow::Continue(())
+        });
+        if let Err(e) = result {
+            warn!("Skipping unreadable archive {}: {}", archive.display(), e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Deepest level for the walker; 0 in the filters means no limit.
+fn max_depth(filters: &FileFilters) -> usize {
+    if filters.max_depth > 0 {
+        filters.max_depth
+    } else {
+        usize::MAX
+    }
+}
+
+/// The entry at `path` if it passes the size, time and extension filters.
+/// Symlinks are described by the link itself, not by their target.
+fn found_entry(path: &Path, filters: &FileFilters) -> Option<FoundEntry> {
+    let metadata = fs::symlink_metadata(path).ok()?;
+    let kind = if metadata.is_symlink() {
+        EntryType::Symlink
+    } else if metadata.is_dir() {
+        EntryType::Dir
+    } else {
+        EntryType::File
+    };
+    filtered_entry(
+        path.to_path_buf(),
+        kind,
+        metadata.len(),
+        metadata.modified().ok(),
+        filters,
+    )
+}
+
+/// The entry if it passes the type, size, time and extension filters.
+fn filtered_entry(
+    path: PathBuf,
+    kind: EntryType,
+    size: u64,
+    modified: Option<SystemTime>,
+    filters: &FileFilters,
+) -> Option<FoundEntry> {
+    if filters.entry_type.is_some_and(|wanted| wanted != kind) {
+        return None;
+    }
+    if let Some(range) = &filters.size {
+        if kind != EntryType::File || !range.contains(size) {
+            return None;
+        }
+    }
+    if filters
+        .modified_since
+        .is_some_and(|since| modified.is_none_or(|modified| modified < since))
+    {
+        return None;
+    }
+    if filters
+        .modified_before
+        .is_some_and(|before| modified.is_none_or(|modified| modified >= before))
+    {
+        return None;
+    }
+    if !filters.extensions.is_empty() {
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if !filters.extensions.iter().any(|wanted| {
+            wanted
+                .trim_start_matches('.')
+                .eq_ignore_ascii_case(&extension)
+        }) {
+            return None;
+        }
+    }
+
+    Some(FoundEntry {
+        path,
+        kind,
+        size: (kind == EntryType::File).then_some(size),
+        modified: modified
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_secs() as i64),
+    })
+}
+
+impl FromStr for SizeRange {
+    type Err = String;
+
+    /// `MIN..MAX`, `MIN..` or `..MAX`, each a number of bytes with an
+    /// optional K, M, G or T suffix (powers of 1024), e.g. `10K..2M`.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (min, max) = s.split_once("..").ok_or_else(|| {
+            format!(
+                "expected a size range like 10K..2M, 1M.. or ..4K, got '{}'",
+                s
+            )
+        })?;
+        let bound = |text: &str| -> std::result::Result<Option<u64>, String> {
+            if text.trim().is_empty() {
+                Ok(None)
+            } else {
+                parse_size(text).map(Some)
+            }
+        };
+
+        Ok(SizeRange {
+            min: bound(min)?,
+            max: bound(max)?,
+        })
+    }
+}
+
+impl SizeRange {
+    /// Both bounds are inclusive.
+    pub fn contains(&self, size: u64) -> bool {
+        self.min.is_none_or(|min| size >= min) && self.max.is_none_or(|max| size <= max)
+    }
+}
+
+fn parse_size(text: &str) -> std::result::Result<u64, String> {
+    let text = text.trim();
+    let upper = text.to_uppercase();
+    let digits = upper
+        .trim_end_matches("IB")
+        .trim_end_matches('B')
+        .trim_end_matches(['K', 'M', 'G', 'T']);
+    let unit = &upper[digits.len()..];
+    let multiplier: u64 = match unit.trim_end_matches("IB").trim_end_matches('B') {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => return Err(format!("invalid size '{}'", text)),
+    };
+    let number: f64 = digits
+        .parse()
+        .map_err(|_| format!("invalid size '{}'", text))?;
+    if !number.is_finite() || number < 0.0 {
+        return Err(format!("invalid size '{}'", text));
+    }
+    Ok((number * multiplier as f64) as u64)
+}
+
+/// A point in time: a local date (`2024-05-31`), a local date and time
+/// (`2024-05-31 14:00` or with `T`), or an age such as `90m`, `12h`, `7d`
+/// or `2w` before now.
+pub fn parse_time(text: &str) -> std::result::Result<SystemTime, String> {
+    let text = text.trim();
+    let invalid = || {
+        format!(
+            "invalid time '{}' (expected YYYY-MM-DD, YYYY-MM-DD HH:MM or an age like 7d)",
+            text
+        )
+    };
+
+    if let Some(unit) = text.chars().last().filter(|c| c.is_ascii_alphabetic()) {
+        let seconds: u64 = match unit {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86400,
+            'w' => 7 * 86400,
+            _ => return Err(invalid()),
+        };
+        let count: u64 = text[..text.len() - 1].parse().map_err(|_| invalid())?;
+        let age = count.checked_mul(seconds).ok_or_else(invalid)?;
+        return SystemTime::now()
+            .checked_sub(Duration::from_secs(age))
+            .ok_or_else(invalid);
+    }
+
+    let naive = NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .map(|date| date.and_hms_opt(0, 0, 0).unwrap())
+        .or_else(|_| NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M"))
+        .or_else(|_| NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M"))
+        .or_else(|_| NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S"))
+        .or_else(|_| NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S"))
+        .map_err(|_| invalid())?;
+    naive
+        .and_local_timezone(Local)
+        .earliest()
+        .map(SystemTime::from)
+        .ok_or_else(invalid)
+}
+
+impl Render for FileSearch {
+    fn render(&self) {
+        for entry in &self.matches {
+            if self.long {
+                let modified = entry
+                    .modified
+                    .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+                    .map(|time| {
+                        time.with_timezone(&Local)
+                            .format("%Y-%m-%d %H:%M")
+                            .to_string()
+                    })
+                    .unwrap_or_else(|| "-".repeat(16));
+                let size = entry
+                    .size
+                    .map(format_size)
+                    .unwrap_or_else(|| "-".to_string());
+                print!("{:>8}  {}  ", size, modified);
             }
+            print_colored_path(&entry.path);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size_range() {
+        let range: SizeRange = "10K..1.5MiB".parse().unwrap();
+        assert_eq!(range.min, Some(10 * 1024));
+        assert_eq!(range.max, Some(3 * 512 * 1024));
+        assert!(range.contains(10 * 1024) && !range.contains(2 << 20));
+
+        assert_eq!(
+            "..512".parse(),
+            Ok(SizeRange {
+                min: None,
+                max: Some(512)
+            })
+        );
+        assert!("10K".parse::<SizeRange>().is_err());
+        assert!("1X..".parse::<SizeRange>().is_err());
+        for size in ["-1K..", "..inf", "NaN.."] {
+            assert!(size.parse::<SizeRange>().is_err(), "{}", size);
+        }
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let week_ago = parse_time("7d").unwrap();
+        let age = SystemTime::now().duration_since(week_ago).unwrap();
+        assert!(age >= Duration::from_secs(7 * 86400) && age < Duration::from_secs(7 * 86400 + 60));
+
+        assert!(parse_time("2024-05-31").unwrap() < parse_time("2024-05-31 00:01").unwrap());
+        assert!(parse_time("yesterday").is_err());
+        assert!(parse_time("99999999999999999w").is_err());
+    }
+}
@@ -0,0 +1,170 @@
+// plugin_module.rs
+//
+// git-style extensions: an executable named `clh-<name>` anywhere on PATH
+// is run for `clh <name> ...` when `<name>` is not a built-in command.
+
+use clap::ValueEnum;
+use colored::Colorize;
+use serde::Serialize;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::Config;
+use crate::exit_code;
+use crate::output::{OutputFormat, Render};
+use crate::utilities::load_system_paths;
+use crate::{Error, Result};
+
+/// File name prefix that marks an executable as a plugin.
+pub const PLUGIN_PREFIX: &str = "clh-";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Plugin {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PluginList {
+    pub plugins: Vec<Plugin>,
+}
+
+/// Find every plugin on PATH, sorted by name. When several directories
+/// provide the same plugin, the first one on PATH wins, as in the shell.
+pub fn discover_plugins() -> PluginList {
+    let mut plugins: Vec<Plugin> = Vec::new();
+
+    for dir in load_system_paths() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(name) = plugin_name(&path) else {
+                continue;
+            };
+            if is_executable(&path) && !plugins.iter().any(|plugin| plugin.name == name) {
+                plugins.push(Plugin { name, path });
+            }
+        }
+    }
+
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+    PluginList { plugins }
+}
+
+/// Look up a single plugin by command name.
+pub fn find_plugin(name: &str) -> Option<Plugin> {
+    discover_plugins()
+        .plugins
+        .into_iter()
+        .find(|plugin| plugin.name == name)
+}
+
+/// Run a plugin with the remaining arguments and return its exit code.
+/// The plugin inherits stdio and receives the helper's context as
+/// `CLH_CWD`, `CLH_CONFIG_PATH`, `CLH_OUTPUT` and `CLH_BIN`.
+pub fn run_plugin(plugin: &Plugin, args: &[String], format: OutputFormat) -> Result<u8> {
+    let mut command = Command::new(&plugin.path);
+    command.args(args).env("CLH_OUTPUT", output_name(format));
+
+    if let Ok(cwd) = env::current_dir() {
+        command.env("CLH_CWD", cwd);
+    }
+    if let Ok(config_path) = Config::config_path() {
+        command.env("CLH_CONFIG_PATH", config_path);
+    }
+    if let Ok(exe) = env::current_exe() {
+        command.env("CLH_BIN", exe);
+    }
+
+    let status = command.status().map_err(|_| Error::Command {
+        command: plugin.path.display().to_string(),
+        code: None,
+    })?;
+
+    // A plugin killed by a signal has no exit code
+    Ok(status
+        .code()
+        .map(|code| code.clamp(0, u8::MAX as i32) as u8)
+        .unwrap_or(exit_code::FAILURE))
+}
+
+fn output_name(format: OutputFormat) -> String {
+    format
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+/// The command name for a `clh-<name>` file, without any Windows
+/// executable extension.
+fn plugin_name(path: &Path) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    let name = file_name.strip_prefix(PLUGIN_PREFIX)?;
+    let name = if cfg!(windows) {
+        match name.rsplit_once('.') {
+            Some((stem, extension))
+                if ["exe", "bat", "cmd", "ps1"].contains(&extension.to_lowercase().as_str()) =>
+            {
+                stem
+            }
+            _ => return None,
+        }
+    } else {
+        name
+    };
+
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::metadata(path)
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+impl Render for PluginList {
+    fn render(&self) {
+        if self.plugins.is_empty() {
+            println!(
+                "{} No plugins found (executables named {}<name> on PATH)",
+                "ℹ".cyan(),
+                PLUGIN_PREFIX
+            );
+            return;
+        }
+
+        println!("{}", "Plugins".cyan().bold());
+        println!("{}", "=".repeat(80).cyan());
+        for plugin in &self.plugins {
+            println!("  {:<20} {}", plugin.name.green(), plugin.path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_plugin_name() {
+        assert_eq!(
+            plugin_name(Path::new("/usr/bin/clh-deploy")),
+            Some("deploy".to_string())
+        );
+        assert_eq!(plugin_name(Path::new("/usr/bin/clh-")), None);
+        assert_eq!(plugin_name(Path::new("/usr/bin/clh")), None);
+    }
+}
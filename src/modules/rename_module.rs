@@ -0,0 +1,600 @@
+// rename_module.rs
+//
+// Renaming many files at once, by a regex replacement or a template, with
+// an optional case conversion of the result. Every batch is checked for
+// collisions before anything is renamed, and renames go through temporary
+// names so that files can swap names. Each batch is recorded in a journal
+// so that it can be undone later.
+
+use chrono::format::StrftimeItems;
+use chrono::{DateTime, Local};
+use colored::Colorize;
+use log::debug;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{self, Path, PathBuf};
+
+use crate::exit_code;
+use crate::modules::text_module::{to_case, TextCase};
+use crate::modules::transfer_module::{expand_sources, same_file};
+use crate::output::Render;
+use crate::utilities::write_atomic;
+use crate::{Error, Result};
+
+/// Batches kept in the journal; older ones can no longer be undone.
+const JOURNAL_BATCHES: usize = 50;
+
+#[derive(Debug, Clone, Default)]
+pub struct RenameOptions {
+    /// Regular expression to find in each file name.
+    pub find: Option<String>,
+    /// Replacement for `find`; `$1` and `${name}` insert capture groups.
+    pub replace: String,
+    /// New file name with `{name}`, `{ext}`, `{n}`, `{n:03}` and
+    /// `{mtime:%Y%m%d}` placeholders; `{{` and `}}` are literal braces.
+    pub template: Option<String>,
+    /// Case of the new name, without its extension.
+    pub case: Option<TextCase>,
+    /// Value of `{n}` for the first file.
+    pub start: usize,
+    /// Only report the new names.
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RenameReport {
+    pub dry_run: bool,
+    pub undo: bool,
+    pub renames: Vec<PlannedRename>,
+    /// Files whose name stays the same.
+    pub unchanged: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlannedRename {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    /// Why the batch cannot be renamed, e.g. a collision.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub problem: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Journal {
+    batches: Vec<JournalBatch>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JournalBatch {
+    time: String,
+    /// Absolute paths before and after.
+    renames: Vec<(PathBuf, PathBuf)>,
+}
+
+enum Part {
+    Literal(String),
+    Name,
+    Ext,
+    Number { width: usize, zero: bool },
+    Mtime(String),
+}
+
+/// Where the journal of rename batches is kept.
+pub fn journal_path() -> Result<PathBuf> {
+    let data_dir = dirs::data_local_dir()
+        .ok_or_else(|| Error::NotFound("Could not determine the data directory".to_string()))?;
+    Ok(data_dir
+        .join("command-line-helper")
+        .join("rename-journal.json"))
+}
+
+/// Rename `files` (paths or glob patterns) as `options` describe. If any
+/// new name is invalid or collides, nothing is renamed and the report says
+/// why. Applied batches are added to `journal`.
+pub fn rename_files(
+    files: &[PathBuf],
+    options: &RenameOptions,
+    journal: &Path,
+) -> Result<RenameReport> {
+    let find = match (&options.find, &options.template) {
+        (Some(_), Some(_)) => {
+            return Err(Error::InvalidInput(
+                "Use either a find pattern or a template, not both".to_string(),
+            ))
+        }
+        (None, None) if options.case.is_none() => {
+            return Err(Error::InvalidInput(
+                "Nothing to do: give a find pattern, a template or a case".to_string(),
+            ))
+        }
+        (Some(find), None) => Some(Regex::new(find).map_err(|e| Error::Pattern(e.to_string()))?),
+        _ => None,
+    };
+    let template = options
+        .template
+        .as_deref()
+        .map(parse_template)
+        .transpose()?;
+
+    let mut renames = Vec::new();
+    let mut unchanged = 0;
+    for (index, from) in expand_sources(files)?.into_iter().enumerate() {
+        let name = file_name(&from);
+        let mut new_name = match (&find, &template) {
+            (Some(find), _) => find
+                .replace_all(&name, options.replace.as_str())
+                .into_owned(),
+            (_, Some(parts)) => render_template(parts, &from, options.start + index)?,
+            _ => name.clone(),
+        };
+        if let Some(case) = options.case {
+            let (stem, extension) = split_name(&new_name);
+            new_name = to_case(stem, case).output + extension;
+        }
+
+        if new_name == name {
+            unchanged += 1;
+            continue;
+        }
+        let problem = invalid_name(&new_name).map(str::to_string);
+        renames.push(PlannedRename {
+            to: from.with_file_name(&new_name),
+            from,
+            problem,
+        });
+    }
+
+    finish(renames, unchanged, options.dry_run, false, |pairs| {
+        record(journal, pairs)
+    })
+}
+
+/// Reverse the last batch in `journal`.
+pub fn undo_rename(journal: &Path, dry_run: bool) -> Result<RenameReport> {
+    let mut stored = load_journal(journal)?;
+    let batch = stored
+        .batches
+        .pop()
+        .ok_or_else(|| Error::NotFound("No renames to undo".to_string()))?;
+    let renames = batch
+        .renames
+        .into_iter()
+        .map(|(original, current)| PlannedRename {
+            problem: fs::symlink_metadata(&current)
+                .is_err()
+                .then(|| "no longer exists".to_string()),
+            from: current,
+            to: original,
+        })
+        .collect();
+
+    finish(renames, 0, dry_run, true, |_| {
+        save_journal(journal, &stored)
+    })
+}
+
+/// Check `renames` for collisions and, unless there are problems or this
+/// is a dry run, carry them out and call `done` with the absolute paths.
+fn finish<F>(
+    mut renames: Vec<PlannedRename>,
+    unchanged: usize,
+    dry_run: bool,
+    undo: bool,
+    done: F,
+) -> Result<RenameReport>
+where
+    F: FnOnce(&[(PathBuf, PathBuf)]) -> Result<()>,
+{
+    let pairs: Vec<(PathBuf, PathBuf)> = renames
+        .iter()
+        .map(|rename| Ok((path::absolute(&rename.from)?, path::absolute(&rename.to)?)))
+        .collect::<Result<_>>()?;
+    find_collisions(&mut renames, &pairs);
+
+    let problems = renames.iter().any(|rename| rename.problem.is_some());
+    if !dry_run && !problems && !renames.is_empty() {
+        rename_all(&pairs)?;
+        done(&pairs)?;
+    }
+
+    Ok(RenameReport {
+        dry_run,
+        undo,
+        renames,
+        unchanged,
+    })
+}
+
+/// Mark renames whose new name is taken, by another file of the batch or
+/// by a file that is not being renamed.
+fn find_collisions(renames: &mut [PlannedRename], pairs: &[(PathBuf, PathBuf)]) {
+    let sources: HashSet<&PathBuf> = pairs.iter().map(|(from, _)| from).collect();
+    let mut targets: HashMap<&PathBuf, usize> = HashMap::new();
+    for (_, to) in pairs {
+        *targets.entry(to).or_default() += 1;
+    }
+
+    for (rename, (from, to)) in renames.iter_mut().zip(pairs) {
+        if rename.problem.is_some() {
+            continue;
+        }
+        if targets[to] > 1 {
+            rename.problem = Some("another file gets the same name".to_string());
+        } else if fs::symlink_metadata(to).is_ok()
+            && !sources.contains(to)
+            // A case-only rename on a case-insensitive filesystem
+            && !same_file(from, to).unwrap_or(false)
+        {
+            rename.problem = Some("a file with this name exists".to_string());
+        }
+    }
+}
+
+/// Rename every pair through a temporary name first, so that files can
+/// take each other's names. On failure, what was renamed is put back.
+fn rename_all(pairs: &[(PathBuf, PathBuf)]) -> Result<()> {
+    let temps: Vec<PathBuf> = pairs
+        .iter()
+        .enumerate()
+        .map(|(index, (from, _))| {
+            from.with_file_name(format!(".clh-rename-{}-{}.tmp", std::process::id(), index))
+        })
+        .collect();
+
+    for (done, ((from, _), temp)) in pairs.iter().zip(&temps).enumerate() {
+        if let Err(e) = fs::rename(from, temp) {
+            for ((from, _), temp) in pairs.iter().zip(&temps).take(done) {
+                let _ = fs::rename(temp, from);
+            }
+            return Err(e.into());
+        }
+    }
+    for (done, ((_, to), temp)) in pairs.iter().zip(&temps).enumerate() {
+        if let Err(e) = fs::rename(temp, to) {
+            for (index, ((from, to), temp)) in pairs.iter().zip(&temps).enumerate() {
+                let current = if index < done { to } else { temp };
+                let _ = fs::rename(current, from);
+            }
+            return Err(e.into());
+        }
+    }
+    Ok(())
+}
+
+fn record(journal: &Path, pairs: &[(PathBuf, PathBuf)]) -> Result<()> {
+    let mut stored = load_journal(journal)?;
+    stored.batches.push(JournalBatch {
+        time: Local::now().to_rfc3339(),
+        renames: pairs.to_vec(),
+    });
+    let excess = stored.batches.len().saturating_sub(JOURNAL_BATCHES);
+    stored.batches.drain(..excess);
+    save_journal(journal, &stored)
+}
+
+fn load_journal(journal: &Path) -> Result<Journal> {
+    match fs::read(journal) {
+        Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Journal::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn save_journal(journal: &Path, stored: &Journal) -> Result<()> {
+    if let Some(dir) = journal.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    write_atomic(journal, &serde_json::to_vec_pretty(stored)?)?;
+    debug!(
+        "Saved {} rename batches to {}",
+        stored.batches.len(),
+        journal.display()
+    );
+    Ok(())
+}
+
+fn parse_template(template: &str) -> Result<Vec<Part>> {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut rest = template;
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        match c {
+            '{' | '}' if rest.starts_with(c) => {
+                literal.push(c);
+                rest = &rest[1..];
+            }
+            '{' => {
+                let end = rest.find('}').ok_or_else(|| {
+                    Error::InvalidInput(format!("Unclosed '{{' in template '{}'", template))
+                })?;
+                if !literal.is_empty() {
+                    parts.push(Part::Literal(std::mem::take(&mut literal)));
+                }
+                parts.push(placeholder(&rest[..end])?);
+                rest = &rest[end + 1..];
+            }
+            '}' => {
+                return Err(Error::InvalidInput(format!(
+                    "Unmatched '}}' in template '{}'",
+                    template
+                )))
+            }
+            c => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        parts.push(Part::Literal(literal));
+    }
+    Ok(parts)
+}
+
+fn placeholder(placeholder: &str) -> Result<Part> {
+    let (key, spec) = match placeholder.split_once(':') {
+        Some((key, spec)) => (key, Some(spec)),
+        None => (placeholder, None),
+    };
+    match (key, spec) {
+        ("name", None) => Ok(Part::Name),
+        ("ext", None) => Ok(Part::Ext),
+        ("n", None) => Ok(Part::Number {
+            width: 0,
+            zero: false,
+        }),
+        ("n", Some(spec)) => match spec.parse() {
+            Ok(width) => Ok(Part::Number {
+                width,
+                zero: spec.starts_with('0'),
+            }),
+            Err(_) => Err(Error::InvalidInput(format!(
+                "Invalid width in {{{}}}; use e.g. {{n:03}}",
+                placeholder
+            ))),
+        },
+        ("mtime", spec) => {
+            let format = spec.unwrap_or("%Y%m%d");
+            StrftimeItems::new(format).parse().map_err(|_| {
+                Error::InvalidInput(format!("Invalid date format in {{{}}}", placeholder))
+            })?;
+            Ok(Part::Mtime(format.to_string()))
+        }
+        _ => Err(Error::InvalidInput(format!(
+            "Unknown placeholder {{{}}}; use {{name}}, {{ext}}, {{n}} or {{mtime}}",
+            placeholder
+        ))),
+    }
+}
+
+fn render_template(parts: &[Part], path: &Path, number: usize) -> Result<String> {
+    let name = file_name(path);
+    let (stem, extension) = split_name(&name);
+    let mut rendered = String::new();
+    for part in parts {
+        match part {
+            Part::Literal(literal) => rendered.push_str(literal),
+            Part::Name => rendered.push_str(stem),
+            Part::Ext => rendered.push_str(extension.trim_start_matches('.')),
+            Part::Number { width, zero: true } => {
+                rendered.push_str(&format!("{:0width$}", number, width = width))
+            }
+            Part::Number { width, zero: false } => {
+                rendered.push_str(&format!("{:width$}", number, width = width))
+            }
+            Part::Mtime(format) => {
+                let modified: DateTime<Local> = fs::symlink_metadata(path)?.modified()?.into();
+                rendered.push_str(&modified.format(format).to_string());
+            }
+        }
+    }
+    Ok(rendered)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// A file name split into its stem and its extension with the dot, like
+/// `Path::file_stem` and `Path::extension` do: `.bashrc` has none.
+fn split_name(name: &str) -> (&str, &str) {
+    match name.rfind('.') {
+        Some(0) | None => (name, ""),
+        Some(dot) => name.split_at(dot),
+    }
+}
+
+fn invalid_name(name: &str) -> Option<&'static str> {
+    if name.is_empty() || name == "." || name == ".." {
+        Some("the new name is empty")
+    } else if name.contains(['/', path::MAIN_SEPARATOR]) {
+        Some("the new name contains a path separator")
+    } else {
+        None
+    }
+}
+
+impl Render for RenameReport {
+    fn render(&self) {
+        for rename in &self.renames {
+            let new_name = rename.to.file_name().unwrap_or_default().to_string_lossy();
+            match &rename.problem {
+                None => println!("{} → {}", rename.from.display(), new_name.green()),
+                Some(problem) => println!(
+                    "{} {} → {}: {}",
+                    "✗".red(),
+                    rename.from.display(),
+                    new_name.yellow(),
+                    problem.red()
+                ),
+            }
+        }
+
+        let problems = self
+            .renames
+            .iter()
+            .filter(|rename| rename.problem.is_some())
+            .count();
+        if problems > 0 {
+            println!(
+                "\n{} {} of {} renames cannot be done; nothing was renamed",
+                "Error:".red().bold(),
+                problems,
+                self.renames.len()
+            );
+        } else if self.dry_run {
+            println!(
+                "\n{} {} files would be renamed, {} unchanged",
+                "Dry run:".yellow().bold(),
+                self.renames.len(),
+                self.unchanged
+            );
+        } else if self.undo {
+            println!(
+                "\n{} Restored {} names",
+                "Success:".green().bold(),
+                self.renames.len()
+            );
+        } else {
+            println!(
+                "\n{} Renamed {} files, {} unchanged; undo with `rename --undo`",
+                "Success:".green().bold(),
+                self.renames.len(),
+                self.unchanged
+            );
+        }
+    }
+
+    fn exit_code(&self) -> u8 {
+        if self.renames.iter().any(|rename| rename.problem.is_some()) {
+            exit_code::FAILURE
+        } else {
+            exit_code::SUCCESS
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(template: &str, start: usize) -> RenameOptions {
+        RenameOptions {
+            template: Some(template.to_string()),
+            start,
+            ..RenameOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_collision_blocks_the_batch() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let journal = root.join("journal.json");
+        for name in ["Shot A.PNG", "Shot B.PNG", "shot_001.PNG"] {
+            fs::write(root.join(name), name).unwrap();
+        }
+
+        // shot_001.PNG is taken by a file outside the batch
+        let options = template("shot_{n:03}.{ext}", 1);
+        let blocked = rename_files(&[root.join("Shot *.PNG")], &options, &journal).unwrap();
+        assert!(blocked.renames[0].problem.is_some());
+        assert!(root.join("Shot A.PNG").exists() && root.join("Shot B.PNG").exists());
+        assert!(!journal.exists());
+    }
+
+    #[test]
+    fn test_template_and_case() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let journal = root.join("journal.json");
+        for name in ["Screen Shot A.PNG", "Screen Shot B.PNG"] {
+            fs::write(root.join(name), name).unwrap();
+        }
+        let shots = [root.join("Screen Shot *.PNG")];
+
+        let mut options = RenameOptions {
+            case: Some(TextCase::Kebab),
+            dry_run: true,
+            ..template("shot_{n:03}.{ext}", 1)
+        };
+        let preview = rename_files(&shots, &options, &journal).unwrap();
+        assert_eq!(preview.renames[1].to, root.join("shot-002.PNG"));
+        assert!(root.join("Screen Shot B.PNG").exists());
+
+        options.dry_run = false;
+        rename_files(&shots, &options, &journal).unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join("shot-002.PNG")).unwrap(),
+            "Screen Shot B.PNG"
+        );
+    }
+
+    #[test]
+    fn test_files_can_take_each_others_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let journal = root.join("journal.json");
+        for name in ["shot-1.txt", "shot-2.txt"] {
+            fs::write(root.join(name), name).unwrap();
+        }
+
+        // shot-2.txt is renamed too, so it is free for shot-1.txt
+        let shift = template("shot-{n}.{ext}", 2);
+        rename_files(&[root.join("shot-*.txt")], &shift, &journal).unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join("shot-3.txt")).unwrap(),
+            "shot-2.txt"
+        );
+
+        undo_rename(&journal, false).unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join("shot-1.txt")).unwrap(),
+            "shot-1.txt"
+        );
+        assert!(undo_rename(&journal, false).is_err());
+    }
+
+    #[test]
+    fn test_failed_rename_is_rolled_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("a.txt"), "a").unwrap();
+        fs::write(root.join("b.txt"), "b").unwrap();
+        // A file cannot be renamed over a directory
+        fs::create_dir_all(root.join("taken").join("inner")).unwrap();
+
+        let pairs = [
+            (root.join("a.txt"), root.join("a2.txt")),
+            (root.join("b.txt"), root.join("taken")),
+        ];
+        assert!(rename_all(&pairs).is_err());
+        assert_eq!(fs::read_to_string(root.join("a.txt")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(root.join("b.txt")).unwrap(), "b");
+        assert!(!root.join("a2.txt").exists());
+        // No temporary names are left behind
+        assert_eq!(fs::read_dir(root).unwrap().count(), 3);
+    }
+
+    #[test]
+    fn test_undo_after_journal_is_capped() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let journal = root.join("journal.json");
+        fs::write(root.join("f0"), "f").unwrap();
+
+        // One more batch than the journal keeps: f0 -> f1 -> ... -> f51
+        for n in 0..=JOURNAL_BATCHES {
+            let options = template("f{n}", n + 1);
+            rename_files(&[root.join(format!("f{}", n))], &options, &journal).unwrap();
+        }
+        for _ in 0..JOURNAL_BATCHES {
+            undo_rename(&journal, false).unwrap();
+        }
+        // The first batch was dropped, so f1 stays f1
+        assert!(root.join("f1").exists() && !root.join("f0").exists());
+        assert!(undo_rename(&journal, false).is_err());
+    }
+}
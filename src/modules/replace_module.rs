@@ -0,0 +1,445 @@
+// replace_module.rs
+//
+// Project-wide search and replace. Files are found with the data-search
+// walker and matched line by line, so a match never spans lines. Every line
+// keeps its own line ending, and rewritten files keep their encoding and
+// permissions.
+
+use colored::Colorize;
+use dialoguer::Input;
+use encoding_rs::Encoding;
+use regex::{NoExpand, Regex};
+use serde::Serialize;
+use std::fs;
+use std::ops::{ControlFlow, Range};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::exit_code;
+use crate::modules::search_data_module::{decode_text, encode_text, Matcher};
+use crate::modules::walk_module::{walk_files, WalkOptions};
+use crate::output::Render;
+use crate::utilities::write_atomic;
+use crate::{Error, Result};
+
+/// Unchanged lines shown around the changes of a diff hunk.
+const DIFF_CONTEXT: usize = 3;
+
+#[derive(Debug, Clone, Default)]
+pub struct ReplaceOptions {
+    pub walk: WalkOptions,
+    /// Expand `$1` and `${name}` in the replacement.
+    pub expand: bool,
+    /// Only report the changes, as unified diffs.
+    pub dry_run: bool,
+    /// Ask before applying each hunk.
+    pub interactive: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplaceReport {
+    pub dry_run: bool,
+    pub files: Vec<FileReplacement>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileReplacement {
+    pub path: PathBuf,
+    /// Occurrences replaced, or that would be replaced in a dry run.
+    pub replacements: usize,
+    /// Unified diff of the accepted changes.
+    pub diff: String,
+    /// Why the file could not be rewritten.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A line that reads differently after the replacement.
+struct Change {
+    index: usize,
+    new: String,
+    replacements: usize,
+}
+
+/// The planned changes to one file.
+struct FilePlan {
+    path: PathBuf,
+    /// The decoded lines, each with its line ending.
+    lines: Vec<String>,
+    changes: Vec<Change>,
+    encoding: &'static Encoding,
+    bom: bool,
+}
+
+/// Answer to the per-hunk question of interactive mode.
+enum Answer {
+    Yes,
+    No,
+    /// This hunk and the rest of the file.
+    All,
+    /// None of the rest of the file.
+    Skip,
+    Quit,
+}
+
+/// Replace what `matcher` finds in the files below `root` with
+/// `replacement`. Binary files are left alone.
+pub fn replace_in_files(
+    matcher: &Matcher,
+    replacement: &str,
+    root: &Path,
+    options: &ReplaceOptions,
+) -> Result<ReplaceReport> {
+    let plans = Mutex::new(Vec::new());
+    walk_files(root, &options.walk, |path| {
+        if let Some(plan) = plan_file(matcher.regex(), replacement, options.expand, path) {
+            plans.lock().unwrap().push(plan);
+        }
+        ControlFlow::Continue(())
+    })?;
+    let mut plans = plans.into_inner().unwrap();
+    plans.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut files = Vec::new();
+    for mut plan in plans {
+        let hunks = group_hunks(&plan.changes);
+        let (accepted, quit) = if options.interactive {
+            confirm_hunks(&plan, &hunks)?
+        } else {
+            (hunks, false)
+        };
+
+        if !accepted.is_empty() {
+            files.push(apply(&mut plan, &accepted, options.dry_run));
+        }
+        if quit {
+            break;
+        }
+    }
+
+    Ok(ReplaceReport {
+        dry_run: options.dry_run,
+        files,
+    })
+}
+
+/// The changed lines of a text file, or `None` if nothing changes or the
+/// file cannot be read or is binary.
+fn plan_file(regex: &Regex, replacement: &str, expand: bool, path: &Path) -> Option<FilePlan> {
+    let bytes = fs::read(path).ok()?;
+    let decoded = decode_text(&bytes, None);
+    if decoded.binary {
+        return None;
+    }
+
+    let lines: Vec<String> = decoded
+        .text
+        .split_inclusive('\n')
+        .map(str::to_string)
+        .collect();
+    let changes: Vec<Change> = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let content = line_content(line);
+            let replacements = regex.find_iter(content).count();
+            let new = if expand {
+                regex.replace_all(content, replacement)
+            } else {
+                regex.replace_all(content, NoExpand(replacement))
+            };
+            (new != content).then(|| Change {
+                index,
+                new: new.into_owned(),
+                replacements,
+            })
+        })
+        .collect();
+
+    (!changes.is_empty()).then(|| FilePlan {
+        path: path.to_path_buf(),
+        lines,
+        changes,
+        encoding: decoded.encoding,
+        bom: decoded.bom,
+    })
+}
+
+/// Split the changes into hunks: changes closer than twice the diff
+/// context share their context lines and so a hunk.
+fn group_hunks(changes: &[Change]) -> Vec<Range<usize>> {
+    let mut hunks: Vec<Range<usize>> = Vec::new();
+    for (position, change) in changes.iter().enumerate() {
+        match hunks.last_mut() {
+            Some(hunk) if change.index - changes[hunk.end - 1].index <= 2 * DIFF_CONTEXT => {
+                hunk.end = position + 1
+            }
+            _ => hunks.push(position..position + 1),
+        }
+    }
+    hunks
+}
+
+/// Show each hunk and ask whether to apply it. Returns the accepted hunks
+/// and whether the user asked to stop altogether.
+fn confirm_hunks(plan: &FilePlan, hunks: &[Range<usize>]) -> Result<(Vec<Range<usize>>, bool)> {
+    eprintln!("{}", colored_diff(&diff_header(&plan.path)));
+
+    let mut accepted = Vec::new();
+    let mut offset = 0;
+    for (position, hunk) in hunks.iter().enumerate() {
+        let changes = &plan.changes[hunk.clone()];
+        eprint!(
+            "{}",
+            colored_diff(&format_hunk(&plan.lines, changes, offset))
+        );
+
+        match ask(position + 1, hunks.len())? {
+            Answer::Yes => {}
+            Answer::No => continue,
+            Answer::All => {
+                accepted.extend(hunks[position..].iter().cloned());
+                return Ok((accepted, false));
+            }
+            Answer::Skip => return Ok((accepted, false)),
+            Answer::Quit => return Ok((accepted, true)),
+        }
+        offset += line_delta(changes);
+        accepted.push(hunk.clone());
+    }
+
+    Ok((accepted, false))
+}
+
+fn ask(number: usize, total: usize) -> Result<Answer> {
+    let answer = Input::<String>::new()
+        .with_prompt(format!(
+            "Apply hunk {}/{} [y]es, [n]o, [a]ll in file, [d]one with file, [q]uit",
+            number, total
+        ))
+        .validate_with(|answer: &String| -> std::result::Result<(), &str> {
+            match answer.trim() {
+                "y" | "n" | "a" | "d" | "q" => Ok(()),
+                _ => Err("answer y, n, a, d or q"),
+            }
+        })
+        .interact_text()
+        .map_err(|dialoguer::Error::IO(e)| Error::from(e))?;
+
+    Ok(match answer.trim() {
+        "y" => Answer::Yes,
+        "n" => Answer::No,
+        "a" => Answer::All,
+        "d" => Answer::Skip,
+        _ => Answer::Quit,
+    })
+}
+
+/// Apply the accepted hunks to the plan's lines and write the file, unless
+/// this is a dry run.
+fn apply(plan: &mut FilePlan, accepted: &[Range<usize>], dry_run: bool) -> FileReplacement {
+    let mut diff = diff_header(&plan.path);
+    let mut offset = 0;
+    for hunk in accepted {
+        let changes = &plan.changes[hunk.clone()];
+        diff.push_str(&format_hunk(&plan.lines, changes, offset));
+        offset += line_delta(changes);
+    }
+
+    let mut replacements = 0;
+    for change in accepted.iter().flat_map(|hunk| &plan.changes[hunk.clone()]) {
+        let line = &mut plan.lines[change.index];
+        let ending = line[line_content(line).len()..].to_string();
+        *line = format!("{}{}", change.new, ending);
+        replacements += change.replacements;
+    }
+
+    let text = plan.lines.concat();
+    let error = match encode_text(&text, plan.encoding, plan.bom) {
+        None => Some(format!(
+            "The replacement cannot be encoded in {}",
+            plan.encoding.name()
+        )),
+        Some(_) if dry_run => None,
+        Some(bytes) => write_atomic(&plan.path, &bytes)
+            .err()
+            .map(|e| e.to_string()),
+    };
+
+    FileReplacement {
+        path: plan.path.clone(),
+        replacements,
+        diff,
+        error,
+    }
+}
+
+/// A line without its `\n` or `\r\n` ending.
+fn line_content(line: &str) -> &str {
+    line.strip_suffix('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .unwrap_or(line)
+}
+
+fn diff_header(path: &Path) -> String {
+    let path = path.strip_prefix(".").unwrap_or(path);
+    format!("--- a/{0}\n+++ b/{0}\n", path.display())
+}
+
+/// Lines added minus lines removed by the changes; a replacement can
+/// contain line breaks.
+fn line_delta(changes: &[Change]) -> isize {
+    changes
+        .iter()
+        .map(|change| change.new.split('\n').count() as isize - 1)
+        .sum()
+}
+
+/// One unified diff hunk. `offset` is the line delta of the hunks before
+/// it, to number the new lines.
+fn format_hunk(lines: &[String], changes: &[Change], offset: isize) -> String {
+    let first = changes[0].index;
+    let last = changes[changes.len() - 1].index;
+    let start = first.saturating_sub(DIFF_CONTEXT);
+    let end = (last + DIFF_CONTEXT + 1).min(lines.len());
+    let old_len = end - start;
+    let new_len = (old_len as isize + line_delta(changes)) as usize;
+
+    let mut hunk = format!(
+        "@@ -{},{} +{},{} @@\n",
+        start + 1,
+        old_len,
+        (start as isize + 1 + offset),
+        new_len
+    );
+    // Each run of changed lines is shown as all removals, then all additions
+    let mut changes = changes.iter().peekable();
+    let mut index = start;
+    while index < end {
+        let mut removed = String::new();
+        let mut added = String::new();
+        while let Some(change) = changes.next_if(|change| change.index == index) {
+            removed.push_str(&format!("-{}\n", line_content(&lines[index])));
+            for new_line in change.new.split('\n') {
+                added.push_str(&format!("+{}\n", new_line));
+            }
+            index += 1;
+        }
+
+        if removed.is_empty() {
+            hunk.push_str(&format!(" {}\n", line_content(&lines[index])));
+            index += 1;
+        } else {
+            hunk.push_str(&removed);
+            hunk.push_str(&added);
+        }
+    }
+    hunk
+}
+
+fn colored_diff(diff: &str) -> String {
+    diff.lines()
+        .map(|line| {
+            let colored = if line.starts_with("---") || line.starts_with("+++") {
+                line.bold()
+            } else if line.starts_with("@@") {
+                line.cyan()
+            } else if line.starts_with('+') {
+                line.green()
+            } else if line.starts_with('-') {
+                line.red()
+            } else {
+                line.normal()
+            };
+            format!("{}\n", colored)
+        })
+        .collect()
+}
+
+impl Render for ReplaceReport {
+    fn render(&self) {
+        if self.files.is_empty() {
+            println!("{} No matches found", "ℹ".cyan());
+            return;
+        }
+
+        for file in &self.files {
+            if let Some(error) = &file.error {
+                println!("{} {}: {}", "✗".red().bold(), file.path.display(), error);
+            } else if self.dry_run {
+                print!("{}", colored_diff(&file.diff));
+            } else {
+                println!(
+                    "{} {} ({} replacements)",
+                    "✓".green().bold(),
+                    file.path.display(),
+                    file.replacements.to_string().yellow()
+                );
+            }
+        }
+
+        let total: usize = self.files.iter().map(|file| file.replacements).sum();
+        let summary = format!("{} replacements in {} files", total, self.files.len());
+        if self.dry_run {
+            println!(
+                "\n{} {}",
+                summary.bold(),
+                "(dry run, nothing written)".yellow()
+            );
+        } else {
+            println!("\n{}", summary.bold());
+        }
+    }
+
+    fn exit_code(&self) -> u8 {
+        if self.files.iter().any(|file| file.error.is_some()) {
+            exit_code::FAILURE
+        } else {
+            exit_code::SUCCESS
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::search_data_module::MatchOptions;
+
+    #[test]
+    fn test_replace_keeps_line_endings_and_expands_groups() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let path = root.join("names.txt");
+        fs::write(&path, "first: Ada\r\nkeep\nsecond: Alan\n").unwrap();
+
+        let options = MatchOptions {
+            regex: true,
+            ..MatchOptions::default()
+        };
+        let matcher = Matcher::new(&[r"(\w+): (\w+)".to_string()], options).unwrap();
+        let replace = |dry_run| {
+            let options = ReplaceOptions {
+                expand: true,
+                dry_run,
+                ..ReplaceOptions::default()
+            };
+            replace_in_files(&matcher, "$2 ($1)", root, &options).unwrap()
+        };
+
+        let preview = replace(true);
+        assert_eq!(preview.files[0].replacements, 2);
+        assert!(preview.files[0]
+            .diff
+            .contains("@@ -1,3 +1,3 @@\n-first: Ada\n+Ada (first)\n"));
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "first: Ada\r\nkeep\nsecond: Alan\n"
+        );
+
+        replace(false);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "Ada (first)\r\nkeep\nAlan (second)\n"
+        );
+    }
+}
@@ -1,33 +1,594 @@
 use colored::*;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use log::debug;
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::VecDeque;
 use std::fs;
-use std::io::Write;
-use std::path::PathBuf;
+use std::io::{Read, Write};
+use std::ops::{ControlFlow, Range};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 
-pub fn search_data_in_files(data: &str, output_path: &PathBuf, root_level: usize, limit: usize) {
-    let mut results = Vec::new();
-    search_files_recursive(data, output_path, root_level, &mut results);
-    if results.is_empty() {
-        let mut stderr = StandardStream::stderr(ColorChoice::Auto);
-        stderr
-            .set_color(ColorSpec::new().set_fg(Some(Color::Yellow)).set_bold(true))
-            .unwrap();
-        write!(stderr, "No results found for your input '{}'", data).unwrap();
-        stderr.reset().unwrap();
-        writeln!(stderr).unwrap();
-        return;
+use crate::modules::archive_module::{member_path, read_archive, ArchiveKind};
+use crate::modules::index_module::IndexFilter;
+use crate::modules::walk_module::{walk_files, WalkOptions};
+use crate::output::Render;
+use crate::{Error, Result};
+
+#[derive(Debug, Serialize)]
+pub struct DataSearch {
+    pub patterns: Vec<String>,
+    pub files: Vec<FileMatches>,
+    pub summary: Summary,
+    #[serde(skip)]
+    pub layout: Layout,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileMatches {
+    pub path: PathBuf,
+    /// Encoding the file was decoded with, e.g. `UTF-16LE`.
+    pub encoding: &'static str,
+    /// The file contains NUL bytes; only a match is reported, not its
+    /// lines, unless binary files are searched as text.
+    pub binary: bool,
+    /// Number of selected lines, not counting context lines.
+    pub count: usize,
+    /// Selected lines and their context; empty when only counts or file
+    /// names are reported.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub lines: Vec<LineMatch>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LineMatch {
+    pub line_number: usize,
+    pub line: String,
+    /// Byte ranges of the matches in `line`; empty for inverted matches.
+    pub matches: Vec<Range<usize>>,
+    /// Shown only as context around a selected line.
+    pub context: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Summary {
+    /// Files with at least one selected line.
+    pub files: usize,
+    /// Selected lines in all files.
+    pub lines: usize,
+}
+
+/// What is reported for each file with selected lines.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Report {
+    #[default]
+    Lines,
+    /// Only the number of selected lines.
+    Count,
+    /// Only the file name.
+    Files,
+}
+
+/// How results are printed in text mode.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Layout {
+    pub report: Report,
+    /// Show the column of the first match on each line.
+    pub column: bool,
+    /// Print the total number of files and lines at the end.
+    pub summary: bool,
+    /// Print `--` between groups of lines that are not adjacent.
+    pub separators: bool,
+}
+
+/// How the search patterns are compared with each line.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MatchOptions {
+    /// Patterns are regular expressions instead of literal text.
+    pub regex: bool,
+    pub ignore_case: bool,
+    /// Matches must start and end at word boundaries.
+    pub whole_word: bool,
+    /// Select the lines that match none of the patterns.
+    pub invert: bool,
+}
+
+/// Search patterns compiled into a single regular expression.
+#[derive(Debug, Clone)]
+pub struct Matcher {
+    patterns: Vec<String>,
+    regex: Regex,
+    invert: bool,
+}
+
+impl Matcher {
+    /// A line is selected when it matches any of `patterns`.
+    pub fn new(patterns: &[String], options: MatchOptions) -> Result<Self> {
+        if patterns.is_empty() {
+            return Err(Error::InvalidInput(
+                "At least one search pattern is required".to_string(),
+            ));
+        }
+
+        let alternatives: Vec<String> = patterns
+            .iter()
+            .map(|pattern| {
+                if options.regex {
+                    format!("(?:{})", pattern)
+                } else {
+                    regex::escape(pattern)
+                }
+            })
+            .collect();
+        let mut expression = alternatives.join("|");
+        if options.whole_word {
+            expression = format!(r"\b(?:{})\b", expression);
+        }
+
+        let regex = RegexBuilder::new(&expression)
+            .case_insensitive(options.ignore_case)
+            .build()
+            .map_err(|e| Error::Pattern(e.to_string()))?;
+
+        Ok(Matcher {
+            patterns: patterns.to_vec(),
+            regex,
+            invert: options.invert,
+        })
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// Lines are selected when they do not match.
+    pub fn is_inverted(&self) -> bool {
+        self.invert
+    }
+
+    /// The compiled expression; literal patterns are escaped, so capture
+    /// groups are only those written in regex patterns.
+    pub fn regex(&self) -> &Regex {
+        &self.regex
     }
 
-    for (file_path, lines) in results {
-        if limit == 0 {
-            for (line_number, line) in lines {
-                print_colored_line(&file_path, line_number, &line, &data);
+    /// The match spans in `line`, or `None` if the line is not selected.
+    pub fn find(&self, line: &str) -> Option<Vec<Range<usize>>> {
+        if !self.regex.is_match(line) {
+            return self.invert.then(Vec::new);
+        }
+        if self.invert {
+            return None;
+        }
+
+        Some(
+            self.regex
+                .find_iter(line)
+                .map(|found| found.range())
+                .filter(|span| !span.is_empty())
+                .collect(),
+        )
+    }
+}
+
+/// What is read and reported besides the patterns.
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    pub walk: WalkOptions,
+    /// Maximum number of selected lines per file (0 = unlimited).
+    pub limit: usize,
+    /// Maximum number of selected lines in the whole search (0 = unlimited).
+    /// Files are searched in parallel, so which lines make the cut varies
+    /// between runs.
+    pub total_limit: usize,
+    /// Lines shown before each selected line.
+    pub before_context: usize,
+    /// Lines shown after each selected line.
+    pub after_context: usize,
+    /// Print the lines of binary files (containing NUL bytes) like those of
+    /// text files instead of only noting that they match.
+    pub binary: bool,
+    /// Decode every file with this encoding instead of detecting it.
+    pub encoding: Option<&'static Encoding>,
+    pub report: Report,
+    pub column: bool,
+    pub summary: bool,
+    /// Skip the files that a trigram index rules out.
+    pub index: Option<IndexFilter>,
+    /// Search the files inside `.zip` and `.tar.gz` archives too.
+    pub archives: bool,
+}
+
+impl SearchOptions {
+    pub fn layout(&self) -> Layout {
+        Layout {
+            report: self.report,
+            column: self.column,
+            summary: self.summary,
+            separators: self.before_context > 0 || self.after_context > 0,
+        }
+    }
+}
+
+/// Number of leading bytes checked for NUL when deciding if a file is binary.
+const BINARY_SNIFF_LEN: usize = 8192;
+
+/// File contents transcoded to UTF-8.
+pub struct DecodedText<'a> {
+    pub text: Cow<'a, str>,
+    pub encoding: &'static Encoding,
+    /// The content started with a byte order mark.
+    pub bom: bool,
+    /// NUL bytes were found in content that is not UTF-16.
+    pub binary: bool,
+}
+
+/// Decode `bytes` with `encoding`, or detect the encoding: a byte order
+/// mark wins, then UTF-16 without a BOM (recognised by NUL bytes in every
+/// other position), then valid UTF-8, and Windows-1252, the usual superset
+/// of Latin-1, for everything else. Malformed sequences become U+FFFD.
+pub fn decode_text<'a>(bytes: &'a [u8], encoding: Option<&'static Encoding>) -> DecodedText<'a> {
+    let sniff = &bytes[..bytes.len().min(BINARY_SNIFF_LEN)];
+    let bom = Encoding::for_bom(bytes);
+    let encoding = encoding
+        .or_else(|| bom.map(|(encoding, _)| encoding))
+        .or_else(|| sniff_utf16(sniff))
+        .unwrap_or_else(|| {
+            if std::str::from_utf8(bytes).is_ok() {
+                UTF_8
+            } else {
+                WINDOWS_1252
             }
+        });
+    let binary = encoding != UTF_16LE && encoding != UTF_16BE && sniff.contains(&0);
+
+    // Binary content is searched byte for byte, as far as it is ASCII
+    let text = if binary {
+        String::from_utf8_lossy(bytes)
+    } else {
+        encoding.decode_with_bom_removal(bytes).0
+    };
+    DecodedText {
+        text,
+        encoding: if binary { UTF_8 } else { encoding },
+        bom: bom.is_some_and(|(bom_encoding, _)| bom_encoding == encoding),
+        binary,
+    }
+}
+
+/// Encode `text` back into the encoding it was decoded from, with the same
+/// byte order mark. `None` if a character cannot be represented.
+pub fn encode_text(text: &str, encoding: &'static Encoding, bom: bool) -> Option<Vec<u8>> {
+    let utf16 = |to_bytes: fn(u16) -> [u8; 2]| -> Vec<u8> {
+        let units = bom.then_some(0xFEFF).into_iter().chain(text.encode_utf16());
+        units.flat_map(to_bytes).collect()
+    };
+
+    if encoding == UTF_16LE {
+        Some(utf16(u16::to_le_bytes))
+    } else if encoding == UTF_16BE {
+        Some(utf16(u16::to_be_bytes))
+    } else {
+        let (encoded, _, unmappable) = encoding.encode(text);
+        let mut bytes = if bom && encoding == UTF_8 {
+            vec![0xEF, 0xBB, 0xBF]
         } else {
-            for (line_number, line) in lines.iter().take(limit) {
-                print_colored_line(&file_path, *line_number, line, &data);
+            Vec::new()
+        };
+        bytes.extend_from_slice(&encoded);
+        (!unmappable).then_some(bytes)
+    }
+}
+
+/// UTF-16 without a BOM, assuming mostly ASCII text: one byte of each pair
+/// is never NUL and the other is NUL at least half the time.
+fn sniff_utf16(sniff: &[u8]) -> Option<&'static Encoding> {
+    if sniff.len() < 2 || !sniff.len().is_multiple_of(2) {
+        return None;
+    }
+    let pairs = sniff.len() / 2;
+    let nul_even = sniff.iter().step_by(2).filter(|&&byte| byte == 0).count();
+    let nul_odd = sniff
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .filter(|&&byte| byte == 0)
+        .count();
+
+    match (nul_even, nul_odd) {
+        (0, odd) if odd * 2 >= pairs => Some(UTF_16LE),
+        (even, 0) if even * 2 >= pairs => Some(UTF_16BE),
+        _ => None,
+    }
+}
+
+/// Search the files below `root` for lines selected by `matcher` on several
+/// threads, calling `on_file` for each file with matches as soon as it has
+/// been read. Files arrive in no particular order.
+pub fn search_data<F>(
+    matcher: &Matcher,
+    root: &Path,
+    options: &SearchOptions,
+    mut on_file: F,
+) -> Result<()>
+where
+    F: FnMut(FileMatches),
+{
+    let (sender, receiver) = mpsc::channel();
+    let remaining = AtomicUsize::new(match options.total_limit {
+        0 => usize::MAX,
+        limit => limit,
+    });
+
+    thread::scope(|scope| {
+        let remaining = &remaining;
+        let walker = scope.spawn(move || {
+            walk_files(root, &options.walk, |path| {
+                // The receiver only goes away once the walk is over
+                let found = |file| {
+                    let _ = sender.send(file);
+                };
+                // Archives are indexed compressed, so the index cannot rule them out
+                if let Some(kind) = ArchiveKind::of(path).filter(|_| options.archives) {
+                    let limit = ARCHIVE_MEMBER_LIMIT;
+                    search_archive(matcher, path, kind, limit, options, remaining, found);
+                } else if options
+                    .index
+                    .as_ref()
+                    .is_none_or(|index| index.may_match(path))
+                {
+                    if let Some(file) = search_file(matcher, path, options, remaining) {
+                        found(file);
+                    }
+                }
+                if remaining.load(Ordering::Relaxed) == 0 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            })
+        });
+
+        for file in receiver {
+            on_file(file);
+        }
+        walker.join().expect("data search walker panicked")
+    })
+}
+
+/// Search the files below `root` and collect the results, sorted by path.
+pub fn search_data_in_files(
+    matcher: &Matcher,
+    root: &Path,
+    options: &SearchOptions,
+) -> Result<DataSearch> {
+    let mut files = Vec::new();
+    search_data(matcher, root, options, |file| files.push(file))?;
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let summary = Summary {
+        files: files.len(),
+        lines: files.iter().map(|file| file.count).sum(),
+    };
+    Ok(DataSearch {
+        patterns: matcher.patterns().to_vec(),
+        files,
+        summary,
+        layout: options.layout(),
+    })
+}
+
+/// The selected lines of one file with their context, or `None` if there
+/// are none or the file cannot be read. Each selected line is taken from
+/// `remaining`, the lines left for the whole search.
+fn search_file(
+    matcher: &Matcher,
+    path: &Path,
+    options: &SearchOptions,
+    remaining: &AtomicUsize,
+) -> Option<FileMatches> {
+    let bytes = fs::read(path).ok()?;
+    search_bytes(matcher, path, &bytes, options, remaining)
+}
+
+/// Largest archive member that is unpacked into memory to be searched.
+const ARCHIVE_MEMBER_LIMIT: u64 = 256 << 20;
+
+/// Search each file inside the archive at `path`, passing those with
+/// selected lines to `found` as `archive.zip!inner/path`. Archives that
+/// cannot be read are skipped like unreadable files, and so are members
+/// of more than `limit` bytes.
+fn search_archive(
+    matcher: &Matcher,
+    path: &Path,
+    kind: ArchiveKind,
+    limit: u64,
+    options: &SearchOptions,
+    remaining: &AtomicUsize,
+    found: impl Fn(FileMatches),
+) {
+    let result = read_archive(path, kind, |member, reader| {
+        if member.is_dir {
+            return ControlFlow::Continue(());
+        }
+        let mut bytes = Vec::new();
+        // The recorded size may be wrong, so the reading is capped too
+        if member.size > limit
+            || reader.take(limit + 1).read_to_end(&mut bytes).is_err()
+            || bytes.len() as u64 > limit
+        {
+            debug!("Skipping {}!{}", path.display(), member.name);
+            return ControlFlow::Continue(());
+        }
+        let inner = member_path(path, &member.name);
+        if let Some(file) = search_bytes(matcher, &inner, &bytes, options, remaining) {
+            found(file);
+        }
+        if remaining.load(Ordering::Relaxed) == 0 {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    if let Err(e) = result {
+        debug!("Skipping unreadable archive {}: {}", path.display(), e);
+    }
+}
+
+/// The selected lines in the contents of the file shown as `path`.
+fn search_bytes(
+    matcher: &Matcher,
+    path: &Path,
+    bytes: &[u8],
+    options: &SearchOptions,
+    remaining: &AtomicUsize,
+) -> Option<FileMatches> {
+    let decoded = decode_text(bytes, options.encoding);
+    let keep_lines = options.report == Report::Lines && (options.binary || !decoded.binary);
+    let mut count = 0;
+    let mut lines = Vec::new();
+    let mut before: VecDeque<LineMatch> = VecDeque::new();
+    // Context lines still to show after the last selected line
+    let mut after = 0;
+    // Set once a limit is reached; only trailing context is read after that
+    let mut done = false;
+
+    for (index, text) in decoded.text.lines().enumerate() {
+        let selected = match matcher.find(text).filter(|_| !done) {
+            Some(matches) if take_line(remaining) => Some(matches),
+            Some(_) => {
+                done = true;
+                None
+            }
+            None => None,
+        };
+        let line = |matches, context| LineMatch {
+            line_number: index + 1,
+            line: text.to_string(),
+            matches,
+            context,
+        };
+
+        if let Some(matches) = selected {
+            count += 1;
+            done = count == options.limit;
+            if keep_lines {
+                lines.extend(before.drain(..));
+                lines.push(line(matches, false));
+                after = options.after_context;
+            }
+        } else if after > 0 {
+            after -= 1;
+            lines.push(line(Vec::new(), true));
+        } else if done {
+            break;
+        } else if keep_lines && options.before_context > 0 {
+            if before.len() == options.before_context {
+                before.pop_front();
+            }
+            before.push_back(line(Vec::new(), true));
+        }
+    }
+
+    (count > 0).then(|| FileMatches {
+        path: path.to_path_buf(),
+        encoding: decoded.encoding.name(),
+        binary: decoded.binary,
+        count,
+        lines,
+    })
+}
+
+/// Take one line from the lines left for the whole search.
+fn take_line(remaining: &AtomicUsize) -> bool {
+    remaining
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |left| {
+            left.checked_sub(1)
+        })
+        .is_ok()
+}
+
+impl Render for DataSearch {
+    fn render(&self) {
+        let mut printer = TextPrinter::new(self.layout);
+        for file in &self.files {
+            printer.print(file);
+        }
+        printer.finish(&self.patterns);
+    }
+}
+
+/// Prints results in text mode file by file, so a streaming search can show
+/// them as they are found.
+pub struct TextPrinter {
+    layout: Layout,
+    summary: Summary,
+}
+
+impl TextPrinter {
+    pub fn new(layout: Layout) -> Self {
+        TextPrinter {
+            layout,
+            summary: Summary::default(),
+        }
+    }
+
+    pub fn print(&mut self, file: &FileMatches) {
+        match self.layout.report {
+            Report::Files => println!("{}", get_colored_path(&file.path)),
+            Report::Count => println!(
+                "{} : {}",
+                get_colored_path(&file.path),
+                file.count.to_string().blue()
+            ),
+            Report::Lines if file.lines.is_empty() => {
+                println!("Binary file {} matches", get_colored_path(&file.path))
+            }
+            Report::Lines => {
+                let mut previous: Option<usize> = None;
+                for line in &file.lines {
+                    let adjacent = previous.is_some_and(|number| line.line_number == number + 1);
+                    let first = previous.is_none() && self.summary.files == 0;
+                    if self.layout.separators && !adjacent && !first {
+                        println!("{}", "--".blue());
+                    }
+                    print_colored_line(&file.path, line, self.layout.column);
+                    previous = Some(line.line_number);
+                }
             }
         }
+
+        self.summary.files += 1;
+        self.summary.lines += file.count;
+    }
+
+    /// Print the summary, or a notice on stderr if nothing was found.
+    pub fn finish(&self, patterns: &[String]) {
+        if self.summary.files == 0 {
+            let mut stderr = StandardStream::stderr(ColorChoice::Auto);
+            stderr
+                .set_color(ColorSpec::new().set_fg(Some(Color::Yellow)).set_bold(true))
+                .unwrap();
+            write!(
+                stderr,
+                "No results found for your input '{}'",
+                patterns.join("', '")
+            )
+            .unwrap();
+            stderr.reset().unwrap();
+            writeln!(stderr).unwrap();
+        } else if self.layout.summary {
+            println!(
+                "\n{} matching lines in {} files",
+                self.summary.lines.to_string().bold(),
+                self.summary.files.to_string().bold()
+            );
+        }
     }
 }
 
@@ -44,7 +605,8 @@ pub fn search_data_in_files(data: &str, output_path: &PathBuf, root_level: usize
 //     );
 // }
 
-fn print_colored_line(file_path: &PathBuf, line_number: usize, line: &str, data: &str) {
+/// Selected lines use `:` as separator and context lines `-`, as in grep.
+fn print_colored_line(file_path: &Path, line: &LineMatch, column: bool) {
     let colored_file_path = get_colored_path(file_path);
     let mut stdout = StandardStream::stdout(ColorChoice::Auto);
 
@@ -55,26 +617,50 @@ fn print_colored_line(file_path: &PathBuf, line_number: usize, line: &str, data:
     write!(stdout, "{}", colored_file_path).unwrap();
     stdout.reset().unwrap();
 
-    write!(stdout, " : ").unwrap(); // Separator
+    let separator = if line.context { " - " } else { " : " };
+    write!(stdout, "{}", separator).unwrap();
 
     // Set color for line number (blue)
     stdout
         .set_color(ColorSpec::new().set_fg(Some(Color::Blue)))
         .unwrap();
-    write!(stdout, "{: <4}", line_number).unwrap();
+    write!(stdout, "{: <4}", line.line_number).unwrap();
+    if column {
+        // 1-based byte column of the first match
+        let first = line
+            .matches
+            .first()
+            .map(|span| (span.start + 1).to_string());
+        write!(stdout, "{}{: <3}", separator, first.unwrap_or_default()).unwrap();
+    }
     stdout.reset().unwrap();
 
-    write!(stdout, " : ").unwrap(); // Separator
+    write!(stdout, "{}", separator).unwrap();
 
-    // Set color for data (yellow)
-    let colored_line = line.replace(data, &format!("{}", &data.color("yellow")));
-    write!(stdout, "{}", colored_line.trim()).unwrap();
-    stdout.reset().unwrap();
+    // Highlight the matched spans (yellow) of the trimmed line
+    let text = &line.line;
+    let start = text.len() - text.trim_start().len();
+    let end = text.trim_end().len().max(start);
+    let mut position = start;
+    for span in &line.matches {
+        let (from, to) = (
+            span.start.clamp(position, end),
+            span.end.clamp(position, end),
+        );
+        write!(stdout, "{}", &text[position..from]).unwrap();
+        stdout
+            .set_color(ColorSpec::new().set_fg(Some(Color::Yellow)))
+            .unwrap();
+        write!(stdout, "{}", &text[from..to]).unwrap();
+        stdout.reset().unwrap();
+        position = to;
+    }
+    write!(stdout, "{}", &text[position..end]).unwrap();
 
     writeln!(stdout).unwrap(); // Newline
 }
 
-fn get_colored_path(path: &PathBuf) -> String {
+fn get_colored_path(path: &Path) -> String {
     let path_str = path.display().to_string();
     if path.is_file() {
         path_str.green().to_string()
@@ -84,32 +670,233 @@ fn get_colored_path(path: &PathBuf) -> String {
         path_str
     }
 }
-fn search_files_recursive(
-    data: &str,
-    current_path: &PathBuf,
-    remaining_levels: usize,
-    results: &mut Vec<(PathBuf, Vec<(usize, String)>)>,
-) {
-    if let Ok(entries) = fs::read_dir(current_path) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if path.is_file() {
-                    if let Ok(contents) = fs::read_to_string(&path) {
-                        let mut matching_lines = Vec::new();
-                        for (line_number, line) in contents.lines().enumerate() {
-                            if line.contains(data) {
-                                matching_lines.push((line_number + 1, line.to_string()));
-                            }
-                        }
-                        if !matching_lines.is_empty() {
-                            results.push((path.clone(), matching_lines));
-                        }
-                    }
-                } else if path.is_dir() && remaining_levels > 0 {
-                    search_files_recursive(data, &path, remaining_levels - 1, results);
-                }
-            }
-        }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher(patterns: &[&str], options: MatchOptions) -> Matcher {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        Matcher::new(&patterns, options).unwrap()
+    }
+
+    #[test]
+    fn test_match_spans() {
+        let literal = matcher(&["a.b", "cd"], MatchOptions::default());
+        assert_eq!(literal.find("xa.b cd axb"), Some(vec![1..4, 5..7]));
+        assert_eq!(literal.find("axb"), None);
+
+        let options = MatchOptions {
+            regex: true,
+            ignore_case: true,
+            whole_word: true,
+            ..MatchOptions::default()
+        };
+        let words = matcher(&["fo+"], options);
+        assert_eq!(words.find("FOO food foo"), Some(vec![0..3, 9..12]));
+    }
+
+    #[test]
+    fn test_invert_match() {
+        let options = MatchOptions {
+            invert: true,
+            ..MatchOptions::default()
+        };
+        let inverted = matcher(&["TODO"], options);
+        assert_eq!(inverted.find("done"), Some(Vec::new()));
+        assert_eq!(inverted.find("TODO: later"), None);
+    }
+
+    #[test]
+    fn test_invalid_regex() {
+        let options = MatchOptions {
+            regex: true,
+            ..MatchOptions::default()
+        };
+        assert!(matches!(
+            Matcher::new(&["(".to_string()], options),
+            Err(Error::Pattern(_))
+        ));
+    }
+
+    #[test]
+    fn test_search_inside_archives() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src").join("a.txt"), "hay\nneedle\n").unwrap();
+        crate::modules::archive_module::create_zip(&root.join("src"), &root.join("build.zip"))
+            .unwrap();
+        fs::remove_dir_all(root.join("src")).unwrap();
+
+        let needle = matcher(&["needle"], MatchOptions::default());
+        let mut options = SearchOptions::default();
+        let found = search_data_in_files(&needle, root, &options).unwrap();
+        assert!(found.files.is_empty());
+
+        options.archives = true;
+        let found = search_data_in_files(&needle, root, &options).unwrap();
+        assert_eq!(found.files.len(), 1);
+        assert_eq!(found.files[0].path, root.join("build.zip!a.txt"));
+        assert_eq!(found.files[0].lines[0].line_number, 2);
+    }
+
+    #[test]
+    fn test_search_archive_skips_large_members() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src").join("small.txt"), "needle\n").unwrap();
+        fs::write(root.join("src").join("large.txt"), "needle\n".repeat(100)).unwrap();
+        let archive = root.join("build.zip");
+        crate::modules::archive_module::create_zip(&root.join("src"), &archive).unwrap();
+
+        let needle = matcher(&["needle"], MatchOptions::default());
+        let found = std::sync::Mutex::new(Vec::new());
+        let remaining = AtomicUsize::new(usize::MAX);
+        search_archive(
+            &needle,
+            &archive,
+            ArchiveKind::Zip,
+            64,
+            &SearchOptions::default(),
+            &remaining,
+            |file| found.lock().unwrap().push(file.path),
+        );
+        assert_eq!(
+            found.into_inner().unwrap(),
+            [root.join("build.zip!small.txt")]
+        );
+    }
+
+    #[test]
+    fn test_search_skips_ignored_and_hidden() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("build")).unwrap();
+        fs::create_dir_all(root.join(".cache")).unwrap();
+        fs::write(root.join(".ignore"), "build/\n").unwrap();
+        fs::write(root.join("notes.txt"), "needle\n").unwrap();
+        fs::write(root.join("data.bin"), b"needle\0").unwrap();
+        fs::write(root.join("build").join("out.txt"), "needle\n").unwrap();
+        fs::write(root.join(".cache").join("entry.txt"), "needle\n").unwrap();
+
+        let needle = matcher(&["needle"], MatchOptions::default());
+        let found = |options: &SearchOptions| -> Vec<(String, bool, usize)> {
+            search_data_in_files(&needle, root, options)
+                .unwrap()
+                .files
+                .into_iter()
+                .map(|file| {
+                    let path = file.path.strip_prefix(root).unwrap();
+                    (path.display().to_string(), file.binary, file.lines.len())
+                })
+                .collect()
+        };
+
+        // Binary files are reported without their lines
+        assert_eq!(
+            found(&SearchOptions::default()),
+            vec![
+                ("data.bin".to_string(), true, 0),
+                ("notes.txt".to_string(), false, 1)
+            ]
+        );
+
+        let everything = SearchOptions {
+            walk: WalkOptions {
+                hidden: true,
+                no_ignore: true,
+                exclude: vec!["notes.txt".to_string()],
+                ..WalkOptions::default()
+            },
+            binary: true,
+            ..SearchOptions::default()
+        };
+        let paths: Vec<(String, usize)> = found(&everything)
+            .into_iter()
+            .map(|(path, _, lines)| (path, lines))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                (
+                    Path::new(".cache").join("entry.txt").display().to_string(),
+                    1
+                ),
+                (Path::new("build").join("out.txt").display().to_string(), 1),
+                ("data.bin".to_string(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_context_and_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data_search_context.txt");
+        fs::write(&path, "a\nhit 1\nb\nc\nd\nhit 2\nhit 3\ne\n").unwrap();
+        let hit = matcher(&["hit"], MatchOptions::default());
+        let numbers = |file: &FileMatches| -> Vec<(usize, bool)> {
+            file.lines
+                .iter()
+                .map(|line| (line.line_number, line.context))
+                .collect()
+        };
+
+        let options = SearchOptions {
+            before_context: 1,
+            after_context: 1,
+            ..SearchOptions::default()
+        };
+        let file = search_file(&hit, &path, &options, &AtomicUsize::new(usize::MAX)).unwrap();
+        assert_eq!(file.count, 3);
+        assert_eq!(
+            numbers(&file),
+            vec![
+                (1, true),
+                (2, false),
+                (3, true),
+                (5, true),
+                (6, false),
+                (7, false),
+                (8, true)
+            ]
+        );
+
+        // The total limit stops the file early but keeps trailing context
+        let remaining = AtomicUsize::new(2);
+        let file = search_file(&hit, &path, &options, &remaining).unwrap();
+        assert_eq!(file.count, 2);
+        assert_eq!(numbers(&file).last(), Some(&(7, true)));
+        assert_eq!(remaining.into_inner(), 0);
+
+        let counts = SearchOptions {
+            limit: 1,
+            report: Report::Count,
+            ..SearchOptions::default()
+        };
+        let file = search_file(&hit, &path, &counts, &AtomicUsize::new(usize::MAX)).unwrap();
+        assert_eq!((file.count, file.lines.len()), (1, 0));
+    }
+
+    #[test]
+    fn test_decode_text() {
+        let utf16: Vec<u8> = "Grüße".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let decoded = decode_text(&utf16, None);
+        assert_eq!((decoded.encoding, decoded.binary), (UTF_16LE, false));
+        assert_eq!(decoded.text, "Grüße");
+
+        let with_bom = [&[0xFE, 0xFF, 0x00][..], b"A"].concat();
+        assert_eq!(decode_text(&with_bom, None).text, "A");
+
+        let latin1 = decode_text(b"caf\xe9", None);
+        assert_eq!(
+            (latin1.encoding, latin1.text.as_ref()),
+            (WINDOWS_1252, "café")
+        );
+
+        let binary = decode_text(b"\x7fELF\x00\x00\x01needle", None);
+        assert!(binary.binary);
+        assert!(binary.text.contains("needle"));
     }
 }
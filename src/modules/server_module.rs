@@ -30,7 +30,7 @@ pub struct CommandResponse {
     pub error: Option<String>,
 }
 
-pub async fn start_server(port: u16) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn start_server(port: u16) -> crate::Result<()> {
     let config = crate::config::Config::load().unwrap_or_default();
     let current_dir = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
 
@@ -44,6 +44,7 @@ pub async fn start_server(port: u16) -> Result<(), Box<dyn std::error::Error>> {
         .route("/api/system-info", get(api_system_info))
         .route("/api/env-vars", get(api_env_vars))
         .route("/api/execute", post(api_execute_command))
+        .route("/api/plugins", get(api_plugins))
         .route("/api/config", get(api_get_config))
         .route("/api/config", post(api_update_config))
         .with_state(state);
@@ -120,6 +121,18 @@ async fn api_env_vars() -> Json<serde_json::Value> {
     Json(serde_json::json!(vars))
 }
 
+/// Plugins the dashboard may run; empty unless `server.allow_plugins` is set.
+async fn api_plugins(
+    State(state): State<AppState>,
+) -> Json<crate::modules::plugin_module::PluginList> {
+    let plugins = if state.config.read().await.server.allow_plugins {
+        crate::modules::plugin_module::discover_plugins().plugins
+    } else {
+        Vec::new()
+    };
+    Json(crate::modules::plugin_module::PluginList { plugins })
+}
+
 async fn api_execute_command(
     State(state): State<AppState>,
     Json(payload): Json<CommandRequest>,
@@ -192,7 +205,12 @@ async fn api_execute_command(
 
     // Basic validation
     let cmd = payload.command.trim_start_matches("--");
-    if !allowed_commands.contains(&cmd) {
+    let plugin = if state.config.read().await.server.allow_plugins {
+        crate::modules::plugin_module::find_plugin(cmd)
+    } else {
+        None
+    };
+    if !allowed_commands.contains(&cmd) && plugin.is_none() {
         return Ok(Json(CommandResponse {
             success: false,
             output: String::new(),
@@ -239,8 +257,11 @@ async fn api_execute_command(
 
     // Construct arguments
     let mut args = Vec::new();
-    // Add the main command flag (e.g., --system-info or --git-status)
-    if !payload.command.starts_with("--") {
+    // Add the main command flag (e.g., --system-info or --git-status), or
+    // the plugin name, which the helper dispatches to `clh-<name>`
+    if let Some(plugin) = &plugin {
+        args.push(plugin.name.clone());
+    } else if !payload.command.starts_with("--") {
         if payload.command.len() == 1 {
             args.push(format!("-{}", payload.command));
         } else {
@@ -301,7 +322,7 @@ async fn api_update_config(
     let mut config = state.config.write().await;
     *config = new_config.clone();
 
-    if let Err(e) = config.save() {
+    if config.save().is_err() {
         return Err(StatusCode::INTERNAL_SERVER_ERROR);
     }
 
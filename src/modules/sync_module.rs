@@ -0,0 +1,504 @@
+// sync_module.rs
+//
+// One-way sync of a directory into another. Files, directories and
+// symbolic links that are new or changed in the source are copied over
+// (links as links, never followed), and on request entries that only exist
+// at the destination are deleted. Copies keep their modification times,
+// which the quick comparison of the next sync relies on.
+
+use clap::ValueEnum;
+use colored::Colorize;
+use log::debug;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, Metadata};
+use std::io;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::modules::hash_module::{hash_file, HashAlgorithm};
+use crate::modules::transfer_module::{copy_atomic, copy_link, progress_bar, resolve};
+use crate::modules::walk_module::{walk_entries, WalkOptions};
+use crate::output::Render;
+use crate::utilities::format_size;
+use crate::{Error, Result};
+
+/// How to tell whether a file at the destination is out of date.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CompareMode {
+    /// Sizes and modification times.
+    #[default]
+    Quick,
+    /// Sizes and SHA-256 hashes of the contents.
+    Hash,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SyncOptions {
+    pub compare: CompareMode,
+    /// Delete entries at the destination that are not in the source.
+    pub delete: bool,
+    /// Files and directories to leave alone on both sides (gitignore globs).
+    pub exclude: Vec<String>,
+    /// Only report the planned changes.
+    pub dry_run: bool,
+    /// Show a progress bar on stderr.
+    pub progress: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncAction {
+    Create,
+    Update,
+    Delete,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    File,
+    Directory,
+    Symlink,
+}
+
+impl EntryKind {
+    /// The kind of an entry from its `symlink_metadata`.
+    fn of(metadata: &Metadata) -> Self {
+        if metadata.is_symlink() {
+            EntryKind::Symlink
+        } else if metadata.is_dir() {
+            EntryKind::Directory
+        } else {
+            EntryKind::File
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncChange {
+    pub action: SyncAction,
+    /// What the entry is in the source, or at the destination for deletions.
+    pub kind: EntryKind,
+    /// Relative to the source and destination.
+    pub path: PathBuf,
+    /// Bytes to copy; 0 for directories and links.
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncReport {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    pub dry_run: bool,
+    pub changes: Vec<SyncChange>,
+    /// Files and links that were already up to date.
+    pub unchanged: usize,
+}
+
+/// Bring `destination` up to date with `source`. Nothing is written in a
+/// dry run.
+pub fn sync_dirs(source: &Path, destination: &Path, options: &SyncOptions) -> Result<SyncReport> {
+    if !source.is_dir() {
+        return Err(Error::NotFound(format!(
+            "Directory not found: {}",
+            source.display()
+        )));
+    }
+    if destination.exists() && !destination.is_dir() {
+        return Err(Error::InvalidInput(format!(
+            "Not a directory: {}",
+            destination.display()
+        )));
+    }
+    let source_root = fs::canonicalize(source)?;
+    let destination_root = resolve(destination);
+    if destination_root.starts_with(&source_root) || source_root.starts_with(&destination_root) {
+        return Err(Error::InvalidInput(format!(
+            "Cannot sync {} with {}: one contains the other",
+            source.display(),
+            destination.display()
+        )));
+    }
+
+    let source_entries = list_entries(source, options)?;
+    let destination_entries = if destination.is_dir() {
+        list_entries(destination, options)?
+    } else {
+        BTreeMap::new()
+    };
+
+    let mut changes = Vec::new();
+    let mut unchanged = 0;
+    for (path, metadata) in &source_entries {
+        let kind = EntryKind::of(metadata);
+        let action = match destination_entries.get(path) {
+            None => SyncAction::Create,
+            Some(existing) if EntryKind::of(existing) != kind => SyncAction::Update,
+            Some(_) if kind == EntryKind::Directory => continue,
+            Some(existing) => {
+                let (from, to) = (source.join(path), destination.join(path));
+                if differs(&from, metadata, &to, existing, options.compare)? {
+                    SyncAction::Update
+                } else {
+                    unchanged += 1;
+                    continue;
+                }
+            }
+        };
+        changes.push(change(action, path, metadata));
+    }
+    if options.delete {
+        // Children come before their directory. Directories that still hold
+        // excluded entries are kept, and so are the directories above them.
+        let mut kept = HashSet::new();
+        for (path, metadata) in destination_entries.iter().rev() {
+            if source_entries.contains_key(path) {
+                continue;
+            }
+            if metadata.is_dir() {
+                let mut children = fs::read_dir(destination.join(path))?;
+                if children.any(|child| {
+                    child.map_or(true, |child| {
+                        let child = path.join(child.file_name());
+                        !destination_entries.contains_key(&child) || kept.contains(&child)
+                    })
+                }) {
+                    kept.insert(path.clone());
+                    continue;
+                }
+            }
+            changes.push(change(SyncAction::Delete, path, metadata));
+        }
+    }
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+
+    if !options.dry_run {
+        apply(source, destination, &changes, options.progress)?;
+    }
+
+    Ok(SyncReport {
+        source: source.to_path_buf(),
+        destination: destination.to_path_buf(),
+        dry_run: options.dry_run,
+        changes,
+        unchanged,
+    })
+}
+
+fn change(action: SyncAction, path: &Path, metadata: &Metadata) -> SyncChange {
+    let kind = EntryKind::of(metadata);
+    SyncChange {
+        action,
+        kind,
+        path: path.to_path_buf(),
+        size: if kind == EntryKind::File {
+            metadata.len()
+        } else {
+            0
+        },
+    }
+}
+
+/// Every entry below `root` that is not excluded, by relative path, with
+/// the metadata of links rather than of their targets. Hidden and
+/// git-ignored entries are synced like any other.
+fn list_entries(root: &Path, options: &SyncOptions) -> Result<BTreeMap<PathBuf, Metadata>> {
+    let walk = WalkOptions {
+        hidden: true,
+        no_ignore: true,
+        exclude: options.exclude.clone(),
+        ..WalkOptions::default()
+    };
+    let entries = Mutex::new(BTreeMap::new());
+    walk_entries(root, &walk, |path, _| {
+        match fs::symlink_metadata(path) {
+            Ok(metadata) => {
+                let relative = path.strip_prefix(root).unwrap_or(path).to_path_buf();
+                entries.lock().unwrap().insert(relative, metadata);
+            }
+            Err(e) => debug!("Skipping {}: {}", path.display(), e),
+        }
+        ControlFlow::Continue(())
+    })?;
+    Ok(entries.into_inner().unwrap())
+}
+
+/// Whether two files or two links of the same kind differ.
+fn differs(
+    source: &Path,
+    source_metadata: &Metadata,
+    target: &Path,
+    target_metadata: &Metadata,
+    compare: CompareMode,
+) -> Result<bool> {
+    if source_metadata.is_symlink() {
+        return Ok(fs::read_link(source)? != fs::read_link(target)?);
+    }
+    if source_metadata.len() != target_metadata.len() {
+        return Ok(true);
+    }
+    Ok(match compare {
+        CompareMode::Quick => source_metadata.modified()? != target_metadata.modified()?,
+        CompareMode::Hash => {
+            hash_file(source, HashAlgorithm::SHA256)? != hash_file(target, HashAlgorithm::SHA256)?
+        }
+    })
+}
+
+/// Carry out `changes`. Deletions come first and deepest first, so that a
+/// file can replace a directory of the same name and the other way round.
+fn apply(source: &Path, destination: &Path, changes: &[SyncChange], progress: bool) -> Result<()> {
+    let (deletions, copies): (Vec<_>, Vec<_>) = changes
+        .iter()
+        .partition(|change| change.action == SyncAction::Delete);
+
+    for change in deletions.iter().rev() {
+        remove_entry(&destination.join(&change.path))?;
+    }
+
+    let bar = progress_bar(copies.iter().map(|change| change.size).sum(), progress);
+    fs::create_dir_all(destination)?;
+    for change in copies {
+        let (from, target) = (source.join(&change.path), destination.join(&change.path));
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // An entry of another kind is in the way; files are replaced atomically
+        let existing = fs::symlink_metadata(&target)
+            .ok()
+            .map(|m| EntryKind::of(&m));
+        if existing.is_some_and(|kind| kind != EntryKind::File || change.kind != EntryKind::File) {
+            remove_entry(&target).map_err(|e| match e.kind() {
+                io::ErrorKind::DirectoryNotEmpty => Error::InvalidInput(format!(
+                    "Cannot replace the directory {}: it is not empty (see --delete)",
+                    target.display()
+                )),
+                _ => e.into(),
+            })?;
+        }
+        bar.set_message(change.path.display().to_string());
+        match change.kind {
+            EntryKind::File => copy_atomic(&from, &target, &bar, true)?,
+            EntryKind::Directory => fs::create_dir(&target)?,
+            EntryKind::Symlink => copy_link(&from, &target)?,
+        }
+    }
+    bar.finish_and_clear();
+    Ok(())
+}
+
+/// Remove a file, link or directory at the destination. Directories must be
+/// empty: their contents are only deleted with `--delete`.
+fn remove_entry(path: &Path) -> io::Result<()> {
+    if EntryKind::of(&fs::symlink_metadata(path)?) == EntryKind::Directory {
+        fs::remove_dir(path)?;
+    } else {
+        fs::remove_file(path)?;
+    }
+    debug!("Removed {}", path.display());
+    Ok(())
+}
+
+impl Render for SyncReport {
+    fn render(&self) {
+        for change in &self.changes {
+            let symbol = match change.action {
+                SyncAction::Create => "+".green(),
+                SyncAction::Update => "~".yellow(),
+                SyncAction::Delete => "-".red(),
+            };
+            let slash = if change.kind == EntryKind::Directory {
+                "/"
+            } else {
+                ""
+            };
+            println!("{} {}{}", symbol, change.path.display(), slash);
+        }
+
+        let count = |action| {
+            self.changes
+                .iter()
+                .filter(|change| change.action == action)
+                .count()
+        };
+        let copied: u64 = self
+            .changes
+            .iter()
+            .filter(|change| change.action != SyncAction::Delete)
+            .map(|change| change.size)
+            .sum();
+        let (create, update, delete) = (
+            count(SyncAction::Create),
+            count(SyncAction::Update),
+            count(SyncAction::Delete),
+        );
+        if self.dry_run {
+            println!(
+                "\n{} {} to create, {} to update, {} to delete ({} to copy), {} unchanged",
+                "Dry run:".yellow().bold(),
+                create,
+                update,
+                delete,
+                format_size(copied),
+                self.unchanged
+            );
+        } else {
+            println!(
+                "\n{} Created {}, updated {}, deleted {} entries ({} copied), {} unchanged",
+                "Success:".green().bold(),
+                create,
+                update,
+                delete,
+                format_size(copied),
+                self.unchanged
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+
+    fn dirs(root: &Path) -> (PathBuf, PathBuf) {
+        let (source, destination) = (root.join("src"), root.join("dst"));
+        fs::create_dir_all(&source).unwrap();
+        fs::create_dir_all(&destination).unwrap();
+        (source, destination)
+    }
+
+    fn actions(report: &SyncReport) -> Vec<(SyncAction, &str)> {
+        report
+            .changes
+            .iter()
+            .map(|change| (change.action, change.path.to_str().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_dry_run_plans_without_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        let (source, destination) = dirs(dir.path());
+        fs::create_dir_all(source.join("sub")).unwrap();
+        fs::create_dir_all(destination.join("old")).unwrap();
+        fs::write(source.join("a.txt"), "new").unwrap();
+        fs::write(source.join("sub").join("b.txt"), "b").unwrap();
+        fs::write(destination.join("a.txt"), "older").unwrap();
+        fs::write(destination.join("old").join("x.txt"), "x").unwrap();
+
+        let options = SyncOptions {
+            delete: true,
+            dry_run: true,
+            ..SyncOptions::default()
+        };
+        let planned = sync_dirs(&source, &destination, &options).unwrap();
+        assert_eq!(
+            actions(&planned),
+            [
+                (SyncAction::Update, "a.txt"),
+                (SyncAction::Delete, "old"),
+                (SyncAction::Delete, "old/x.txt"),
+                (SyncAction::Create, "sub"),
+                (SyncAction::Create, "sub/b.txt"),
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(destination.join("a.txt")).unwrap(),
+            "older"
+        );
+        assert!(destination.join("old").join("x.txt").exists());
+        assert!(!destination.join("sub").exists());
+    }
+
+    #[test]
+    fn test_sync_applies_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let (source, destination) = dirs(dir.path());
+        fs::write(source.join("a.txt"), "new").unwrap();
+        fs::write(source.join("build.log"), "log").unwrap();
+        fs::write(destination.join("a.txt"), "older").unwrap();
+        fs::write(destination.join("gone.txt"), "x").unwrap();
+
+        let options = SyncOptions {
+            delete: true,
+            exclude: vec!["*.log".to_string()],
+            ..SyncOptions::default()
+        };
+        sync_dirs(&source, &destination, &options).unwrap();
+        assert_eq!(
+            fs::read_to_string(destination.join("a.txt")).unwrap(),
+            "new"
+        );
+        assert!(!destination.join("gone.txt").exists());
+        assert!(!destination.join("build.log").exists());
+
+        let again = sync_dirs(&source, &destination, &options).unwrap();
+        assert_eq!((again.changes.len(), again.unchanged), (0, 1));
+    }
+
+    #[test]
+    fn test_sync_creates_empty_dirs_and_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let (source, destination) = dirs(dir.path());
+        fs::create_dir_all(source.join("empty")).unwrap();
+        fs::write(source.join("a.txt"), "a").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("a.txt", source.join("link")).unwrap();
+
+        sync_dirs(&source, &destination, &SyncOptions::default()).unwrap();
+        assert!(destination.join("empty").is_dir());
+        #[cfg(unix)]
+        assert_eq!(
+            fs::read_link(destination.join("link")).unwrap(),
+            Path::new("a.txt")
+        );
+    }
+
+    #[test]
+    fn test_delete_keeps_dirs_with_excluded_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let (source, destination) = dirs(dir.path());
+        fs::create_dir_all(destination.join("logs").join("deep")).unwrap();
+        fs::write(destination.join("logs").join("deep").join("z.log"), "z").unwrap();
+        fs::write(destination.join("logs").join("old.txt"), "x").unwrap();
+
+        let options = SyncOptions {
+            delete: true,
+            exclude: vec!["*.log".to_string()],
+            ..SyncOptions::default()
+        };
+        let report = sync_dirs(&source, &destination, &options).unwrap();
+        assert_eq!(actions(&report), [(SyncAction::Delete, "logs/old.txt")]);
+        assert!(destination.join("logs").join("deep").join("z.log").exists());
+        assert!(!destination.join("logs").join("old.txt").exists());
+    }
+
+    #[test]
+    fn test_hash_compare_sees_same_size_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let (source, destination) = dirs(dir.path());
+        fs::write(source.join("a.txt"), "new").unwrap();
+        sync_dirs(&source, &destination, &SyncOptions::default()).unwrap();
+
+        // Same size and time: only hashing notices the change
+        let target = destination.join("a.txt");
+        let modified = fs::metadata(&target).unwrap().modified().unwrap();
+        fs::write(&target, "NEW").unwrap();
+        File::options()
+            .write(true)
+            .open(&target)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+
+        let mut options = SyncOptions::default();
+        let quick = sync_dirs(&source, &destination, &options).unwrap();
+        assert!(quick.changes.is_empty());
+        options.compare = CompareMode::Hash;
+        let hashed = sync_dirs(&source, &destination, &options).unwrap();
+        assert_eq!(actions(&hashed), [(SyncAction::Update, "a.txt")]);
+    }
+}
@@ -1,208 +1,336 @@
 use colored::Colorize;
+use serde::Serialize;
 use sysinfo::{Disks, Networks, System};
 
-/// Display system information
-pub fn display_system_info() {
+use crate::output::Render;
+
+#[derive(Debug, Serialize)]
+pub struct SystemInfo {
+    pub os: OsInfo,
+    pub cpu: CpuSummary,
+    pub memory: MemoryInfo,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OsInfo {
+    pub name: String,
+    pub kernel: String,
+    pub version: String,
+    pub host_name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CpuSummary {
+    pub count: usize,
+    pub brand: Option<String>,
+    pub frequency_mhz: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CpuInfo {
+    pub cpus: Vec<CpuCore>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CpuCore {
+    pub index: usize,
+    pub brand: String,
+    pub frequency_mhz: u64,
+    pub usage_percent: f32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemoryInfo {
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    pub available_bytes: u64,
+    pub total_swap_bytes: u64,
+    pub used_swap_bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiskInfo {
+    pub disks: Vec<DiskEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiskEntry {
+    pub name: String,
+    pub mount_point: String,
+    pub file_system: String,
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NetworkInfo {
+    pub interfaces: Vec<NetworkInterface>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NetworkInterface {
+    pub name: String,
+    pub received_bytes: u64,
+    pub transmitted_bytes: u64,
+    pub packets_received: u64,
+    pub packets_transmitted: u64,
+}
+
+/// Collect general system information
+pub fn system_info() -> SystemInfo {
     let mut sys = System::new_all();
     sys.refresh_all();
 
-    println!("{}", "System Information".cyan().bold());
-    println!("{}", "=".repeat(80).cyan());
-
-    // OS Information
-    println!("\n{}", "Operating System:".yellow().bold());
-    println!(
-        "  Name:         {}",
-        System::name()
-            .unwrap_or_else(|| "Unknown".to_string())
-            .green()
-    );
-    println!(
-        "  Kernel:       {}",
-        System::kernel_version()
-            .unwrap_or_else(|| "Unknown".to_string())
-            .green()
-    );
-    println!(
-        "  OS Version:   {}",
-        System::os_version()
-            .unwrap_or_else(|| "Unknown".to_string())
-            .green()
-    );
-    println!(
-        "  Host Name:    {}",
-        System::host_name()
-            .unwrap_or_else(|| "Unknown".to_string())
-            .green()
-    );
-
-    // CPU Information
-    println!("\n{}", "CPU:".yellow().bold());
-    println!("  CPUs:         {}", sys.cpus().len().to_string().green());
-    if let Some(cpu) = sys.cpus().first() {
-        println!("  Brand:        {}", cpu.brand().green());
-        println!(
-            "  Frequency:    {} MHz",
-            cpu.frequency().to_string().green()
-        );
+    let unknown = || "Unknown".to_string();
+    SystemInfo {
+        os: OsInfo {
+            name: System::name().unwrap_or_else(unknown),
+            kernel: System::kernel_version().unwrap_or_else(unknown),
+            version: System::os_version().unwrap_or_else(unknown),
+            host_name: System::host_name().unwrap_or_else(unknown),
+        },
+        cpu: CpuSummary {
+            count: sys.cpus().len(),
+            brand: sys.cpus().first().map(|cpu| cpu.brand().to_string()),
+            frequency_mhz: sys.cpus().first().map(|cpu| cpu.frequency()),
+        },
+        memory: collect_memory(&sys),
     }
+}
 
-    // Memory Information
-    println!("\n{}", "Memory:".yellow().bold());
-    let total_mem = sys.total_memory();
-    let used_mem = sys.used_memory();
-    let free_mem = sys.available_memory();
-
-    println!("  Total:        {} GB", format_bytes(total_mem).green());
-    println!("  Used:         {} GB", format_bytes(used_mem).green());
-    println!("  Available:    {} GB", format_bytes(free_mem).green());
-    println!(
-        "  Usage:        {}%",
-        ((used_mem as f64 / total_mem as f64) * 100.0)
-            .round()
-            .to_string()
-            .yellow()
-    );
-}
-
-/// Display CPU information
-pub fn display_cpu_info() {
+/// Collect per-CPU information
+pub fn cpu_info() -> CpuInfo {
     let mut sys = System::new_all();
     sys.refresh_all();
 
-    println!("{}", "CPU Information".cyan().bold());
-    println!("{}", "=".repeat(80).cyan());
-
-    for (i, cpu) in sys.cpus().iter().enumerate() {
-        println!("\n{} {}", "CPU".yellow().bold(), i.to_string().green());
-        println!("  Brand:        {}", cpu.brand().green());
-        println!(
-            "  Frequency:    {} MHz",
-            cpu.frequency().to_string().green()
-        );
-        println!(
-            "  Usage:        {}%",
-            cpu.cpu_usage().round().to_string().yellow()
-        );
+    CpuInfo {
+        cpus: sys
+            .cpus()
+            .iter()
+            .enumerate()
+            .map(|(index, cpu)| CpuCore {
+                index,
+                brand: cpu.brand().to_string(),
+                frequency_mhz: cpu.frequency(),
+                usage_percent: cpu.cpu_usage(),
+            })
+            .collect(),
     }
 }
 
-/// Display memory information
-pub fn display_memory_info() {
+/// Collect memory information
+pub fn memory_info() -> MemoryInfo {
     let mut sys = System::new_all();
     sys.refresh_all();
 
-    println!("{}", "Memory Information".cyan().bold());
-    println!("{}", "=".repeat(80).cyan());
-
-    let total_mem = sys.total_memory();
-    let used_mem = sys.used_memory();
-    let free_mem = sys.available_memory();
-    let total_swap = sys.total_swap();
-    let used_swap = sys.used_swap();
-
-    println!("\n{}", "RAM:".yellow().bold());
-    println!("  Total:        {} GB", format_bytes(total_mem).green());
-    println!("  Used:         {} GB", format_bytes(used_mem).green());
-    println!("  Available:    {} GB", format_bytes(free_mem).green());
-    println!(
-        "  Usage:        {}%",
-        ((used_mem as f64 / total_mem as f64) * 100.0)
-            .round()
-            .to_string()
-            .yellow()
-    );
-
-    println!("\n{}", "Swap:".yellow().bold());
-    println!("  Total:        {} GB", format_bytes(total_swap).green());
-    println!("  Used:         {} GB", format_bytes(used_swap).green());
-    if total_swap > 0 {
-        println!(
-            "  Usage:        {}%",
-            ((used_swap as f64 / total_swap as f64) * 100.0)
-                .round()
-                .to_string()
-                .yellow()
-        );
-    }
+    collect_memory(&sys)
 }
 
-/// Display disk information
-pub fn display_disk_info() {
+/// Collect disk information
+pub fn disk_info() -> DiskInfo {
     let disks = Disks::new_with_refreshed_list();
 
-    println!("{}", "Disk Information".cyan().bold());
-    println!("{}", "=".repeat(80).cyan());
+    DiskInfo {
+        disks: disks
+            .iter()
+            .map(|disk| DiskEntry {
+                name: disk.name().to_string_lossy().to_string(),
+                mount_point: disk.mount_point().display().to_string(),
+                file_system: disk.file_system().to_string_lossy().to_string(),
+                total_bytes: disk.total_space(),
+                available_bytes: disk.available_space(),
+            })
+            .collect(),
+    }
+}
 
-    for disk in &disks {
-        println!(
-            "\n{} {}",
-            "Disk:".yellow().bold(),
-            disk.name().to_string_lossy().green()
-        );
-        println!(
-            "  Mount Point:  {}",
-            disk.mount_point().display().to_string().green()
-        );
-        println!(
-            "  File System:  {}",
-            disk.file_system().to_string_lossy().green()
-        );
+/// Collect network interface statistics
+pub fn network_info() -> NetworkInfo {
+    let networks = Networks::new_with_refreshed_list();
+
+    NetworkInfo {
+        interfaces: networks
+            .iter()
+            .map(|(interface_name, network)| NetworkInterface {
+                name: interface_name.clone(),
+                received_bytes: network.total_received(),
+                transmitted_bytes: network.total_transmitted(),
+                packets_received: network.total_packets_received(),
+                packets_transmitted: network.total_packets_transmitted(),
+            })
+            .collect(),
+    }
+}
+
+fn collect_memory(sys: &System) -> MemoryInfo {
+    MemoryInfo {
+        total_bytes: sys.total_memory(),
+        used_bytes: sys.used_memory(),
+        available_bytes: sys.available_memory(),
+        total_swap_bytes: sys.total_swap(),
+        used_swap_bytes: sys.used_swap(),
+    }
+}
+
+impl MemoryInfo {
+    fn render_ram(&self) {
         println!(
             "  Total:        {} GB",
-            format_bytes(disk.total_space()).green()
+            format_bytes(self.total_bytes).green()
         );
         println!(
-            "  Available:    {} GB",
-            format_bytes(disk.available_space()).green()
+            "  Used:         {} GB",
+            format_bytes(self.used_bytes).green()
         );
         println!(
-            "  Used:         {} GB",
-            format_bytes(disk.total_space() - disk.available_space()).green()
+            "  Available:    {} GB",
+            format_bytes(self.available_bytes).green()
         );
-
-        let usage_percent = if disk.total_space() > 0 {
-            ((disk.total_space() - disk.available_space()) as f64 / disk.total_space() as f64)
-                * 100.0
-        } else {
-            0.0
-        };
         println!(
             "  Usage:        {}%",
-            usage_percent.round().to_string().yellow()
+            percent(self.used_bytes, self.total_bytes)
+                .round()
+                .to_string()
+                .yellow()
         );
     }
 }
 
-/// Display network interfaces
-pub fn display_network_info() {
-    let networks = Networks::new_with_refreshed_list();
+impl Render for SystemInfo {
+    fn render(&self) {
+        println!("{}", "System Information".cyan().bold());
+        println!("{}", "=".repeat(80).cyan());
 
-    println!("{}", "Network Interfaces".cyan().bold());
-    println!("{}", "=".repeat(80).cyan());
+        // OS Information
+        println!("\n{}", "Operating System:".yellow().bold());
+        println!("  Name:         {}", self.os.name.green());
+        println!("  Kernel:       {}", self.os.kernel.green());
+        println!("  OS Version:   {}", self.os.version.green());
+        println!("  Host Name:    {}", self.os.host_name.green());
 
-    for (interface_name, network) in &networks {
-        println!(
-            "\n{} {}",
-            "Interface:".yellow().bold(),
-            interface_name.green()
-        );
-        println!(
-            "  Received:     {} MB",
-            format_bytes(network.total_received()).green()
-        );
-        println!(
-            "  Transmitted:  {} MB",
-            format_bytes(network.total_transmitted()).green()
-        );
+        // CPU Information
+        println!("\n{}", "CPU:".yellow().bold());
+        println!("  CPUs:         {}", self.cpu.count.to_string().green());
+        if let (Some(brand), Some(frequency)) = (&self.cpu.brand, self.cpu.frequency_mhz) {
+            println!("  Brand:        {}", brand.green());
+            println!("  Frequency:    {} MHz", frequency.to_string().green());
+        }
+
+        // Memory Information
+        println!("\n{}", "Memory:".yellow().bold());
+        self.memory.render_ram();
+    }
+}
+
+impl Render for CpuInfo {
+    fn render(&self) {
+        println!("{}", "CPU Information".cyan().bold());
+        println!("{}", "=".repeat(80).cyan());
+
+        for cpu in &self.cpus {
+            println!(
+                "\n{} {}",
+                "CPU".yellow().bold(),
+                cpu.index.to_string().green()
+            );
+            println!("  Brand:        {}", cpu.brand.green());
+            println!(
+                "  Frequency:    {} MHz",
+                cpu.frequency_mhz.to_string().green()
+            );
+            println!(
+                "  Usage:        {}%",
+                cpu.usage_percent.round().to_string().yellow()
+            );
+        }
+    }
+}
+
+impl Render for MemoryInfo {
+    fn render(&self) {
+        println!("{}", "Memory Information".cyan().bold());
+        println!("{}", "=".repeat(80).cyan());
+
+        println!("\n{}", "RAM:".yellow().bold());
+        self.render_ram();
+
+        println!("\n{}", "Swap:".yellow().bold());
         println!(
-            "  Packets Rx:   {}",
-            network.total_packets_received().to_string().green()
+            "  Total:        {} GB",
+            format_bytes(self.total_swap_bytes).green()
         );
         println!(
-            "  Packets Tx:   {}",
-            network.total_packets_transmitted().to_string().green()
+            "  Used:         {} GB",
+            format_bytes(self.used_swap_bytes).green()
         );
+        if self.total_swap_bytes > 0 {
+            println!(
+                "  Usage:        {}%",
+                percent(self.used_swap_bytes, self.total_swap_bytes)
+                    .round()
+                    .to_string()
+                    .yellow()
+            );
+        }
+    }
+}
+
+impl Render for DiskInfo {
+    fn render(&self) {
+        println!("{}", "Disk Information".cyan().bold());
+        println!("{}", "=".repeat(80).cyan());
+
+        for disk in &self.disks {
+            let used = disk.total_bytes - disk.available_bytes;
+
+            println!("\n{} {}", "Disk:".yellow().bold(), disk.name.green());
+            println!("  Mount Point:  {}", disk.mount_point.green());
+            println!("  File System:  {}", disk.file_system.green());
+            println!(
+                "  Total:        {} GB",
+                format_bytes(disk.total_bytes).green()
+            );
+            println!(
+                "  Available:    {} GB",
+                format_bytes(disk.available_bytes).green()
+            );
+            println!("  Used:         {} GB", format_bytes(used).green());
+            println!(
+                "  Usage:        {}%",
+                percent(used, disk.total_bytes).round().to_string().yellow()
+            );
+        }
+    }
+}
+
+impl Render for NetworkInfo {
+    fn render(&self) {
+        println!("{}", "Network Interfaces".cyan().bold());
+        println!("{}", "=".repeat(80).cyan());
+
+        for interface in &self.interfaces {
+            println!(
+                "\n{} {}",
+                "Interface:".yellow().bold(),
+                interface.name.green()
+            );
+            println!(
+                "  Received:     {} GB",
+                format_bytes(interface.received_bytes).green()
+            );
+            println!(
+                "  Transmitted:  {} GB",
+                format_bytes(interface.transmitted_bytes).green()
+            );
+            println!(
+                "  Packets Rx:   {}",
+                interface.packets_received.to_string().green()
+            );
+            println!(
+                "  Packets Tx:   {}",
+                interface.packets_transmitted.to_string().green()
+            );
+        }
     }
 }
 
@@ -211,3 +339,11 @@ fn format_bytes(bytes: u64) -> String {
     let gb = bytes as f64 / 1_073_741_824.0;
     format!("{:.2}", gb)
 }
+
+fn percent(part: u64, total: u64) -> f64 {
+    if total > 0 {
+        (part as f64 / total as f64) * 100.0
+    } else {
+        0.0
+    }
+}
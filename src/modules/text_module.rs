@@ -1,157 +1,263 @@
 use base64::{engine::general_purpose, Engine as _};
+use clap::ValueEnum;
 use colored::Colorize;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
 use url::{form_urlencoded, Url};
 
+use crate::output::Render;
+use crate::{Error, Result};
+
+#[derive(Debug, Serialize)]
+pub struct TextTransform {
+    pub operation: &'static str,
+    pub output: String,
+    /// Label shown in front of the output in text mode.
+    #[serde(skip)]
+    pub label: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Greeting {
+    pub name: String,
+    pub greeting: String,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextCase {
+    Upper,
+    Lower,
+    Title,
+    Camel,
+    Snake,
+    Kebab,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TextStats {
+    pub lines: usize,
+    pub words: usize,
+    pub characters: usize,
+    pub bytes: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DecodedFile {
+    pub path: PathBuf,
+    pub bytes: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Replacement {
+    pub replacements: usize,
+    pub output: String,
+}
+
+fn transform(operation: &'static str, label: &'static str, output: String) -> TextTransform {
+    TextTransform {
+        operation,
+        output,
+        label,
+    }
+}
+
 /// Base64 encode a string
-pub fn base64_encode(input: &str) {
-    let encoded = general_purpose::STANDARD.encode(input.as_bytes());
-    println!("{} {}", "Encoded:".cyan().bold(), encoded.green());
+pub fn base64_encode(input: &str) -> TextTransform {
+    base64_encode_bytes(input.as_bytes())
+}
+
+/// Base64 encode arbitrary bytes
+pub fn base64_encode_bytes(input: &[u8]) -> TextTransform {
+    let encoded = general_purpose::STANDARD.encode(input);
+    transform("base64-encode", "Encoded:", encoded)
+}
+
+/// Base64 decode into raw bytes; whitespace and line breaks are ignored
+pub fn base64_decode_bytes(input: &str) -> Result<Vec<u8>> {
+    let compact: String = input.split_whitespace().collect();
+    Ok(general_purpose::STANDARD.decode(compact)?)
 }
 
 /// Base64 decode a string
-pub fn base64_decode(input: &str) {
-    match general_purpose::STANDARD.decode(input) {
-        Ok(decoded) => match String::from_utf8(decoded) {
-            Ok(text) => println!("{} {}", "Decoded:".cyan().bold(), text.green()),
-            Err(_) => eprintln!("{} Decoded data is not valid UTF-8", "Error:".red().bold()),
-        },
-        Err(e) => eprintln!("{} Failed to decode: {}", "Error:".red().bold(), e),
-    }
+pub fn base64_decode(input: &str) -> Result<TextTransform> {
+    let text = String::from_utf8(base64_decode_bytes(input)?)?;
+    Ok(transform("base64-decode", "Decoded:", text))
+}
+
+/// Base64 decode and write the raw bytes to a file
+pub fn base64_decode_to_file(input: &str, path: &Path) -> Result<DecodedFile> {
+    let decoded = base64_decode_bytes(input)?;
+    fs::write(path, &decoded)?;
+    Ok(DecodedFile {
+        path: path.to_path_buf(),
+        bytes: decoded.len(),
+    })
 }
 
 /// URL encode a string
-pub fn url_encode(input: &str) {
+pub fn url_encode(input: &str) -> TextTransform {
     let encoded: String = form_urlencoded::byte_serialize(input.as_bytes()).collect();
-    println!("{} {}", "Encoded:".cyan().bold(), encoded.green());
+    transform("url-encode", "Encoded:", encoded)
 }
 
 /// URL decode a string
-pub fn url_decode(input: &str) {
-    match Url::parse(&format!("http://example.com?q={}", input)) {
-        Ok(url) => {
-            if let Some(decoded) = url.query_pairs().next() {
-                println!("{} {}", "Decoded:".cyan().bold(), decoded.1.green());
-            }
-        }
-        Err(_) => {
-            // Try direct percent decoding
-            match form_urlencoded::parse(input.as_bytes()).next() {
-                Some((_, decoded)) => println!("{} {}", "Decoded:".cyan().bold(), decoded.green()),
-                None => eprintln!("{} Failed to decode URL", "Error:".red().bold()),
-            }
-        }
+pub fn url_decode(input: &str) -> Result<TextTransform> {
+    let input = input.trim_end_matches(['\r', '\n']);
+    let decoded = match Url::parse(&format!("http://example.com?q={}", input)) {
+        Ok(url) => url.query_pairs().next().map(|(_, value)| value.to_string()),
+        // Try direct percent decoding
+        Err(_) => form_urlencoded::parse(input.as_bytes())
+            .next()
+            .map(|(_, value)| value.to_string()),
+    };
+
+    decoded
+        .map(|text| transform("url-decode", "Decoded:", text))
+        .ok_or_else(|| Error::Decode("Failed to decode URL".to_string()))
+}
+
+/// Convert text to `case`
+pub fn to_case(input: &str, case: TextCase) -> TextTransform {
+    match case {
+        TextCase::Upper => to_uppercase(input),
+        TextCase::Lower => to_lowercase(input),
+        TextCase::Title => to_titlecase(input),
+        TextCase::Camel => to_camelcase(input),
+        TextCase::Snake => to_snakecase(input),
+        TextCase::Kebab => to_kebabcase(input),
     }
 }
 
 /// Convert text to uppercase
-pub fn to_uppercase(input: &str) {
-    println!(
-        "{} {}",
-        "Uppercase:".cyan().bold(),
-        input.to_uppercase().green()
-    );
+pub fn to_uppercase(input: &str) -> TextTransform {
+    transform("uppercase", "Uppercase:", input.to_uppercase())
 }
 
 /// Convert text to lowercase
-pub fn to_lowercase(input: &str) {
-    println!(
-        "{} {}",
-        "Lowercase:".cyan().bold(),
-        input.to_lowercase().green()
-    );
+pub fn to_lowercase(input: &str) -> TextTransform {
+    transform("lowercase", "Lowercase:", input.to_lowercase())
 }
 
 /// Convert text to title case
-pub fn to_titlecase(input: &str) {
+pub fn to_titlecase(input: &str) -> TextTransform {
     let title: String = input
         .split_whitespace()
-        .map(|word| {
-            let mut chars = word.chars();
-            match chars.next() {
-                None => String::new(),
-                Some(first) => {
-                    first.to_uppercase().collect::<String>() + &chars.as_str().to_lowercase()
-                }
-            }
-        })
+        .map(capitalize)
         .collect::<Vec<_>>()
         .join(" ");
-    println!("{} {}", "Title Case:".cyan().bold(), title.green());
+    transform("titlecase", "Title Case:", title)
 }
 
 /// Convert text to camelCase
-pub fn to_camelcase(input: &str) {
+pub fn to_camelcase(input: &str) -> TextTransform {
     let words: Vec<&str> = input
         .split(|c: char| !c.is_alphanumeric())
         .filter(|s| !s.is_empty())
         .collect();
-    if words.is_empty() {
-        println!("{} {}", "camelCase:".cyan().bold(), "".green());
-        return;
-    }
 
-    let camel = words[0].to_lowercase()
-        + &words[1..]
-            .iter()
-            .map(|word| {
-                let mut chars = word.chars();
-                match chars.next() {
-                    None => String::new(),
-                    Some(first) => {
-                        first.to_uppercase().collect::<String>() + &chars.as_str().to_lowercase()
-                    }
-                }
-            })
-            .collect::<String>();
-    println!("{} {}", "camelCase:".cyan().bold(), camel.green());
+    let camel = match words.split_first() {
+        Some((first, rest)) => {
+            first.to_lowercase() + &rest.iter().map(|word| capitalize(word)).collect::<String>()
+        }
+        None => String::new(),
+    };
+    transform("camelcase", "camelCase:", camel)
 }
 
 /// Convert text to snake_case
-pub fn to_snakecase(input: &str) {
-    let snake = input
-        .split(|c: char| !c.is_alphanumeric())
-        .filter(|s| !s.is_empty())
-        .map(|s| s.to_lowercase())
-        .collect::<Vec<_>>()
-        .join("_");
-    println!("{} {}", "snake_case:".cyan().bold(), snake.green());
+pub fn to_snakecase(input: &str) -> TextTransform {
+    transform("snakecase", "snake_case:", join_lowercase_words(input, "_"))
 }
 
 /// Convert text to kebab-case
-pub fn to_kebabcase(input: &str) {
-    let kebab = input
+pub fn to_kebabcase(input: &str) -> TextTransform {
+    transform("kebabcase", "kebab-case:", join_lowercase_words(input, "-"))
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        None => String::new(),
+        Some(first) => first.to_uppercase().collect::<String>() + &chars.as_str().to_lowercase(),
+    }
+}
+
+fn join_lowercase_words(input: &str, separator: &str) -> String {
+    input
         .split(|c: char| !c.is_alphanumeric())
         .filter(|s| !s.is_empty())
         .map(|s| s.to_lowercase())
         .collect::<Vec<_>>()
-        .join("-");
-    println!("{} {}", "kebab-case:".cyan().bold(), kebab.green());
+        .join(separator)
 }
 
 /// Count lines, words, and characters in text
-pub fn text_stats(input: &str) {
-    let lines = input.lines().count();
-    let words = input.split_whitespace().count();
-    let chars = input.chars().count();
-    let bytes = input.len();
-
-    println!("{}", "Text Statistics:".cyan().bold());
-    println!("  Lines:      {}", lines.to_string().green());
-    println!("  Words:      {}", words.to_string().green());
-    println!("  Characters: {}", chars.to_string().green());
-    println!("  Bytes:      {}", bytes.to_string().green());
+pub fn text_stats(input: &str) -> TextStats {
+    TextStats {
+        lines: input.lines().count(),
+        words: input.split_whitespace().count(),
+        characters: input.chars().count(),
+        bytes: input.len(),
+    }
 }
 
 /// Find and replace in text
-pub fn find_replace(input: &str, find: &str, replace: &str) {
-    let result = input.replace(find, replace);
-    let count = input.matches(find).count();
-
-    println!(
-        "{} Replaced {} occurrences",
-        "✓".green().bold(),
-        count.to_string().yellow()
-    );
-    println!("{}", "Result:".cyan().bold());
-    println!("{}", result.green());
+pub fn greet(name: &str) -> Greeting {
+    Greeting {
+        name: name.to_string(),
+        greeting: format!("Hello {}!", name),
+    }
+}
+
+pub fn find_replace(input: &str, find: &str, replace: &str) -> Replacement {
+    Replacement {
+        replacements: input.matches(find).count(),
+        output: input.replace(find, replace),
+    }
+}
+
+impl Render for Greeting {
+    fn render(&self) {
+        println!("{}", self.greeting);
+    }
+}
+
+impl Render for TextTransform {
+    fn render(&self) {
+        println!("{} {}", self.label.cyan().bold(), self.output.green());
+    }
+}
+
+impl Render for TextStats {
+    fn render(&self) {
+        println!("{}", "Text Statistics:".cyan().bold());
+        println!("  Lines:      {}", self.lines.to_string().green());
+        println!("  Words:      {}", self.words.to_string().green());
+        println!("  Characters: {}", self.characters.to_string().green());
+        println!("  Bytes:      {}", self.bytes.to_string().green());
+    }
+}
+
+impl Render for DecodedFile {
+    fn render(&self) {
+        println!(
+            "{} Wrote {} decoded bytes to {}",
+            "✓".green().bold(),
+            self.bytes.to_string().yellow(),
+            self.path.display().to_string().green()
+        );
+    }
+}
+
+impl Render for Replacement {
+    fn render(&self) {
+        println!(
+            "{} Replaced {} occurrences",
+            "✓".green().bold(),
+            self.replacements.to_string().yellow()
+        );
+        println!("{}", "Result:".cyan().bold());
+        println!("{}", self.output.green());
+    }
 }
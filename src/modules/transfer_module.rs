@@ -0,0 +1,818 @@
+// transfer_module.rs
+//
+// Copying and moving files and whole directory trees. Sources can be glob
+// patterns, which is handy when the shell does not expand them (quoted, or
+// on Windows). Transfers report their progress in bytes and files on
+// stderr. Files are written under a temporary name and renamed into place,
+// so an interrupted copy never leaves a half-written file behind.
+
+use clap::ValueEnum;
+use colored::Colorize;
+use dialoguer::Input;
+use indicatif::{ProgressBar, ProgressStyle};
+use log::{debug, info};
+use serde::Serialize;
+use std::fs::{self, File, FileTimes};
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::output::Render;
+use crate::utilities::{format_size, write_atomic_with};
+use crate::{Error, Result};
+
+/// What happens when a file already exists at the destination.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Replace the existing file.
+    #[default]
+    Overwrite,
+    /// Keep the existing file.
+    Skip,
+    /// Write the new file under a free name like `name (1).txt`.
+    Rename,
+    /// Ask for each file.
+    Ask,
+    /// Replace the existing file only if the source was modified later.
+    Newer,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TransferOptions {
+    /// New name at the destination; only for a single source.
+    pub name: Option<String>,
+    /// Copy permissions and access and modification times too. Moves
+    /// always keep them.
+    pub preserve: bool,
+    pub on_conflict: ConflictPolicy,
+    /// Show a progress bar on stderr.
+    pub progress: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Transfer {
+    pub operation: &'static str,
+    pub destination: PathBuf,
+    pub entries: Vec<TransferEntry>,
+    /// Files copied or moved, including those inside directories.
+    pub files: usize,
+    pub bytes: u64,
+    /// Sources left alone because a file already existed at the destination.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<PathBuf>,
+    /// Files written under a free name because the destination existed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub renamed: Vec<TransferEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransferEntry {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+}
+
+/// One source and everything below it.
+struct PlannedEntry {
+    source: PathBuf,
+    target: PathBuf,
+    /// Directories to create, parents first.
+    dirs: Vec<(PathBuf, PathBuf)>,
+    files: Vec<PlannedFile>,
+}
+
+struct PlannedFile {
+    source: PathBuf,
+    target: PathBuf,
+    size: u64,
+    /// A symbolic link, moved as a link rather than what it points to.
+    link: bool,
+}
+
+impl PlannedEntry {
+    fn bytes(&self) -> u64 {
+        self.files.iter().map(|file| file.size).sum()
+    }
+}
+
+/// Answer to the question asked for each conflict with `--on-conflict ask`.
+enum Answer {
+    Overwrite,
+    Skip,
+    Rename,
+    OverwriteAll,
+    SkipAll,
+}
+
+/// Copy files and directories into `destination`, which is created if
+/// needed. Directories are copied with everything below them and merged
+/// into existing ones.
+pub fn copy_paths(
+    sources: &[PathBuf],
+    destination: &Path,
+    options: &TransferOptions,
+) -> Result<Transfer> {
+    let plan = plan(sources, destination, options, true)?;
+    let mut run = Run::new(&plan, options);
+
+    fs::create_dir_all(destination)?;
+    for entry in &plan {
+        let created = create_dirs(entry)?;
+        for file in &entry.files {
+            run.copy(file)?;
+        }
+        // After the contents, which would change the times again
+        if options.preserve {
+            for (source, target) in created.iter().rev() {
+                preserve_metadata(source, target)?;
+            }
+        }
+    }
+
+    info!("Copied {} files to {}", run.files, destination.display());
+    Ok(run.finish("copy", destination, plan))
+}
+
+/// Move files and directories into `destination`, which is created if
+/// needed. Across filesystems, files are copied, compared with the
+/// original and only then deleted. Symbolic links are moved as links, so
+/// what they point to stays where it is.
+pub fn move_paths(
+    sources: &[PathBuf],
+    destination: &Path,
+    options: &TransferOptions,
+) -> Result<Transfer> {
+    let plan = plan(sources, destination, options, false)?;
+    let mut run = Run::new(&plan, options);
+
+    fs::create_dir_all(destination)?;
+    for entry in &plan {
+        // Without a conflict, a whole tree is renamed at once
+        if fs::symlink_metadata(&entry.target).is_err() {
+            match fs::rename(&entry.source, &entry.target) {
+                Ok(()) => {
+                    run.bar.inc(entry.bytes());
+                    entry.files.iter().for_each(|file| run.count(file));
+                    continue;
+                }
+                Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+                    debug!(
+                        "{} is on another filesystem, copying instead",
+                        entry.target.display()
+                    );
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let created = create_dirs(entry)?;
+        for file in &entry.files {
+            run.move_file(file)?;
+        }
+        for (source, target) in created.iter().rev() {
+            preserve_metadata(source, target)?;
+        }
+        // Directories that still hold skipped files stay
+        for (source, _) in entry.dirs.iter().rev() {
+            if let Err(e) = fs::remove_dir(source) {
+                debug!("Keeping {}: {}", source.display(), e);
+            }
+        }
+    }
+
+    info!("Moved {} files to {}", run.files, destination.display());
+    Ok(run.finish("move", destination, plan))
+}
+
+/// Carries out a plan file by file and keeps track of what was done.
+struct Run<'a> {
+    options: &'a TransferOptions,
+    /// The conflict policy, until an "all" answer replaces it.
+    policy: ConflictPolicy,
+    bar: ProgressBar,
+    files: usize,
+    bytes: u64,
+    skipped: Vec<PathBuf>,
+    renamed: Vec<TransferEntry>,
+}
+
+impl<'a> Run<'a> {
+    fn new(plan: &[PlannedEntry], options: &'a TransferOptions) -> Self {
+        Run {
+            options,
+            policy: options.on_conflict,
+            bar: progress_bar(plan.iter().map(PlannedEntry::bytes).sum(), options.progress),
+            files: 0,
+            bytes: 0,
+            skipped: Vec::new(),
+            renamed: Vec::new(),
+        }
+    }
+
+    fn copy(&mut self, file: &PlannedFile) -> Result<()> {
+        let Some(target) = self.destination(file)? else {
+            return Ok(());
+        };
+        copy_atomic(&file.source, &target, &self.bar, self.options.preserve)?;
+        self.done(file, target);
+        Ok(())
+    }
+
+    fn move_file(&mut self, file: &PlannedFile) -> Result<()> {
+        let Some(target) = self.destination(file)? else {
+            return Ok(());
+        };
+        match fs::rename(&file.source, &target) {
+            Ok(()) => self.bar.inc(file.size),
+            Err(e) if e.kind() == io::ErrorKind::CrossesDevices && file.link => {
+                if fs::symlink_metadata(&target).is_ok() {
+                    fs::remove_file(&target)?;
+                }
+                copy_link(&file.source, &target)?;
+                fs::remove_file(&file.source)?;
+            }
+            Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+                copy_atomic(&file.source, &target, &self.bar, true)?;
+                if !same_contents(&file.source, &target)? {
+                    return Err(Error::Io(io::Error::other(format!(
+                        "{} differs from {} after copying; the original was kept",
+                        target.display(),
+                        file.source.display()
+                    ))));
+                }
+                fs::remove_file(&file.source)?;
+            }
+            Err(e) => return Err(e.into()),
+        }
+        self.done(file, target);
+        Ok(())
+    }
+
+    /// Where `file` goes under the conflict policy, or `None` if it is
+    /// skipped.
+    fn destination(&mut self, file: &PlannedFile) -> Result<Option<PathBuf>> {
+        if fs::symlink_metadata(&file.target).is_err() {
+            return Ok(Some(file.target.clone()));
+        }
+
+        let overwrite = match self.policy {
+            ConflictPolicy::Overwrite => true,
+            ConflictPolicy::Skip => false,
+            ConflictPolicy::Rename => return Ok(Some(free_name(&file.target))),
+            ConflictPolicy::Newer => {
+                let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified());
+                modified(&file.source)? > modified(&file.target)?
+            }
+            ConflictPolicy::Ask => match self.bar.suspend(|| ask(&file.target))? {
+                Answer::Overwrite => true,
+                Answer::Skip => false,
+                Answer::Rename => return Ok(Some(free_name(&file.target))),
+                Answer::OverwriteAll => {
+                    self.policy = ConflictPolicy::Overwrite;
+                    true
+                }
+                Answer::SkipAll => {
+                    self.policy = ConflictPolicy::Skip;
+                    false
+                }
+            },
+        };
+
+        if overwrite {
+            Ok(Some(file.target.clone()))
+        } else {
+            debug!("Keeping existing {}", file.target.display());
+            self.bar.inc(file.size);
+            self.skipped.push(file.source.clone());
+            Ok(None)
+        }
+    }
+
+    fn done(&mut self, file: &PlannedFile, target: PathBuf) {
+        self.count(file);
+        if target != file.target {
+            self.renamed.push(TransferEntry {
+                source: file.source.clone(),
+                destination: target,
+            });
+        }
+    }
+
+    fn count(&mut self, file: &PlannedFile) {
+        self.files += 1;
+        self.bytes += file.size;
+        self.bar.set_message(format!("{} files", self.files));
+    }
+
+    fn finish(
+        self,
+        operation: &'static str,
+        destination: &Path,
+        plan: Vec<PlannedEntry>,
+    ) -> Transfer {
+        self.bar.finish_and_clear();
+        Transfer {
+            operation,
+            destination: destination.to_path_buf(),
+            entries: plan
+                .into_iter()
+                .map(|entry| TransferEntry {
+                    source: entry.source,
+                    destination: entry.target,
+                })
+                .collect(),
+            files: self.files,
+            bytes: self.bytes,
+            skipped: self.skipped,
+            renamed: self.renamed,
+        }
+    }
+}
+
+fn ask(target: &Path) -> Result<Answer> {
+    let answer = Input::<String>::new()
+        .with_prompt(format!(
+            "{} exists: [o]verwrite, [s]kip, [r]ename, overwrite [a]ll, s[k]ip all",
+            target.display()
+        ))
+        .validate_with(|answer: &String| -> std::result::Result<(), &str> {
+            match answer.trim() {
+                "o" | "s" | "r" | "a" | "k" => Ok(()),
+                _ => Err("answer o, s, r, a or k"),
+            }
+        })
+        .interact_text()
+        .map_err(|dialoguer::Error::IO(e)| Error::from(e))?;
+
+    Ok(match answer.trim() {
+        "o" => Answer::Overwrite,
+        "s" => Answer::Skip,
+        "r" => Answer::Rename,
+        "a" => Answer::OverwriteAll,
+        _ => Answer::SkipAll,
+    })
+}
+
+/// Create the directories of `entry` that do not exist yet and return them.
+fn create_dirs(entry: &PlannedEntry) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let mut created = Vec::new();
+    for (source, target) in &entry.dirs {
+        if !target.is_dir() {
+            fs::create_dir_all(target)?;
+            created.push((source.clone(), target.clone()));
+        }
+    }
+    Ok(created)
+}
+
+/// Every file and directory to transfer, found before anything is touched
+/// so that the progress bar knows the total. Unless `follow_links`,
+/// symbolic links are planned as links and never descended into.
+fn plan(
+    sources: &[PathBuf],
+    destination: &Path,
+    options: &TransferOptions,
+    follow_links: bool,
+) -> Result<Vec<PlannedEntry>> {
+    let sources = expand_sources(sources)?;
+    if options.name.is_some() && sources.len() != 1 {
+        return Err(Error::InvalidInput(format!(
+            "--name needs exactly one source, got {}",
+            sources.len()
+        )));
+    }
+
+    let mut plan = Vec::new();
+    for source in sources {
+        let link = !follow_links && fs::symlink_metadata(&source)?.is_symlink();
+        let name = match &options.name {
+            Some(name) => name.into(),
+            // The name of the link itself, not of what it points to
+            None if link => source
+                .file_name()
+                .map(|name| name.to_os_string())
+                .ok_or_else(|| {
+                    Error::InvalidInput(format!("Cannot transfer {}", source.display()))
+                })?,
+            None => fs::canonicalize(&source)?
+                .file_name()
+                .map(|name| name.to_os_string())
+                .ok_or_else(|| {
+                    Error::InvalidInput(format!("Cannot transfer {}", source.display()))
+                })?,
+        };
+        let target = destination.join(name);
+        if target.exists() && same_file(&source, &target)? {
+            return Err(Error::InvalidInput(format!(
+                "{} is already at {}",
+                source.display(),
+                target.display()
+            )));
+        }
+
+        let mut entry = PlannedEntry {
+            source: source.clone(),
+            target: target.clone(),
+            dirs: Vec::new(),
+            files: Vec::new(),
+        };
+        if source.is_dir() && !link {
+            if resolve(destination).starts_with(fs::canonicalize(&source)?) {
+                return Err(Error::InvalidInput(format!(
+                    "Cannot transfer {} into itself",
+                    source.display()
+                )));
+            }
+            let walk = WalkDir::new(&source)
+                .follow_links(follow_links)
+                .sort_by_file_name();
+            for item in walk {
+                let item = item.map_err(|e| Error::Io(e.into()))?;
+                let relative = item.path().strip_prefix(&source).unwrap_or(item.path());
+                let item_target = target.join(relative);
+                if item.file_type().is_dir() {
+                    entry.dirs.push((item.path().to_path_buf(), item_target));
+                } else {
+                    let link = item.path_is_symlink();
+                    entry.files.push(PlannedFile {
+                        source: item.path().to_path_buf(),
+                        target: item_target,
+                        size: if link {
+                            0
+                        } else {
+                            item.metadata().map_err(|e| Error::Io(e.into()))?.len()
+                        },
+                        link,
+                    });
+                }
+            }
+        } else {
+            entry.files.push(PlannedFile {
+                size: if link {
+                    0
+                } else {
+                    fs::metadata(&source)?.len()
+                },
+                source,
+                target,
+                link,
+            });
+        }
+        plan.push(entry);
+    }
+    Ok(plan)
+}
+
+/// The existing paths among `sources`, with glob patterns replaced by the
+/// paths they match.
+pub(crate) fn expand_sources(sources: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut expanded = Vec::new();
+    for source in sources {
+        if source.exists() {
+            expanded.push(source.clone());
+            continue;
+        }
+        let pattern = source.to_string_lossy();
+        if !pattern.contains(['*', '?', '[']) {
+            return Err(Error::NotFound(format!(
+                "Source not found: {}",
+                source.display()
+            )));
+        }
+        let matches: Vec<PathBuf> = glob::glob(&pattern)
+            .map_err(|e| Error::Pattern(e.to_string()))?
+            .filter_map(|path| path.ok())
+            .collect();
+        if matches.is_empty() {
+            return Err(Error::NotFound(format!("No files match {}", pattern)));
+        }
+        debug!("{} matches {} paths", pattern, matches.len());
+        expanded.extend(matches);
+    }
+    Ok(expanded)
+}
+
+/// `path` made absolute with symlinks resolved, even if its last
+/// components do not exist yet.
+pub(crate) fn resolve(path: &Path) -> PathBuf {
+    let mut missing = Vec::new();
+    let mut existing = path;
+    loop {
+        if let Ok(resolved) = fs::canonicalize(existing) {
+            return missing
+                .iter()
+                .rev()
+                .fold(resolved, |path, name| path.join(name));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                existing = if parent.as_os_str().is_empty() {
+                    Path::new(".")
+                } else {
+                    parent
+                };
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+pub(crate) fn same_file(a: &Path, b: &Path) -> Result<bool> {
+    Ok(fs::canonicalize(a)? == fs::canonicalize(b)?)
+}
+
+/// `path` with ` (1)`, ` (2)`, ... added to its stem, whichever is free
+/// first.
+fn free_name(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    (1..)
+        .map(|number| path.with_file_name(format!("{} ({}){}", stem, number, extension)))
+        .find(|candidate| fs::symlink_metadata(candidate).is_err())
+        .expect("a free name")
+}
+
+/// Copy `source` to `target` through a temporary file, so that `target`
+/// is either complete or untouched. Like `fs::copy`, the copy gets the
+/// permissions of `source`; `preserve` keeps its times too.
+pub(crate) fn copy_atomic(
+    source: &Path,
+    target: &Path,
+    bar: &ProgressBar,
+    preserve: bool,
+) -> Result<()> {
+    let metadata = fs::metadata(source)?;
+    let mut reader = bar.wrap_read(File::open(source)?);
+    write_atomic_with(target, |file| {
+        io::copy(&mut reader, file)?;
+        // Through the open file, which may not be writable afterwards
+        if preserve {
+            file.set_times(file_times(&metadata)?)?;
+        }
+        file.set_permissions(metadata.permissions())
+    })?;
+    Ok(())
+}
+
+/// Recreate the link at `source` as `target`, pointing to the same place.
+#[cfg(unix)]
+pub(crate) fn copy_link(source: &Path, target: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(source)?, target)
+}
+
+#[cfg(windows)]
+pub(crate) fn copy_link(source: &Path, target: &Path) -> io::Result<()> {
+    use std::os::windows::fs::{symlink_dir, symlink_file};
+
+    let link = fs::read_link(source)?;
+    if fs::metadata(source).is_ok_and(|metadata| metadata.is_dir()) {
+        symlink_dir(link, target)
+    } else {
+        symlink_file(link, target)
+    }
+}
+
+/// Whether two files have the same contents.
+fn same_contents(a: &Path, b: &Path) -> Result<bool> {
+    if fs::metadata(a)?.len() != fs::metadata(b)?.len() {
+        return Ok(false);
+    }
+    let mut a = BufReader::new(File::open(a)?);
+    let mut b = BufReader::new(File::open(b)?);
+    let mut chunk_a = [0; 8192];
+    let mut chunk_b = [0; 8192];
+    loop {
+        let read = a.read(&mut chunk_a)?;
+        if read == 0 {
+            return Ok(true);
+        }
+        b.read_exact(&mut chunk_b[..read])?;
+        if chunk_a[..read] != chunk_b[..read] {
+            return Ok(false);
+        }
+    }
+}
+
+/// Give `target` the permissions and times of `source`.
+fn preserve_metadata(source: &Path, target: &Path) -> Result<()> {
+    let metadata = fs::metadata(source)?;
+    // Times first: the permissions may not allow opening the file for writing
+    if metadata.is_dir() {
+        File::open(target)?.set_times(file_times(&metadata)?)?;
+    } else {
+        File::options()
+            .write(true)
+            .open(target)?
+            .set_times(file_times(&metadata)?)?;
+    }
+    fs::set_permissions(target, metadata.permissions())?;
+    Ok(())
+}
+
+fn file_times(metadata: &fs::Metadata) -> io::Result<FileTimes> {
+    Ok(FileTimes::new()
+        .set_accessed(metadata.accessed()?)
+        .set_modified(metadata.modified()?))
+}
+
+/// A progress bar for copying `total` bytes, hidden unless `visible`.
+pub(crate) fn progress_bar(total: u64, visible: bool) -> ProgressBar {
+    if !visible {
+        return ProgressBar::hidden();
+    }
+    let bar = ProgressBar::new(total);
+    bar.set_style(
+        ProgressStyle::with_template(
+            "{spinner:.green} [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta}) {msg}",
+        )
+        .expect("valid progress template")
+        .progress_chars("=>-"),
+    );
+    bar
+}
+
+impl Render for Transfer {
+    fn render(&self) {
+        for entry in &self.entries {
+            println!(
+                "{} {} → {}",
+                "✓".green(),
+                entry.source.display(),
+                entry.destination.display().to_string().yellow()
+            );
+        }
+        for entry in &self.renamed {
+            println!(
+                "{} {} → {} (destination existed)",
+                "↪".cyan(),
+                entry.source.display(),
+                entry.destination.display().to_string().yellow()
+            );
+        }
+        for source in &self.skipped {
+            println!(
+                "{} Skipped {} (destination exists)",
+                "-".yellow(),
+                source.display()
+            );
+        }
+        let verb = if self.operation == "move" {
+            "Moved"
+        } else {
+            "Copied"
+        };
+        println!(
+            "\n{} {} {} files ({}) to {}",
+            "Success:".green().bold(),
+            verb,
+            self.files,
+            format_size(self.bytes),
+            self.destination.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_copy_directory_and_globs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src").join("nested")).unwrap();
+        fs::write(root.join("src").join("a.txt"), "a").unwrap();
+        fs::write(root.join("src").join("nested").join("b.txt"), "bb").unwrap();
+        let old = SystemTime::now() - Duration::from_secs(86400);
+        File::options()
+            .write(true)
+            .open(root.join("src").join("a.txt"))
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        let options = TransferOptions {
+            preserve: true,
+            ..TransferOptions::default()
+        };
+        let copied = copy_paths(&[root.join("src")], &root.join("out"), &options).unwrap();
+        assert_eq!((copied.files, copied.bytes), (2, 3));
+        let copy = root.join("out").join("src");
+        assert_eq!(
+            fs::read_to_string(copy.join("nested").join("b.txt")).unwrap(),
+            "bb"
+        );
+        assert_eq!(
+            fs::metadata(copy.join("a.txt"))
+                .unwrap()
+                .modified()
+                .unwrap(),
+            old
+        );
+
+        // Copying a directory into itself is refused
+        let inside = root.join("src").join("new");
+        assert!(copy_paths(&[root.join("src")], &inside, &options).is_err());
+
+        let pattern = root.join("src").join("*.txt");
+        let moved = move_paths(&[pattern], &root.join("moved"), &options).unwrap();
+        assert_eq!(moved.entries.len(), 1);
+        assert!(root.join("moved").join("a.txt").exists());
+        assert!(!root.join("src").join("a.txt").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_copy_keeps_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("out")).unwrap();
+        let script = root.join("run.sh");
+        fs::write(&script, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        // Overwriting keeps the mode of the source, not of the old file
+        fs::write(root.join("out").join("run.sh"), "").unwrap();
+
+        copy_paths(&[script], &root.join("out"), &TransferOptions::default()).unwrap();
+        let mode = fs::metadata(root.join("out").join("run.sh"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_move_keeps_link_targets() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("outside")).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("dst").join("src")).unwrap();
+        fs::write(root.join("outside").join("f.txt"), "f").unwrap();
+        fs::write(root.join("src").join("a.txt"), "a").unwrap();
+        std::os::unix::fs::symlink("../outside", root.join("src").join("link")).unwrap();
+
+        // The existing dst/src makes the move merge file by file
+        let moved = move_paths(
+            &[root.join("src")],
+            &root.join("dst"),
+            &TransferOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(moved.files, 2);
+        assert!(!root.join("src").exists());
+        assert_eq!(
+            fs::read_link(root.join("dst").join("src").join("link")).unwrap(),
+            Path::new("../outside")
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("outside").join("f.txt")).unwrap(),
+            "f"
+        );
+    }
+
+    #[test]
+    fn test_conflict_policies() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("out")).unwrap();
+        fs::write(root.join("a.txt"), "new").unwrap();
+        fs::write(root.join("out").join("a.txt"), "old").unwrap();
+        let copy = |on_conflict| {
+            let options = TransferOptions {
+                on_conflict,
+                ..TransferOptions::default()
+            };
+            copy_paths(&[root.join("a.txt")], &root.join("out"), &options).unwrap()
+        };
+        let contents = |name: &str| fs::read_to_string(root.join("out").join(name)).unwrap();
+
+        let skipped = copy(ConflictPolicy::Skip);
+        assert_eq!((skipped.files, skipped.skipped.len()), (0, 1));
+        assert_eq!(contents("a.txt"), "old");
+
+        let renamed = copy(ConflictPolicy::Rename);
+        assert_eq!(
+            renamed.renamed[0].destination,
+            root.join("out").join("a (1).txt")
+        );
+        assert_eq!(contents("a (1).txt"), "new");
+
+        // The existing file is the newer one
+        let newer = copy(ConflictPolicy::Newer);
+        assert_eq!(newer.skipped.len(), 1);
+        assert_eq!(contents("a.txt"), "old");
+
+        copy(ConflictPolicy::Overwrite);
+        assert_eq!(contents("a.txt"), "new");
+        // No temporary files are left behind
+        assert_eq!(fs::read_dir(root.join("out")).unwrap().count(), 2);
+    }
+}
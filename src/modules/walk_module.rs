@@ -0,0 +1,108 @@
+// walk_module.rs
+//
+// Parallel directory walker shared by the commands that read many files.
+// Like git and ripgrep, it skips hidden entries and anything listed in
+// `.gitignore`, `.ignore` or the global git excludes unless asked not to.
+
+use ignore::overrides::OverrideBuilder;
+use ignore::{WalkBuilder, WalkState};
+use log::debug;
+use std::fs::FileType;
+use std::ops::ControlFlow;
+use std::path::Path;
+
+use crate::{Error, Result};
+
+/// Which files below a root are visited.
+#[derive(Debug, Clone, Default)]
+pub struct WalkOptions {
+    /// Deepest level to descend to; files directly in the root are level 1.
+    pub max_depth: Option<usize>,
+    /// Visit hidden files and directories too.
+    pub hidden: bool,
+    /// Do not read `.gitignore`, `.ignore` and git exclude files.
+    pub no_ignore: bool,
+    /// Only visit files matching one of these globs (gitignore syntax).
+    pub include: Vec<String>,
+    /// Skip files and directories matching these globs.
+    pub exclude: Vec<String>,
+    /// Worker threads; 0 picks one per CPU.
+    pub threads: usize,
+}
+
+/// Call `visit` with every regular file below `root`, from several threads
+/// at once, until it returns `Break`. Entries that cannot be read are
+/// logged and skipped.
+pub fn walk_files<F>(root: &Path, options: &WalkOptions, visit: F) -> Result<()>
+where
+    F: Fn(&Path) -> ControlFlow<()> + Sync,
+{
+    walk_entries(root, options, |path, kind| {
+        if kind.is_file() {
+            visit(path)
+        } else {
+            ControlFlow::Continue(())
+        }
+    })
+}
+
+/// Like [`walk_files`], but directories and symbolic links below `root` are
+/// visited too. Links are not followed.
+pub fn walk_entries<F>(root: &Path, options: &WalkOptions, visit: F) -> Result<()>
+where
+    F: Fn(&Path, FileType) -> ControlFlow<()> + Sync,
+{
+    if !root.is_dir() {
+        return Err(Error::NotFound(format!(
+            "Directory not found: {}",
+            root.display()
+        )));
+    }
+
+    let mut overrides = OverrideBuilder::new(root);
+    let globs = options
+        .include
+        .iter()
+        .cloned()
+        .chain(options.exclude.iter().map(|glob| format!("!{}", glob)));
+    for glob in globs {
+        overrides
+            .add(&glob)
+            .map_err(|e| Error::Pattern(e.to_string()))?;
+    }
+    let overrides = overrides
+        .build()
+        .map_err(|e| Error::Pattern(e.to_string()))?;
+
+    let respect_ignore = !options.no_ignore;
+    WalkBuilder::new(root)
+        .max_depth(options.max_depth)
+        .hidden(!options.hidden)
+        .parents(respect_ignore)
+        .ignore(respect_ignore)
+        .git_ignore(respect_ignore)
+        .git_global(respect_ignore)
+        .git_exclude(respect_ignore)
+        .overrides(overrides)
+        .threads(options.threads)
+        .build_parallel()
+        .run(|| {
+            let visit = &visit;
+            Box::new(move |entry| {
+                match entry {
+                    Ok(entry) if entry.depth() > 0 => {
+                        if let Some(kind) = entry.file_type() {
+                            if visit(entry.path(), kind).is_break() {
+                                return WalkState::Quit;
+                            }
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => debug!("Skipping unreadable entry: {}", e),
+                }
+                WalkState::Continue
+            })
+        });
+
+    Ok(())
+}
@@ -1,14 +1,25 @@
 use colored::Colorize;
+use serde::Serialize;
+use std::path::PathBuf;
 use std::process::{Command, Stdio};
 
-/// Downloads a YouTube video using yt-dlp
-pub async fn download_video(
-    url: &str,
-    download_path: &str,
-    quality: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
-    println!("{}", "YouTube Video Downloader".cyan().bold());
-    println!("{}", "=".repeat(80).cyan());
+use crate::output::Render;
+use crate::{Error, Result};
+
+#[derive(Debug, Serialize)]
+pub struct Download {
+    pub url: String,
+    pub quality: String,
+    pub destination: PathBuf,
+    /// Files written or already there, as yt-dlp reports them.
+    pub files: Vec<PathBuf>,
+}
+
+/// Downloads a YouTube video using yt-dlp. Progress goes to stderr, so
+/// that stdout only carries the result.
+pub async fn download_video(url: &str, download_path: &str, quality: &str) -> Result<Download> {
+    eprintln!("{}", "YouTube Video Downloader".cyan().bold());
+    eprintln!("{}", "=".repeat(80).cyan());
 
     // Check if yt-dlp is installed
     let yt_dlp_check = if cfg!(target_os = "windows") {
@@ -40,7 +51,7 @@ pub async fn download_video(
                 );
             }
 
-            return Err("yt-dlp not found".into());
+            return Err(Error::NotFound("yt-dlp is not installed".to_string()));
         }
         Err(e) => {
             eprintln!(
@@ -51,7 +62,7 @@ pub async fn download_video(
             return Err(e.into());
         }
         _ => {
-            println!("{} yt-dlp found", "✓".green());
+            eprintln!("{} yt-dlp found", "✓".green());
         }
     }
 
@@ -62,10 +73,10 @@ pub async fn download_video(
         _ => "bestvideo+bestaudio/best", // Default to best
     };
 
-    println!("{} Quality: {}", "→".cyan(), quality.yellow());
-    println!("{} Format: {}", "→".cyan(), format_arg.yellow());
-    println!("{} Destination: {}", "→".cyan(), download_path.yellow());
-    println!();
+    eprintln!("{} Quality: {}", "→".cyan(), quality.yellow());
+    eprintln!("{} Format: {}", "→".cyan(), format_arg.yellow());
+    eprintln!("{} Destination: {}", "→".cyan(), download_path.yellow());
+    eprintln!();
 
     // Build yt-dlp command
     let mut cmd = Command::new("yt-dlp");
@@ -90,26 +101,32 @@ pub async fn download_video(
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
 
-    println!("{} Starting download...", "→".cyan());
+    eprintln!("{} Starting download...", "→".cyan());
 
     // Execute command
     let output = cmd.output()?;
 
     if output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout);
-
-        // Print relevant output lines
-        for line in stdout.lines() {
-            if line.contains("Destination:")
-                || line.contains("100%")
-                || line.contains("has already been downloaded")
-            {
-                println!("{}", line);
-            }
-        }
-
-        println!("\n{} Download complete!", "✓".green().bold());
-        Ok(())
+        let files = stdout
+            .lines()
+            .filter_map(|line| {
+                line.split_once("Destination: ")
+                    .map(|(_, path)| path)
+                    .or_else(|| {
+                        line.strip_prefix("[download] ")?
+                            .strip_suffix(" has already been downloaded")
+                    })
+            })
+            .map(PathBuf::from)
+            .collect();
+
+        Ok(Download {
+            url: url.to_string(),
+            quality: quality.to_string(),
+            destination: PathBuf::from(download_path),
+            files,
+        })
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
         eprintln!("\n{} Download failed", "✗".red().bold());
@@ -124,6 +141,18 @@ pub async fn download_video(
             "pip install -U yt-dlp".green()
         );
 
-        Err(format!("yt-dlp failed with exit code: {}", output.status).into())
+        Err(Error::Command {
+            command: "yt-dlp".to_string(),
+            code: output.status.code(),
+        })
+    }
+}
+
+impl Render for Download {
+    fn render(&self) {
+        for file in &self.files {
+            println!("  {}", file.display());
+        }
+        println!("\n{} Download complete!", "✓".green().bold());
     }
 }
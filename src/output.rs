@@ -0,0 +1,72 @@
+// output.rs
+//
+// Every operation returns a typed result that can either be rendered as
+// colored text for humans or serialized as a stable JSON/YAML document.
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::utilities::print_error_message;
+use crate::{exit_code, Result};
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Colored, human-readable text.
+    #[default]
+    Text,
+    /// Pretty-printed JSON document.
+    Json,
+    /// YAML document.
+    Yaml,
+}
+
+/// Human-readable, colored rendering of an operation result.
+pub trait Render {
+    fn render(&self);
+
+    /// Exit code reported for this result; checks that did not pass
+    /// override this with [`exit_code::MISMATCH`].
+    fn exit_code(&self) -> u8 {
+        exit_code::SUCCESS
+    }
+}
+
+/// Print a result in the requested output format and return its exit code.
+pub fn emit<T: Serialize + Render>(value: &T, format: OutputFormat) -> u8 {
+    let serialized = match format {
+        OutputFormat::Text => {
+            value.render();
+            return value.exit_code();
+        }
+        OutputFormat::Json => serde_json::to_string_pretty(value)
+            .map(|json| json + "\n")
+            .map_err(|e| e.to_string()),
+        OutputFormat::Yaml => serde_yaml::to_string(value).map_err(|e| e.to_string()),
+    };
+
+    match serialized {
+        Ok(document) => {
+            print!("{}", document);
+            value.exit_code()
+        }
+        Err(e) => {
+            print_error_message(&format!("Error: Failed to serialize output: {}\n", e));
+            exit_code::FAILURE
+        }
+    }
+}
+
+/// Print a successful result, or report the error with some context.
+/// Returns the exit code for either outcome.
+pub fn emit_result<T>(result: Result<T>, format: OutputFormat, context: &str) -> u8
+where
+    T: Serialize + Render,
+{
+    match result {
+        Ok(value) => emit(&value, format),
+        Err(e) => {
+            print_error_message(&format!("Error: {}: {}\n", context, e));
+            e.exit_code()
+        }
+    }
+}
@@ -0,0 +1,330 @@
+// repl.rs
+//
+// Interactive shell, opened by `shell` or by running the helper without a
+// command. Each line is parsed like the arguments of a normal invocation;
+// an empty line opens a fuzzy menu of every operation that prompts for the
+// required arguments. Lines are kept in a history file between sessions.
+
+use clap::builder::PossibleValue;
+use clap::{Arg, Command};
+use colored::Colorize;
+use dialoguer::{FuzzySelect, History, Input};
+use log::warn;
+use std::collections::VecDeque;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::script::split_command_line;
+use command_line_helper::utilities::print_error_message;
+
+/// Number of lines kept in the history file.
+const HISTORY_SIZE: usize = 500;
+
+/// What the user asked the shell to do next.
+pub enum Request {
+    /// Run a command line, given without the program name.
+    Run(Vec<String>),
+    /// Change the working directory, like `--goto`.
+    Goto(PathBuf),
+    Exit,
+}
+
+/// A leaf command of the CLI, e.g. `hash file`.
+struct Operation {
+    words: Vec<String>,
+    about: String,
+    args: Vec<Arg>,
+}
+
+pub struct Repl {
+    operations: Vec<Operation>,
+    history: LineHistory,
+}
+
+impl Repl {
+    pub fn new(command: &Command) -> Self {
+        let mut operations = Vec::new();
+        collect_operations(command, &mut Vec::new(), &mut operations);
+
+        Repl {
+            operations,
+            history: LineHistory::load(),
+        }
+    }
+
+    pub fn print_banner(&self) {
+        println!("{}", "Command Line Helper shell".cyan().bold());
+        println!(
+            "Type a command (e.g. {}), press {} for the menu, {} to change directory, {} to quit.",
+            "hash file Cargo.toml".yellow(),
+            "Enter".yellow(),
+            "cd <path>".yellow(),
+            "exit".yellow()
+        );
+    }
+
+    /// Prompt until the user asks for something to do. Input errors, such
+    /// as Ctrl+C or a closed terminal, end the session.
+    pub fn next_request(&mut self) -> Request {
+        loop {
+            let cwd = env::current_dir()
+                .map(|dir| dir.display().to_string())
+                .unwrap_or_default();
+
+            let line = match Input::<String>::new()
+                .with_prompt(format!("clh {}", cwd))
+                .allow_empty(true)
+                .history_with(&mut self.history)
+                .interact_text()
+            {
+                Ok(line) => line,
+                Err(_) => return Request::Exit,
+            };
+
+            let line = line.trim();
+            let words = match line {
+                "" | "menu" => match self.select_operation() {
+                    Ok(Some(words)) => {
+                        self.history.write(&join_command_line(&words));
+                        words
+                    }
+                    Ok(None) => continue,
+                    Err(_) => return Request::Exit,
+                },
+                "exit" | "quit" => return Request::Exit,
+                "history" => {
+                    for (index, entry) in self.history.entries.iter().rev().enumerate() {
+                        println!("{:>4}  {}", index + 1, entry);
+                    }
+                    continue;
+                }
+                _ => match split_command_line(line) {
+                    Ok(words) => words,
+                    Err(e) => {
+                        print_error_message(&format!("Error: {}\n", e));
+                        continue;
+                    }
+                },
+            };
+
+            match words.first().map(String::as_str) {
+                Some("cd") => {
+                    let target = words
+                        .get(1)
+                        .map(PathBuf::from)
+                        .or_else(dirs::home_dir)
+                        .unwrap_or_else(|| PathBuf::from("."));
+                    return Request::Goto(target);
+                }
+                _ => return Request::Run(words),
+            }
+        }
+    }
+
+    /// Pick an operation from the menu and prompt for its required
+    /// arguments. `None` means the user backed out with Esc.
+    fn select_operation(&self) -> dialoguer::Result<Option<Vec<String>>> {
+        let width = self
+            .operations
+            .iter()
+            .map(|operation| operation.words.join(" ").len())
+            .max()
+            .unwrap_or(0);
+        let items: Vec<String> = self
+            .operations
+            .iter()
+            .map(|operation| {
+                format!(
+                    "{:<width$}  {}",
+                    operation.words.join(" "),
+                    operation.about,
+                    width = width
+                )
+            })
+            .collect();
+
+        let Some(index) = FuzzySelect::new()
+            .with_prompt("Operation (Esc to cancel)")
+            .items(&items)
+            .max_length(15)
+            .interact_opt()?
+        else {
+            return Ok(None);
+        };
+
+        let operation = &self.operations[index];
+        let mut words = operation.words.clone();
+        for arg in operation.args.iter().filter(|arg| arg.is_required_set()) {
+            let Some(value) = prompt_argument(arg)? else {
+                return Ok(None);
+            };
+            if let Some(long) = arg.get_long().filter(|_| !arg.is_positional()) {
+                words.push(format!("--{}", long));
+            }
+            words.push(value);
+        }
+
+        Ok(Some(words))
+    }
+}
+
+fn prompt_argument(arg: &Arg) -> dialoguer::Result<Option<String>> {
+    let name = arg.get_id().as_str().replace('_', " ");
+    let prompt = match arg.get_help() {
+        Some(help) => format!("{} ({})", name, help),
+        None => name,
+    };
+
+    let values: Vec<PossibleValue> = arg
+        .get_possible_values()
+        .into_iter()
+        .filter(|value| !value.is_hide_set())
+        .collect();
+    if values.is_empty() {
+        return Input::<String>::new()
+            .with_prompt(prompt)
+            .interact_text()
+            .map(Some);
+    }
+
+    let names: Vec<&str> = values.iter().map(PossibleValue::get_name).collect();
+    Ok(FuzzySelect::new()
+        .with_prompt(prompt)
+        .items(&names)
+        .interact_opt()?
+        .map(|index| names[index].to_string()))
+}
+
+/// Every visible leaf command below `command`, except the shell itself.
+fn collect_operations(command: &Command, words: &mut Vec<String>, operations: &mut Vec<Operation>) {
+    for subcommand in command.get_subcommands() {
+        let name = subcommand.get_name();
+        if subcommand.is_hide_set() || name == "shell" || name == "help" {
+            continue;
+        }
+
+        words.push(name.to_string());
+        if subcommand.has_subcommands() {
+            collect_operations(subcommand, words, operations);
+        } else {
+            operations.push(Operation {
+                words: words.clone(),
+                about: subcommand
+                    .get_about()
+                    .map(|about| about.to_string())
+                    .unwrap_or_default(),
+                args: subcommand.get_arguments().cloned().collect(),
+            });
+        }
+        words.pop();
+    }
+}
+
+/// The inverse of `split_command_line`, for the history file.
+fn join_command_line(words: &[String]) -> String {
+    words
+        .iter()
+        .map(|word| {
+            if !word.is_empty()
+                && !word.contains(|c: char| c.is_whitespace() || "'\"\\".contains(c))
+            {
+                word.clone()
+            } else {
+                format!("'{}'", word.replace('\'', r"'\''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Line history, newest first, saved to the data directory after each line.
+struct LineHistory {
+    entries: VecDeque<String>,
+    path: Option<PathBuf>,
+}
+
+impl LineHistory {
+    fn load() -> Self {
+        let path = dirs::data_dir().map(|dir| dir.join("command-line-helper").join("history"));
+        let entries = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|content| content.lines().rev().map(str::to_string).collect())
+            .unwrap_or_default();
+
+        LineHistory { entries, path }
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut content = String::new();
+        for entry in self.entries.iter().rev() {
+            content.push_str(entry);
+            content.push('\n');
+        }
+        fs::write(path, content)
+    }
+}
+
+impl<T: ToString> History<T> for LineHistory {
+    fn read(&self, pos: usize) -> Option<String> {
+        self.entries.get(pos).cloned()
+    }
+
+    fn write(&mut self, val: &T) {
+        let line = val.to_string();
+        let line = line.trim();
+        if line.is_empty() || self.entries.front().is_some_and(|last| last == line) {
+            return;
+        }
+
+        self.entries.push_front(line.to_string());
+        self.entries.truncate(HISTORY_SIZE);
+        if let Err(e) = self.save() {
+            warn!("Could not save shell history: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_collect_operations() {
+        let mut operations = Vec::new();
+        collect_operations(
+            &crate::cli::Args::command(),
+            &mut Vec::new(),
+            &mut operations,
+        );
+
+        let names: Vec<String> = operations
+            .iter()
+            .map(|operation| operation.words.join(" "))
+            .collect();
+        assert!(names.contains(&"hash file".to_string()));
+        assert!(names.contains(&"format json query".to_string()));
+        assert!(!names.iter().any(|name| name == "shell" || name == "hash"));
+    }
+
+    #[test]
+    fn test_join_command_line_round_trip() {
+        let words: Vec<String> = ["text", "case", "upper", "it's a test", ""]
+            .iter()
+            .map(|word| word.to_string())
+            .collect();
+        assert_eq!(
+            split_command_line(&join_command_line(&words)).unwrap(),
+            words
+        );
+    }
+}
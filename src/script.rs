@@ -0,0 +1,353 @@
+// script.rs
+//
+// Batch mode: a YAML file lists helper commands that `run-script` executes in
+// order, through the same dispatch as the command line.
+//
+//   variables:
+//     out: dist
+//   steps:
+//     - goto: ./my-project
+//     - run: env load .env
+//     - name: Build
+//       run: project build -L cargo
+//     - run: archive zip create target/release ${out}/release.zip
+//       continue_on_error: true
+
+use colored::Colorize;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use command_line_helper::output::Render;
+use command_line_helper::{exit_code, Error, Result};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Script {
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+    /// Keep running the remaining steps after a step fails.
+    #[serde(default)]
+    pub continue_on_error: bool,
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Step {
+    pub name: Option<String>,
+    /// A helper command, without the program name.
+    pub run: Option<StepCommand>,
+    /// Change the working directory for the following steps.
+    pub goto: Option<String>,
+    /// Define variables for the following steps.
+    pub set: Option<HashMap<String, String>>,
+    #[serde(default)]
+    pub continue_on_error: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum StepCommand {
+    /// A command line, split on whitespace; quotes group words.
+    Line(String),
+    /// Already split arguments.
+    Args(Vec<String>),
+}
+
+/// What a step does once its variables have been substituted.
+pub enum Action {
+    Run(Vec<String>),
+    Goto(PathBuf),
+    Set(Vec<(String, String)>),
+}
+
+impl Script {
+    pub fn load(path: &Path) -> Result<Script> {
+        let content = fs::read_to_string(path)?;
+        let script: Script = serde_yaml::from_str(&content)?;
+
+        for (index, step) in script.steps.iter().enumerate() {
+            let actions = [step.run.is_some(), step.goto.is_some(), step.set.is_some()];
+            if actions.iter().filter(|&&present| present).count() != 1 {
+                return Err(Error::InvalidInput(format!(
+                    "Step {}: expected exactly one of `run`, `goto` or `set`",
+                    index + 1
+                )));
+            }
+        }
+
+        Ok(script)
+    }
+}
+
+impl Step {
+    /// Name shown in progress output and in the summary.
+    pub fn label(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        match (&self.run, &self.goto, &self.set) {
+            (Some(StepCommand::Line(line)), _, _) => line.clone(),
+            (Some(StepCommand::Args(args)), _, _) => args.join(" "),
+            (_, Some(path), _) => format!("goto {}", path),
+            (_, _, Some(vars)) => {
+                let mut keys: Vec<_> = vars.keys().map(String::as_str).collect();
+                keys.sort_unstable();
+                format!("set {}", keys.join(", "))
+            }
+            _ => String::new(),
+        }
+    }
+
+    pub fn resolve(&self, variables: &Variables) -> Result<Action> {
+        if let Some(command) = &self.run {
+            let args = match command {
+                StepCommand::Line(line) => split_command_line(line)?,
+                StepCommand::Args(args) => args.clone(),
+            };
+            let args = args
+                .iter()
+                .map(|arg| variables.expand(arg))
+                .collect::<Result<_>>()?;
+            Ok(Action::Run(args))
+        } else if let Some(path) = &self.goto {
+            Ok(Action::Goto(PathBuf::from(variables.expand(path)?)))
+        } else {
+            let mut vars = self
+                .set
+                .iter()
+                .flatten()
+                .map(|(key, value)| Ok((key.clone(), variables.expand(value)?)))
+                .collect::<Result<Vec<_>>>()?;
+            vars.sort();
+            Ok(Action::Set(vars))
+        }
+    }
+}
+
+/// Script variables. `${name}` falls back to the environment, so values
+/// loaded by an `env load` step are visible to the following steps.
+pub struct Variables {
+    values: HashMap<String, String>,
+}
+
+impl Variables {
+    pub fn new(values: HashMap<String, String>) -> Self {
+        Variables { values }
+    }
+
+    pub fn extend(&mut self, values: impl IntoIterator<Item = (String, String)>) {
+        self.values.extend(values);
+    }
+
+    /// Substitute `${name}` references; `$$` is a literal `$`.
+    pub fn expand(&self, text: &str) -> Result<String> {
+        let pattern = Regex::new(r"\$\$|\$\{([^}]*)\}").unwrap();
+        let mut expanded = String::with_capacity(text.len());
+        let mut last = 0;
+
+        for captures in pattern.captures_iter(text) {
+            let whole = captures.get(0).unwrap();
+            expanded.push_str(&text[last..whole.start()]);
+            match captures.get(1) {
+                None => expanded.push('$'),
+                Some(name) => {
+                    let name = name.as_str().trim();
+                    let value = self
+                        .values
+                        .get(name)
+                        .cloned()
+                        .or_else(|| env::var(name).ok())
+                        .ok_or_else(|| Error::NotFound(format!("Undefined variable '{}'", name)))?;
+                    expanded.push_str(&value);
+                }
+            }
+            last = whole.end();
+        }
+
+        expanded.push_str(&text[last..]);
+        Ok(expanded)
+    }
+}
+
+/// Split a command line into arguments. Single and double quotes group
+/// words, and a backslash escapes the next character outside single quotes.
+pub fn split_command_line(line: &str) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') | (None, '\\') => {
+                in_word = true;
+                current.extend(chars.next());
+            }
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                in_word = true;
+                quote = Some(c);
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    args.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+
+    if quote.is_some() {
+        return Err(Error::InvalidInput(format!(
+            "Unterminated quote in `{}`",
+            line
+        )));
+    }
+    if in_word {
+        args.push(current);
+    }
+
+    Ok(args)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepStatus {
+    Ok,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StepOutcome {
+    pub step: usize,
+    pub name: String,
+    pub status: StepStatus,
+    pub exit_code: Option<u8>,
+    pub duration_ms: u128,
+}
+
+impl StepOutcome {
+    pub fn finished(step: usize, name: String, code: u8, duration: Duration) -> Self {
+        StepOutcome {
+            step,
+            name,
+            status: if code == exit_code::SUCCESS {
+                StepStatus::Ok
+            } else {
+                StepStatus::Failed
+            },
+            exit_code: Some(code),
+            duration_ms: duration.as_millis(),
+        }
+    }
+
+    pub fn skipped(step: usize, name: String) -> Self {
+        StepOutcome {
+            step,
+            name,
+            status: StepStatus::Skipped,
+            exit_code: None,
+            duration_ms: 0,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScriptSummary {
+    pub script: PathBuf,
+    pub steps: Vec<StepOutcome>,
+}
+
+impl Render for ScriptSummary {
+    fn render(&self) {
+        println!("\n{}", "Script Summary".cyan().bold());
+        println!("{}", "=".repeat(80).cyan());
+        println!(
+            "{}",
+            format!(
+                "{:>4}  {:<8}  {:>4}  {:>9}  Step",
+                "#", "Status", "Exit", "Time"
+            )
+            .bold()
+        );
+
+        for outcome in &self.steps {
+            let status = format!("{:<8}", format!("{:?}", outcome.status).to_lowercase());
+            let status = match outcome.status {
+                StepStatus::Ok => status.green(),
+                StepStatus::Failed => status.red().bold(),
+                StepStatus::Skipped => status.yellow(),
+            };
+            let code = outcome
+                .exit_code
+                .map(|code| code.to_string())
+                .unwrap_or_else(|| "-".to_string());
+
+            println!(
+                "{:>4}  {}  {:>4}  {:>6} ms  {}",
+                outcome.step, status, code, outcome.duration_ms, outcome.name
+            );
+        }
+    }
+
+    fn exit_code(&self) -> u8 {
+        self.steps
+            .iter()
+            .filter_map(|outcome| outcome.exit_code)
+            .find(|&code| code != exit_code::SUCCESS)
+            .unwrap_or(exit_code::SUCCESS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_command_line() {
+        assert_eq!(
+            split_command_line(r#"git commit "first release" --x='a b' c\ d"#).unwrap(),
+            vec!["git", "commit", "first release", "--x=a b", "c d"]
+        );
+        assert_eq!(
+            split_command_line(r#"text case upper """#).unwrap().len(),
+            4
+        );
+        assert!(split_command_line("text stats 'oops").is_err());
+    }
+
+    #[test]
+    fn test_expand_variables() {
+        let variables = Variables::new(HashMap::from([("out".to_string(), "dist".to_string())]));
+        assert_eq!(
+            variables.expand("${out}/app.zip costs $$5").unwrap(),
+            "dist/app.zip costs $5"
+        );
+        assert!(matches!(
+            variables.expand("${clh_undefined_variable}"),
+            Err(Error::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_step_needs_one_action() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("script.yaml");
+        fs::write(&path, "steps:\n  - run: system info\n    goto: /tmp\n").unwrap();
+
+        assert!(matches!(Script::load(&path), Err(Error::InvalidInput(_))));
+    }
+}
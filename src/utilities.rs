@@ -1,12 +1,13 @@
 use log::info;
 use std::env;
+use std::fs;
 use std::io::{self, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 
 // Helper function to clean language string
-pub fn clean_language_string(language: &String) -> String {
+pub fn clean_language_string(language: &str) -> String {
     let cleaned_language = format!("{:?}", language)
         .to_lowercase()
         .replace("rust", "cargo")
@@ -19,7 +20,7 @@ pub fn clean_language_string(language: &String) -> String {
 }
 
 // Helper function to clean action string
-pub fn clean_action_string(action: &String) -> String {
+pub fn clean_action_string(action: &str) -> String {
     let cleaned_action = format!("{:?}", action).to_lowercase().replace("\"", "");
 
     info!("Cleaned action string: {}", cleaned_action);
@@ -40,6 +41,7 @@ pub fn print_hyperlink(path: &std::path::Path) {
     }
 }
 
+#[allow(dead_code)]
 pub fn get_current_os() -> &'static str {
     let os_info = std::env::consts::OS;
 
@@ -95,18 +97,11 @@ pub fn validate_and_suggest_installation(language: &str) {
 #[allow(dead_code)]
 pub fn get_system_path() -> Option<String> {
     match env::var_os("PATH") {
-        Some(paths) => {
-            if let Some(paths_str) = paths.to_str() {
-                Some(paths_str.to_string())
-            } else {
-                None
-            }
-        }
+        Some(paths) => paths.to_str().map(|paths_str| paths_str.to_string()),
         None => None,
     }
 }
 
-#[allow(dead_code)]
 pub fn load_system_paths() -> Vec<PathBuf> {
     // Retrieve the system PATH
     if let Some(system_path) = env::var_os("PATH") {
@@ -170,6 +165,66 @@ pub fn print_error_message(message: &str) {
     stderr.reset().unwrap();
 }
 
+/// A byte count in binary units, like `512 B` or `1.5 MB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Replace the contents of `path` without ever leaving it half written: the
+/// data goes to a temporary file in the same directory, which takes over
+/// the permissions of an existing file and is then renamed over it.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    write_atomic_with(path, |file| file.write_all(contents))
+}
+
+/// Like [`write_atomic`], with the contents written by `write`, e.g. when
+/// they are streamed from another file. `write` may set other permissions.
+pub fn write_atomic_with<F>(path: &Path, write: F) -> io::Result<()>
+where
+    F: FnOnce(&mut fs::File) -> io::Result<()>,
+{
+    let file_name = path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Not a file path: {}", path.display()),
+        )
+    })?;
+    let dir = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let temp = dir.join(format!(
+        ".{}.clh-{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+
+    let written = (|| {
+        let mut file = fs::File::create(&temp)?;
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        write(&mut file)?;
+        file.sync_all()?;
+        fs::rename(&temp, path)
+    })();
+    if written.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    written
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -179,6 +234,13 @@ mod tests {
         assert_eq!(get_current_os(), std::env::consts::OS);
     }
 
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(5 * 1024 * 1024 * 1024), "5.0 GB");
+    }
+
     #[test]
     fn test_validate_and_suggest_installation() {
         // Test with an installed language